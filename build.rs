@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Generates the lookup tables embedded in the library from the files in `data/`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+#[allow(dead_code)]
+#[path = "src/punycode.rs"]
+mod punycode;
+#[path = "src/psl/trie.rs"]
+mod trie;
+
+
+// https://www.unicode.org/reports/tr46/#IDNA_Mapping_Table
+// Emits `MAPPING_TABLE`, a list of `(first code point, mapping)` sorted by code point.
+// Each entry applies up to the first code point of the next one.
+fn generate_idna_table(out_dir: &Path) {
+    let source: String = fs::read_to_string("data/IdnaMappingTable.txt").unwrap();
+    let mut entries: Vec<(u32, String)> = Vec::new();
+    for line in source.lines() {
+        let line: &str = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(';').map(|field: &str| field.trim()).collect();
+        let start: u32 = u32::from_str_radix(fields[0].split("..").next().unwrap(), 16).unwrap();
+        let mapping: String = fields
+            .get(2)
+            .filter(|mapping: &&&str| !mapping.is_empty())
+            .map(|mapping: &&str| -> String {
+                mapping
+                    .split(' ')
+                    .map(|c: &str| format!("\\u{{{}}}", c.to_ascii_lowercase().trim_start_matches('0')))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mapping: String = match fields[1] {
+            "valid" => "Mapping::Valid".to_string(),
+            "ignored" => "Mapping::Ignored".to_string(),
+            "mapped" => format!("Mapping::Mapped(\"{}\")", mapping),
+            "deviation" => format!("Mapping::Deviation(\"{}\")", mapping),
+            "disallowed" => "Mapping::Disallowed".to_string(),
+            "disallowed_STD3_valid" => "Mapping::DisallowedStd3Valid".to_string(),
+            "disallowed_STD3_mapped" => format!("Mapping::DisallowedStd3Mapped(\"{}\")", mapping),
+            status => panic!("unknown IDNA status: {}", status),
+        };
+        // Adjacent ranges with the same mapping are merged.
+        if entries
+            .last()
+            .map_or(true, |(_, last): &(u32, String)| *last != mapping)
+        {
+            entries.push((start, mapping));
+        }
+    }
+    let mut output: String = String::from("static MAPPING_TABLE: &[(u32, Mapping)] = &[\n");
+    for (start, mapping) in entries {
+        writeln!(output, "    (0x{:x}, {}),", start, mapping).unwrap();
+    }
+    output += "];\n";
+    fs::write(out_dir.join("idna_table.rs"), output).unwrap();
+}
+
+
+// Emits the property tables of `src/idna/properties.rs` from `data/IdnaProperties.txt`:
+// `COMBINING_CLASSES`, `MARKS`, `JOINING_TYPES` and `BIDI_CLASSES`, lists of ranges sorted by code
+// point, `DECOMPOSITIONS`, the full canonical decompositions, and `COMPOSITIONS`, the primary
+// composites keyed by the pair they compose from.
+fn generate_idna_properties(out_dir: &Path) {
+    let source: String = fs::read_to_string("data/IdnaProperties.txt").unwrap();
+    let mut ranges: BTreeMap<&str, Vec<(u32, u32, &str)>> = BTreeMap::new();
+    let mut mappings: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    let mut exclusions: Vec<(u32, u32)> = Vec::new();
+    for line in source.lines() {
+        let line: &str = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(';').map(|field: &str| field.trim()).collect();
+        let (start, end): (&str, &str) = fields[0].split_once("..").unwrap_or((fields[0], fields[0]));
+        let start: u32 = u32::from_str_radix(start, 16).unwrap();
+        let end: u32 = u32::from_str_radix(end, 16).unwrap();
+        match fields[1] {
+            "dm" => {
+                let mapping: Vec<u32> = fields[2]
+                    .split(' ')
+                    .map(|c: &str| u32::from_str_radix(c, 16).unwrap())
+                    .collect();
+                mappings.insert(start, mapping);
+            }
+            "Comp_Ex" => exclusions.push((start, end)),
+            property => {
+                ranges
+                    .entry(property)
+                    .or_default()
+                    .push((start, end, fields[2]))
+            }
+        }
+    }
+
+    fn decompose(c: u32, mappings: &BTreeMap<u32, Vec<u32>>, output: &mut String) {
+        match mappings.get(&c) {
+            Some(mapping) => {
+                for &c in mapping {
+                    decompose(c, mappings, output);
+                }
+            }
+            None => write!(output, "\\u{{{:x}}}", c).unwrap(),
+        }
+    }
+
+    let mut output: String = String::from("static COMBINING_CLASSES: &[(u32, u32, u8)] = &[\n");
+    for &(start, end, value) in &ranges["ccc"] {
+        writeln!(output, "    (0x{:x}, 0x{:x}, {}),", start, end, value).unwrap();
+    }
+    output += "];\nstatic MARKS: &[(u32, u32)] = &[\n";
+    for &(start, end, _) in &ranges["gc"] {
+        writeln!(output, "    (0x{:x}, 0x{:x}),", start, end).unwrap();
+    }
+    output += "];\n";
+    for (property, name, ty) in [
+        ("jt", "JOINING_TYPES", "JoiningType"),
+        ("bc", "BIDI_CLASSES", "BidiClass"),
+    ] {
+        writeln!(output, "static {}: &[(u32, u32, {})] = &[", name, ty).unwrap();
+        for &(start, end, value) in &ranges[property] {
+            writeln!(
+                output,
+                "    (0x{:x}, 0x{:x}, {}::{}),",
+                start, end, ty, value
+            )
+            .unwrap();
+        }
+        output += "];\n";
+    }
+    output += "static DECOMPOSITIONS: &[(u32, &str)] = &[\n";
+    for &c in mappings.keys() {
+        let mut decomposition: String = String::new();
+        decompose(c, &mappings, &mut decomposition);
+        writeln!(output, "    (0x{:x}, \"{}\"),", c, decomposition).unwrap();
+    }
+    output += "];\n";
+    let mut compositions: Vec<((u32, u32), u32)> = mappings
+        .iter()
+        .filter(|(c, _): &(&u32, &Vec<u32>)| {
+            !exclusions
+                .iter()
+                .any(|&(start, end): &(u32, u32)| (start..=end).contains(*c))
+        })
+        .map(|(&c, mapping): (&u32, &Vec<u32>)| ((mapping[0], mapping[1]), c))
+        .collect();
+    compositions.sort();
+    output += "static COMPOSITIONS: &[((u32, u32), u32)] = &[\n";
+    for ((first, second), composite) in compositions {
+        writeln!(
+            output,
+            "    ((0x{:x}, 0x{:x}), 0x{:x}),",
+            first, second, composite
+        )
+        .unwrap();
+    }
+    output += "];\n";
+    fs::write(out_dir.join("idna_properties.rs"), output).unwrap();
+}
+
+
+// https://publicsuffix.org/list/
+// Emits `NODES`, the rule trie of `data/public_suffix_list.dat` built by `src/psl/trie.rs`.
+fn generate_psl_trie(out_dir: &Path) {
+    let source: String = fs::read_to_string("data/public_suffix_list.dat").unwrap();
+    let mut output: String = String::from("static NODES: &[Node] = &[\n");
+    for node in trie::build(&source) {
+        let rule: String = match node.rule {
+            Some(rule) => {
+                format!(
+                    "Some(Rule {{ is_exception: {}, is_private: {} }})",
+                    rule.is_exception, rule.is_private,
+                )
+            }
+            None => "None".to_string(),
+        };
+        writeln!(
+            output,
+            "    Node {{ label: Cow::Borrowed({:?}), first_child: {}, child_count: {}, rule: {} }},",
+            node.label, node.first_child, node.child_count, rule,
+        )
+        .unwrap();
+    }
+    output += "];\n";
+    fs::write(out_dir.join("psl_trie.rs"), output).unwrap();
+}
+
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=data/IdnaMappingTable.txt");
+    println!("cargo:rerun-if-changed=data/IdnaProperties.txt");
+    println!("cargo:rerun-if-changed=data/public_suffix_list.dat");
+    println!("cargo:rerun-if-changed=src/psl/trie.rs");
+    println!("cargo:rerun-if-changed=src/punycode.rs");
+    let out_dir: PathBuf = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    generate_idna_table(&out_dir);
+    generate_idna_properties(&out_dir);
+    generate_psl_trie(&out_dir);
+}
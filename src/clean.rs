@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Removes tracking parameters from URLs and unwraps redirectors, following rules in the ClearURLs
+// format. https://github.com/ClearURLs/Rules
+// The default rules, in `data/clearurls.json`, cover common trackers. The full ClearURLs list can
+// be loaded with `Rules::from_file`. Patterns use `regex` crate syntax and match
+// case-insensitively.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::{fs, io};
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+use crate::percent_encoding::percent_decode_str;
+use crate::url::URL;
+
+
+static BUILTIN: OnceLock<Rules> = OnceLock::new();
+
+// Redirectors may point to other redirectors. Cleaning stops after this many, which also breaks
+// cycles.
+const MAX_REDIRECTIONS: usize = 8;
+
+
+#[derive(Debug)]
+pub enum RulesError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    // A pattern that does not compile, with the name of its provider.
+    InvalidRegex { provider: String, pattern: String },
+}
+
+impl Display for RulesError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(fmt, "cannot read rules: {}", err),
+            Self::Json(err) => write!(fmt, "invalid rules: {}", err),
+            Self::InvalidRegex { provider, pattern } => write!(fmt, "invalid regex in {}: {}", provider, pattern),
+        }
+    }
+}
+
+impl Error for RulesError {}
+
+
+#[derive(Deserialize)]
+struct RawRules {
+    providers: BTreeMap<String, RawProvider>,
+}
+
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawProvider {
+    url_pattern: String,
+    #[serde(default)]
+    complete_provider: bool,
+    #[serde(default)]
+    rules: Vec<String>,
+    #[serde(default)]
+    referral_marketing: Vec<String>,
+    #[serde(default)]
+    raw_rules: Vec<String>,
+    #[serde(default)]
+    exceptions: Vec<String>,
+    #[serde(default)]
+    redirections: Vec<String>,
+}
+
+
+#[derive(Clone, Debug)]
+struct Provider {
+    url_pattern: Regex,
+    // Blocks every matching URL.
+    complete_provider: bool,
+    // Match whole parameter names, including the referral marketing ones.
+    rules: Vec<Regex>,
+    // Removed from the serialized URL.
+    raw_rules: Vec<Regex>,
+    exceptions: Vec<Regex>,
+    // The first capture group is the percent-encoded target.
+    redirections: Vec<Regex>,
+}
+
+impl Provider {
+    fn compile(name: &str, raw: RawProvider) -> Result<Self, RulesError> {
+        let compile = |pattern: &str| -> Result<Regex, RulesError> {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|_| {
+                    RulesError::InvalidRegex {
+                        provider: name.to_string(),
+                        pattern: pattern.to_string(),
+                    }
+                })
+        };
+        let compile_all = |patterns: &[String]| -> Result<Vec<Regex>, RulesError> {
+            patterns
+                .iter()
+                .map(|pattern: &String| compile(pattern))
+                .collect()
+        };
+        Ok(Self {
+            url_pattern: compile(&raw.url_pattern)?,
+            complete_provider: raw.complete_provider,
+            rules: raw
+                .rules
+                .iter()
+                .chain(&raw.referral_marketing)
+                .map(|rule: &String| compile(&format!("^(?:{})$", rule)))
+                .collect::<Result<Vec<Regex>, RulesError>>()?,
+            raw_rules: compile_all(&raw.raw_rules)?,
+            exceptions: compile_all(&raw.exceptions)?,
+            redirections: compile_all(&raw.redirections)?,
+        })
+    }
+
+    fn matches(&self, url: &URL) -> bool {
+        self.url_pattern.is_match(url.href())
+            && !self
+                .exceptions
+                .iter()
+                .any(|exception: &Regex| exception.is_match(url.href()))
+    }
+
+    fn redirection(&self, url: &URL) -> Option<URL> {
+        self.redirections.iter().find_map(|redirection: &Regex| {
+            let target: &str = redirection.captures(url.href())?.get(1)?.as_str();
+            URL::parse(&percent_decode_str(target)).ok()
+        })
+    }
+
+    // Applies the raw rules, then drops the query and fragment parameters named by the rules.
+    // The other parameters are kept as they are.
+    fn strip(&self, url: &URL) -> URL {
+        let mut url: URL = url.clone();
+        for raw_rule in &self.raw_rules {
+            if let Ok(stripped) = URL::parse(&raw_rule.replace_all(url.href(), "")) {
+                url = stripped;
+            }
+        }
+        if let Some(query) = url.query() {
+            if let Some(query) = self.strip_params(query) {
+                url.set_search(&query);
+            }
+        }
+        if let Some(fragment) = url.fragment() {
+            if let Some(fragment) = self.strip_params(fragment) {
+                url.set_hash(&fragment);
+            }
+        }
+        url
+    }
+
+    // `None` if no parameter is removed.
+    fn strip_params(&self, params: &str) -> Option<String> {
+        let pairs: Vec<&str> = params
+            .split('&')
+            .filter(|pair: &&str| {
+                let name: String = percent_decode_str(&pair.split('=').next().unwrap().replace('+', " "));
+                !self.rules.iter().any(|rule: &Regex| rule.is_match(&name))
+            })
+            .collect();
+        if pairs.len() == params.split('&').count() {
+            return None;
+        }
+        Some(pairs.join("&"))
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct Rules {
+    providers: Vec<Provider>,
+}
+
+impl Rules {
+    // The rules in `data/clearurls.json`.
+    pub fn builtin() -> &'static Self {
+        BUILTIN.get_or_init(|| Self::parse(include_str!("../data/clearurls.json")).unwrap())
+    }
+
+    // Parses rules in the ClearURLs JSON format.
+    pub fn parse(source: &str) -> Result<Self, RulesError> {
+        let raw: RawRules = serde_json::from_str(source).map_err(RulesError::Json)?;
+        let providers: Vec<Provider> = raw
+            .providers
+            .into_iter()
+            .map(|(name, provider): (String, RawProvider)| Provider::compile(&name, provider))
+            .collect::<Result<Vec<Provider>, RulesError>>()?;
+        Ok(Self { providers })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, RulesError> {
+        Self::parse(&fs::read_to_string(path).map_err(RulesError::Io)?)
+    }
+
+    // Unwraps redirectors and removes tracking parameters.
+    // `None` if a complete provider blocks the URL.
+    pub fn clean(&self, url: &URL) -> Option<URL> {
+        let mut url: URL = url.clone();
+        'redirect: for _ in 0..=MAX_REDIRECTIONS {
+            for provider in &self.providers {
+                if !provider.matches(&url) {
+                    continue;
+                }
+                if let Some(target) = provider.redirection(&url) {
+                    url = target;
+                    continue 'redirect;
+                }
+                if provider.complete_provider {
+                    return None;
+                }
+                url = provider.strip(&url);
+            }
+            break;
+        }
+        Some(url)
+    }
+}
+
+
+// Cleans with `Rules::builtin()`.
+pub fn clean(url: &URL) -> Option<URL> {
+    Rules::builtin().clean(url)
+}
@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis
+// The request URL stands in for the request-host and request-path.
+
+use crate::url::{Host, URL};
+
+
+// Where a cookie is sent, as decided by its `Domain` attribute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CookieDomain {
+    // Only sent to this exact host.
+    HostOnly(String),
+    // Sent to this domain and its subdomains.
+    Domain(String),
+}
+
+
+fn request_host(url: &URL) -> Option<&Host> {
+    url.host().filter(|host: &&Host| !host.is_empty())
+}
+
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.1.3
+// Whether the URL's host domain-matches `domain`, a canonicalized cookie domain.
+pub fn domain_match(url: &URL, domain: &str) -> bool {
+    let Some(host) = request_host(url) else {
+        return false;
+    };
+    let domain: String = domain.to_ascii_lowercase();
+    match host {
+        Host::Domain(host) => {
+            host == &domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|prefix: &str| prefix.ends_with('.'))
+        }
+        host => host.to_string() == domain,
+    }
+}
+
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.1.4
+// The path used when a cookie has no `Path` attribute, e.g. `/a` for `https://example.com/a/b`.
+pub fn default_path(url: &URL) -> String {
+    let path: String = url.path();
+    if !path.starts_with('/') {
+        return "/".to_string();
+    }
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => path[..end].to_string(),
+    }
+}
+
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.1.4
+// Whether the URL's path path-matches `cookie_path`.
+pub fn path_match(url: &URL, cookie_path: &str) -> bool {
+    let path: String = url.path();
+    match path.strip_prefix(cookie_path) {
+        Some(rest) => rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.7
+// Applies a `Domain` attribute value set by a response to the URL. Returns `None` if the cookie
+// must be ignored: the domain does not cover the URL's host, or it is a public suffix other than
+// the host itself. An empty value makes the cookie host-only.
+pub fn domain_attribute(url: &URL, value: &str) -> Option<CookieDomain> {
+    let host: &Host = request_host(url)?;
+    let value: &str = value.strip_prefix('.').unwrap_or(value);
+    if value.is_empty() {
+        return Some(CookieDomain::HostOnly(host.to_string()));
+    }
+    let domain: Host = Host::parse(value).ok()?;
+    if domain.is_public_suffix() {
+        return if &domain == host {
+            Some(CookieDomain::HostOnly(host.to_string()))
+        } else {
+            None
+        };
+    }
+    let domain: String = domain.to_string();
+    if !domain_match(url, &domain) {
+        return None;
+    }
+    Some(CookieDomain::Domain(domain))
+}
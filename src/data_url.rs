@@ -0,0 +1,274 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://fetch.spec.whatwg.org/#data-urls
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::percent_encoding::percent_decode;
+use crate::url::URL;
+
+
+// https://mimesniff.spec.whatwg.org/#http-token-code-point
+fn is_token_code_point(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+
+// https://mimesniff.spec.whatwg.org/#http-quoted-string-token-code-point
+fn is_quoted_string_token_code_point(c: char) -> bool {
+    matches!(c, '\t' | ' '..='~' | '\u{80}'..='\u{ff}')
+}
+
+
+// https://fetch.spec.whatwg.org/#http-whitespace
+fn is_http_whitespace(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | ' ')
+}
+
+
+// https://fetch.spec.whatwg.org/#collect-an-http-quoted-string
+// `input` starts with `"`. Returns the extracted value and the rest of the input.
+fn collect_quoted_string(input: &str) -> (String, &str) {
+    let mut value: String = String::new();
+    let mut chars = input[1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => {
+                match chars.next() {
+                    Some(c) => value.push(c),
+                    None => {
+                        value.push('\\');
+                        break;
+                    }
+                }
+            }
+            _ => value.push(c),
+        }
+    }
+    (value, chars.as_str())
+}
+
+
+// https://mimesniff.spec.whatwg.org/#mime-type-representation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MIMEType {
+    type_: String,
+    subtype: String,
+    parameters: Vec<(String, String)>,
+}
+
+impl MIMEType {
+    // https://mimesniff.spec.whatwg.org/#parse-a-mime-type
+    pub fn parse(input: &str) -> Option<Self> {
+        let input: &str = input.trim_matches(is_http_whitespace);
+        let (type_, rest) = input.split_once('/')?;
+        let (subtype, mut rest) = match rest.split_once(';') {
+            Some((subtype, rest)) => (subtype, Some(rest)),
+            None => (rest, None),
+        };
+        let subtype: &str = subtype.trim_end_matches(is_http_whitespace);
+        if type_.is_empty() || !type_.chars().all(is_token_code_point) {
+            return None;
+        }
+        if subtype.is_empty() || !subtype.chars().all(is_token_code_point) {
+            return None;
+        }
+        let mut mime_type: Self = Self {
+            type_: type_.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            parameters: Vec::new(),
+        };
+        // `rest` is the input after a `;`, or `None` once the end is reached.
+        while let Some(parameter) = rest {
+            let parameter: &str = parameter.trim_start_matches(is_http_whitespace);
+            let end: usize = parameter.find([';', '=']).unwrap_or(parameter.len());
+            let name: String = parameter[..end].to_ascii_lowercase();
+            let parameter: &str = match parameter[end..].chars().next() {
+                Some(';') => {
+                    rest = Some(&parameter[end + 1..]);
+                    continue;
+                }
+                Some(_) if end + 1 < parameter.len() => &parameter[end + 1..],
+                _ => break,
+            };
+            let value: String = if parameter.starts_with('"') {
+                let (value, after) = collect_quoted_string(parameter);
+                rest = after.split_once(';').map(|(_, rest): (&str, &str)| rest);
+                value
+            } else {
+                let (value, after) = match parameter.split_once(';') {
+                    Some((value, after)) => (value, Some(after)),
+                    None => (parameter, None),
+                };
+                rest = after;
+                let value: &str = value.trim_end_matches(is_http_whitespace);
+                if value.is_empty() {
+                    continue;
+                }
+                value.to_string()
+            };
+            if !name.is_empty()
+                && name.chars().all(is_token_code_point)
+                && value.chars().all(is_quoted_string_token_code_point)
+                && mime_type.parameter(&name).is_none()
+            {
+                mime_type.parameters.push((name, value));
+            }
+        }
+        Some(mime_type)
+    }
+
+    pub fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    // https://mimesniff.spec.whatwg.org/#mime-type-essence
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.type_, self.subtype)
+    }
+
+    // In the order they appeared in the input. Names are lowercased; values are kept as is.
+    pub fn parameters(&self) -> &[(String, String)] {
+        &self.parameters
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(key, _): &&(String, String)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value): &(String, String)| value.as_str())
+    }
+}
+
+// https://mimesniff.spec.whatwg.org/#serialize-a-mime-type
+impl Display for MIMEType {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}/{}", self.type_, self.subtype)?;
+        for (name, value) in &self.parameters {
+            if !value.is_empty() && value.chars().all(is_token_code_point) {
+                write!(fmt, ";{}={}", name, value)?;
+            } else {
+                write!(
+                    fmt,
+                    ";{}=\"{}\"",
+                    name,
+                    value.replace('\\', "\\\\").replace('"', "\\\"")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// https://infra.spec.whatwg.org/#forgiving-base64-decode
+pub fn forgiving_base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut data: Vec<u8> = input
+        .bytes()
+        .filter(|b: &u8| !matches!(b, b'\t' | b'\n' | b'\x0c' | b'\r' | b' '))
+        .collect();
+    if data.len() % 4 == 0 {
+        for _ in 0..2 {
+            if data.last() == Some(&b'=') {
+                data.pop();
+            }
+        }
+    }
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let mut output: Vec<u8> = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits: u32 = 0;
+    for byte in data {
+        let value: u8 = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(output)
+}
+
+
+// https://fetch.spec.whatwg.org/#data-url-struct
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataURL {
+    mime_type: MIMEType,
+    body: Vec<u8>,
+}
+
+impl DataURL {
+    // https://fetch.spec.whatwg.org/#data-url-processor
+    // Returns `None` if the scheme is not `data`, there is no `,` or the body is not valid base64.
+    pub fn process(url: &URL) -> Option<Self> {
+        if url.scheme() != "data" {
+            return None;
+        }
+        let href: &str = url.href();
+        let input: &str = match url.fragment() {
+            Some(fragment) => &href[..href.len() - fragment.len() - 1],
+            None => href,
+        };
+        let (mime_type, body) = input["data:".len()..].split_once(',')?;
+        let mut mime_type: &str = mime_type.trim_matches(|c: char| c.is_ascii_whitespace());
+        let mut body: Vec<u8> = percent_decode(body.as_bytes());
+        if let Some(rest) = strip_base64_suffix(mime_type) {
+            mime_type = rest;
+            // Isomorphic decode.
+            let body_str: String = body.iter().map(|&b: &u8| b as char).collect();
+            body = forgiving_base64_decode(&body_str)?;
+        }
+        let mime_type: Option<MIMEType> = if mime_type.starts_with(';') {
+            MIMEType::parse(&format!("text/plain{}", mime_type))
+        } else {
+            MIMEType::parse(mime_type)
+        };
+        let mime_type: MIMEType = mime_type.unwrap_or_else(|| {
+            MIMEType {
+                type_: "text".to_string(),
+                subtype: "plain".to_string(),
+                parameters: vec![("charset".to_string(), "US-ASCII".to_string())],
+            }
+        });
+        Some(Self { mime_type, body })
+    }
+
+    pub fn mime_type(&self) -> &MIMEType {
+        &self.mime_type
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+
+// Strips `;`, any spaces and a case-insensitive `base64` from the end.
+fn strip_base64_suffix(mime_type: &str) -> Option<&str> {
+    let split: usize = mime_type.len().checked_sub(6)?;
+    if !mime_type.is_char_boundary(split) || !mime_type[split..].eq_ignore_ascii_case("base64") {
+        return None;
+    }
+    mime_type[..split].trim_end_matches(' ').strip_suffix(';')
+}
@@ -0,0 +1,304 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://www.unicode.org/reports/tr46/
+// The mapping table is generated from `data/IdnaMappingTable.txt` (Unicode 13.0.0) by the build
+// script, and the properties used by normalization, CheckJoiners and CheckBidi from
+// `data/IdnaProperties.txt` (Unicode 14.0.0).
+
+mod properties;
+
+use properties::{bidi_class, combining_class, joining_type, BidiClass, JoiningType};
+
+use crate::punycode;
+
+
+const ACE_PREFIX: &str = "xn--";
+
+const VIRAMA: u8 = 9;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mapping {
+    Valid,
+    Ignored,
+    Mapped(&'static str),
+    Deviation(&'static str),
+    Disallowed,
+    DisallowedStd3Valid,
+    DisallowedStd3Mapped(&'static str),
+}
+
+include!(concat!(env!("OUT_DIR"), "/idna_table.rs"));
+
+
+fn lookup(c: char) -> Mapping {
+    let index: usize = match MAPPING_TABLE.binary_search_by_key(&(c as u32), |&(start, _): &(u32, Mapping)| start) {
+        Ok(index) => index,
+        Err(index) => index - 1,
+    };
+    MAPPING_TABLE[index].1
+}
+
+
+fn has_ace_prefix(label: &str) -> bool {
+    label.len() >= ACE_PREFIX.len() && label.as_bytes()[..ACE_PREFIX.len()].eq_ignore_ascii_case(ACE_PREFIX.as_bytes())
+}
+
+
+// https://www.unicode.org/reports/tr15/#Norm_Forms
+fn nfc(input: &str) -> String {
+    let mut chars: Vec<char> = Vec::with_capacity(input.len());
+    for c in input.chars() {
+        properties::decompose(c, &mut chars);
+    }
+    // Canonical ordering: each run of non-starters is sorted by combining class.
+    let mut start: usize = 0;
+    while start < chars.len() {
+        if combining_class(chars[start]) == 0 {
+            start += 1;
+            continue;
+        }
+        let end: usize = chars[start..]
+            .iter()
+            .position(|&c: &char| combining_class(c) == 0)
+            .map_or(chars.len(), |length: usize| start + length);
+        chars[start..end].sort_by_key(|&c: &char| combining_class(c));
+        start = end;
+    }
+    // Canonical composition: a character composes with the last starter unless a character left in
+    // between has a combining class not lower than its own.
+    let mut composed: Vec<char> = Vec::with_capacity(chars.len());
+    let mut starter: Option<usize> = None;
+    let mut last_class: Option<u8> = None;
+    for c in chars {
+        let class: u8 = combining_class(c);
+        if let Some(index) = starter {
+            if last_class.map_or(true, |last: u8| last < class) {
+                if let Some(composite) = properties::compose(composed[index], c) {
+                    composed[index] = composite;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(composed.len());
+            last_class = None;
+        } else {
+            last_class = Some(class);
+        }
+        composed.push(c);
+    }
+    composed.into_iter().collect()
+}
+
+
+// Whether the first character other than `T` in `chars` has one of the joining types `expected`.
+fn joins<'a>(mut chars: impl Iterator<Item = &'a char>, expected: [JoiningType; 2]) -> bool {
+    chars
+        .find(|&&c: &&char| joining_type(c) != JoiningType::T)
+        .is_some_and(|&c: &char| expected.contains(&joining_type(c)))
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc5892#appendix-A.1
+// https://www.rfc-editor.org/rfc/rfc5892#appendix-A.2
+fn check_joiners(label: &str) -> bool {
+    let chars: Vec<char> = label.chars().collect();
+    chars.iter().enumerate().all(|(i, &c): (usize, &char)| {
+        if c != '\u{200c}' && c != '\u{200d}' || i > 0 && combining_class(chars[i - 1]) == VIRAMA {
+            return true;
+        }
+        c == '\u{200c}'
+            && joins(chars[..i].iter().rev(), [JoiningType::L, JoiningType::D])
+            && joins(chars[i + 1..].iter(), [JoiningType::R, JoiningType::D])
+    })
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc5893#section-1.4
+fn is_bidi_domain(labels: &[String]) -> bool {
+    labels.iter().any(|label: &String| {
+        label
+            .chars()
+            .any(|c: char| matches!(bidi_class(c), BidiClass::R | BidiClass::AL | BidiClass::AN))
+    })
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc5893#section-2
+fn check_bidi(label: &str) -> bool {
+    use BidiClass::*;
+
+    let classes: Vec<BidiClass> = label.chars().map(bidi_class).collect();
+    let last: Option<&BidiClass> = classes
+        .iter()
+        .rev()
+        .find(|&&class: &&BidiClass| class != NSM);
+    match classes.first() {
+        Some(R | AL) => {
+            classes
+                .iter()
+                .all(|class: &BidiClass| matches!(class, R | AL | AN | EN | ES | CS | ET | ON | BN | NSM))
+                && matches!(last, Some(R | AL | EN | AN))
+                && !(classes.contains(&EN) && classes.contains(&AN))
+        }
+        Some(L) => {
+            classes
+                .iter()
+                .all(|class: &BidiClass| matches!(class, L | EN | ES | CS | ET | ON | BN | NSM))
+                && matches!(last, Some(L | EN))
+        }
+        Some(_) => false,
+        None => true,
+    }
+}
+
+
+// The UTS #46 processing options. `Config::default()` is the profile used by the URL Standard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub use_std3_ascii_rules: bool,
+    pub transitional_processing: bool,
+    pub check_hyphens: bool,
+    pub check_bidi: bool,
+    pub check_joiners: bool,
+    pub verify_dns_length: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            use_std3_ascii_rules: false,
+            transitional_processing: false,
+            check_hyphens: false,
+            check_bidi: true,
+            check_joiners: true,
+            verify_dns_length: false,
+        }
+    }
+}
+
+impl Config {
+    // https://www.unicode.org/reports/tr46/#Validity_Criteria
+    fn is_valid_label(&self, label: &str, transitional: bool) -> bool {
+        if nfc(label) != label {
+            return false;
+        }
+        if self.check_hyphens && (label.get(2..4) == Some("--") || label.starts_with('-') || label.ends_with('-')) {
+            return false;
+        }
+        if label.chars().next().is_some_and(properties::is_mark) {
+            return false;
+        }
+        if self.check_joiners && !check_joiners(label) {
+            return false;
+        }
+        label.chars().all(|c: char| {
+            match lookup(c) {
+                Mapping::Valid => c != '.',
+                Mapping::Deviation(_) => !transitional,
+                Mapping::DisallowedStd3Valid => !self.use_std3_ascii_rules,
+                _ => false,
+            }
+        })
+    }
+
+    // https://www.unicode.org/reports/tr46/#Processing
+    // Returns the processed domain and whether an error was recorded.
+    fn process(&self, domain: &str) -> (String, bool) {
+        let mut errors: bool = false;
+        let mut mapped: String = String::with_capacity(domain.len());
+        for c in domain.chars() {
+            match lookup(c) {
+                Mapping::Valid => mapped.push(c),
+                Mapping::Ignored => (),
+                Mapping::Mapped(mapping) => mapped += mapping,
+                Mapping::Deviation(mapping) if self.transitional_processing => mapped += mapping,
+                Mapping::Deviation(_) => mapped.push(c),
+                Mapping::Disallowed => {
+                    errors = true;
+                    mapped.push(c);
+                }
+                Mapping::DisallowedStd3Valid => {
+                    errors |= self.use_std3_ascii_rules;
+                    mapped.push(c);
+                }
+                Mapping::DisallowedStd3Mapped(mapping) => {
+                    errors |= self.use_std3_ascii_rules;
+                    mapped += mapping;
+                }
+            }
+        }
+        let normalized: String = nfc(&mapped);
+        let mut labels: Vec<String> = Vec::new();
+        for label in normalized.split('.') {
+            if has_ace_prefix(label) {
+                match punycode::decode(&label[ACE_PREFIX.len()..]) {
+                    Some(decoded) if !decoded.is_ascii() && self.is_valid_label(&decoded, false) => {
+                        labels.push(decoded);
+                        continue;
+                    }
+                    _ => errors = true,
+                }
+            } else if !self.is_valid_label(label, self.transitional_processing) {
+                errors = true;
+            }
+            labels.push(label.to_string());
+        }
+        if self.check_bidi && is_bidi_domain(&labels) {
+            errors |= !labels
+                .iter()
+                .filter(|label: &&String| !label.is_empty())
+                .all(|label: &String| check_bidi(label));
+        }
+        (labels.join("."), errors)
+    }
+
+    // https://www.unicode.org/reports/tr46/#ToASCII
+    pub fn to_ascii(&self, domain: &str) -> Option<String> {
+        let (processed, errors) = self.process(domain);
+        if errors {
+            return None;
+        }
+        let mut labels: Vec<String> = Vec::new();
+        for label in processed.split('.') {
+            if label.is_ascii() {
+                labels.push(label.to_string());
+            } else {
+                labels.push(format!("{}{}", ACE_PREFIX, punycode::encode(label)?));
+            }
+        }
+        let ascii: String = labels.join(".");
+        if self.verify_dns_length {
+            let domain: &str = ascii.strip_suffix('.').unwrap_or(&ascii);
+            if domain.is_empty()
+                || domain.len() > 253
+                || domain
+                    .split('.')
+                    .any(|l: &str| l.is_empty() || l.len() > 63)
+            {
+                return None;
+            }
+        }
+        Some(ascii)
+    }
+
+    // https://www.unicode.org/reports/tr46/#ToUnicode
+    // Returns the converted domain and whether an error was recorded.
+    pub fn to_unicode(&self, domain: &str) -> (String, bool) {
+        self.process(domain)
+    }
+}
+
+
+// https://url.spec.whatwg.org/#concept-domain-to-ascii
+pub fn domain_to_ascii(domain: &str) -> Option<String> {
+    Config::default().to_ascii(domain)
+}
+
+
+// https://url.spec.whatwg.org/#concept-domain-to-unicode
+pub fn domain_to_unicode(domain: &str) -> String {
+    Config::default().to_unicode(domain).0
+}
@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// The character properties generated from `data/IdnaProperties.txt` (Unicode 14.0.0) by the build
+// script.
+
+use std::cmp::Ordering;
+
+
+// https://www.unicode.org/reports/tr44/#Joining_Type
+// `U` (non-joining) is not listed in the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum JoiningType {
+    C,
+    D,
+    L,
+    R,
+    T,
+    U,
+}
+
+
+// https://www.unicode.org/reports/tr9/#Bidirectional_Character_Types
+// `L` is not listed in the table.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum BidiClass {
+    L,
+    R,
+    AL,
+    EN,
+    ES,
+    ET,
+    AN,
+    CS,
+    NSM,
+    BN,
+    B,
+    S,
+    WS,
+    ON,
+    LRE,
+    LRO,
+    RLE,
+    RLO,
+    PDF,
+    LRI,
+    RLI,
+    FSI,
+    PDI,
+}
+
+include!(concat!(env!("OUT_DIR"), "/idna_properties.rs"));
+
+
+const HANGUL_S_BASE: u32 = 0xac00;
+const HANGUL_L_BASE: u32 = 0x1100;
+const HANGUL_V_BASE: u32 = 0x1161;
+const HANGUL_T_BASE: u32 = 0x11a7;
+const HANGUL_L_COUNT: u32 = 19;
+const HANGUL_V_COUNT: u32 = 21;
+const HANGUL_T_COUNT: u32 = 28;
+const HANGUL_N_COUNT: u32 = HANGUL_V_COUNT * HANGUL_T_COUNT;
+const HANGUL_S_COUNT: u32 = HANGUL_L_COUNT * HANGUL_N_COUNT;
+
+
+fn compare_range(start: u32, end: u32, c: u32) -> Ordering {
+    if end < c {
+        Ordering::Less
+    } else if start > c {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+
+fn find_range<T: Copy>(table: &[(u32, u32, T)], c: char) -> Option<T> {
+    let c: u32 = c as u32;
+    table
+        .binary_search_by(|&(start, end, _): &(u32, u32, T)| compare_range(start, end, c))
+        .ok()
+        .map(|index: usize| table[index].2)
+}
+
+
+pub(super) fn combining_class(c: char) -> u8 {
+    find_range(COMBINING_CLASSES, c).unwrap_or(0)
+}
+
+
+pub(super) fn is_mark(c: char) -> bool {
+    let c: u32 = c as u32;
+    MARKS
+        .binary_search_by(|&(start, end): &(u32, u32)| compare_range(start, end, c))
+        .is_ok()
+}
+
+
+pub(super) fn joining_type(c: char) -> JoiningType {
+    find_range(JOINING_TYPES, c).unwrap_or(JoiningType::U)
+}
+
+
+pub(super) fn bidi_class(c: char) -> BidiClass {
+    find_range(BIDI_CLASSES, c).unwrap_or(BidiClass::L)
+}
+
+
+// https://www.unicode.org/versions/latest/ch03.pdf#G56669
+// Appends the full canonical decomposition of `c` to `output`.
+pub(super) fn decompose(c: char, output: &mut Vec<char>) {
+    let s_index: u32 = (c as u32).wrapping_sub(HANGUL_S_BASE);
+    if s_index < HANGUL_S_COUNT {
+        output.push(char::from_u32(HANGUL_L_BASE + s_index / HANGUL_N_COUNT).unwrap());
+        output.push(char::from_u32(HANGUL_V_BASE + (s_index % HANGUL_N_COUNT) / HANGUL_T_COUNT).unwrap());
+        if s_index % HANGUL_T_COUNT != 0 {
+            output.push(char::from_u32(HANGUL_T_BASE + s_index % HANGUL_T_COUNT).unwrap());
+        }
+        return;
+    }
+    match DECOMPOSITIONS.binary_search_by_key(&(c as u32), |&(c, _): &(u32, &str)| c) {
+        Ok(index) => output.extend(DECOMPOSITIONS[index].1.chars()),
+        Err(_) => output.push(c),
+    }
+}
+
+
+// The primary composite of `first` and `second`, if any.
+pub(super) fn compose(first: char, second: char) -> Option<char> {
+    let (first, second): (u32, u32) = (first as u32, second as u32);
+    let l_index: u32 = first.wrapping_sub(HANGUL_L_BASE);
+    let v_index: u32 = second.wrapping_sub(HANGUL_V_BASE);
+    if l_index < HANGUL_L_COUNT && v_index < HANGUL_V_COUNT {
+        return char::from_u32(HANGUL_S_BASE + (l_index * HANGUL_V_COUNT + v_index) * HANGUL_T_COUNT);
+    }
+    let s_index: u32 = first.wrapping_sub(HANGUL_S_BASE);
+    let t_index: u32 = second.wrapping_sub(HANGUL_T_BASE);
+    if s_index < HANGUL_S_COUNT && s_index % HANGUL_T_COUNT == 0 && 0 < t_index && t_index < HANGUL_T_COUNT {
+        return char::from_u32(first + t_index);
+    }
+    COMPOSITIONS
+        .binary_search_by_key(&(first, second), |&(pair, _): &((u32, u32), u32)| pair)
+        .ok()
+        .and_then(|index: usize| char::from_u32(COMPOSITIONS[index].1))
+}
@@ -1,7 +1,21 @@
 // SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
 // SPDX-License-Identifier: Apache-2.0
 
-pub use url_parser_derive::QueryParams;
-pub use url_parser_trait::QueryParams;
+pub use url_parser_derive::{PathParams, QueryParams, UrlRequest};
+pub use url_parser_trait::{PathParams, QueryParams};
 
+#[cfg(feature = "clean")]
+pub mod clean;
+pub mod cookie;
+pub mod data_url;
+pub mod idna;
+#[cfg(feature = "pattern")]
+pub mod pattern;
+pub mod percent_encoding;
+pub mod psl;
+pub mod punycode;
+#[cfg(feature = "serde")]
+pub mod query;
+pub mod template;
+pub mod uri;
 pub mod url;
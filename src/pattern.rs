@@ -0,0 +1,551 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://urlpattern.spec.whatwg.org/
+// Regular expression groups use the syntax of the `regex` crate instead of ECMAScript, so
+// lookarounds and backreferences are rejected.
+
+mod canonicalize;
+mod constructor;
+mod parser;
+mod tokenizer;
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use parser::{EncodingCallback, Options, Part, PartType};
+use regex::Regex;
+
+use crate::url::{self, URL};
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternError {
+    // An invalid `:name`, `(regexp)` or trailing `\` at this byte offset of a component pattern.
+    Tokenize(usize),
+    // A token that cannot appear here, e.g. an unclosed `{`, at this byte offset of a component pattern.
+    UnexpectedToken(usize),
+    // Two groups of a component share this name.
+    DuplicateName(String),
+    // Fixed text that is not valid in this component, e.g. a hostname with a space.
+    InvalidComponent(&'static str),
+    // The regular expression generated for a component, which the `regex` crate rejected.
+    InvalidRegex(String),
+    // A constructor string without a protocol was given without a base URL.
+    BaseURLRequired,
+}
+
+impl Display for PatternError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Tokenize(index) => write!(fmt, "invalid token at {}", index),
+            Self::UnexpectedToken(index) => write!(fmt, "unexpected token at {}", index),
+            Self::DuplicateName(name) => write!(fmt, "duplicate group name {:?}", name),
+            Self::InvalidComponent(component) => write!(fmt, "invalid {}", component),
+            Self::InvalidRegex(regex) => write!(fmt, "invalid regular expression {:?}", regex),
+            Self::BaseURLRequired => write!(fmt, "relative pattern without a base URL"),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+
+// https://urlpattern.spec.whatwg.org/#dictdef-urlpatterninit
+// Missing components match anything, unless they are inherited from `base_url`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct URLPatternInit {
+    pub protocol: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub hostname: Option<String>,
+    pub port: Option<String>,
+    pub pathname: Option<String>,
+    pub search: Option<String>,
+    pub hash: Option<String>,
+    pub base_url: Option<URL>,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#dictdef-urlpatternoptions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct URLPatternOptions {
+    // Applies to the pathname, search and hash.
+    pub ignore_case: bool,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#dictdef-urlpatternresult
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct URLPatternResult {
+    pub protocol: URLPatternComponentResult,
+    pub username: URLPatternComponentResult,
+    pub password: URLPatternComponentResult,
+    pub hostname: URLPatternComponentResult,
+    pub port: URLPatternComponentResult,
+    pub pathname: URLPatternComponentResult,
+    pub search: URLPatternComponentResult,
+    pub hash: URLPatternComponentResult,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#dictdef-urlpatterncomponentresult
+// Groups are in pattern order. Unnamed groups are named by their index, and a group with an
+// unmatched optional modifier has no value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct URLPatternComponentResult {
+    pub input: String,
+    pub groups: Vec<(String, Option<String>)>,
+}
+
+impl URLPatternComponentResult {
+    pub fn group(&self, name: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#component
+#[derive(Clone, Debug)]
+pub(crate) struct Component {
+    pattern: String,
+    regex: Regex,
+    group_names: Vec<String>,
+    has_regexp_groups: bool,
+}
+
+impl Component {
+    // https://urlpattern.spec.whatwg.org/#compile-a-component
+    pub(crate) fn compile(
+        input: &str,
+        encoding_callback: EncodingCallback,
+        options: &Options,
+    ) -> Result<Self, PatternError> {
+        let parts: Vec<Part> = parser::parse_pattern_string(input, options, encoding_callback)?;
+        let (regex, group_names) = parser::generate_regular_expression(&parts, options);
+        Ok(Self {
+            pattern: parser::generate_pattern_string(&parts, options),
+            regex: Regex::new(&regex).map_err(|_| PatternError::InvalidRegex(regex))?,
+            group_names,
+            has_regexp_groups: parts
+                .iter()
+                .any(|part: &Part| part.kind == PartType::Regexp),
+        })
+    }
+
+    // https://urlpattern.spec.whatwg.org/#protocol-component-matches-a-special-scheme
+    pub(crate) fn matches_special_scheme(&self) -> bool {
+        ["ftp", "file", "http", "https", "ws", "wss"]
+            .iter()
+            .any(|scheme: &&str| self.regex.is_match(scheme))
+    }
+
+    fn exec(&self, input: &str) -> Option<URLPatternComponentResult> {
+        let captures: regex::Captures = self.regex.captures(input)?;
+        let groups: Vec<(String, Option<String>)> = self
+            .group_names
+            .iter()
+            .enumerate()
+            .map(|(i, name): (usize, &String)| {
+                (
+                    name.clone(),
+                    captures
+                        .get(i + 1)
+                        .map(|value: regex::Match| value.as_str().to_string()),
+                )
+            })
+            .collect();
+        Some(URLPatternComponentResult {
+            input: input.to_string(),
+            groups,
+        })
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProcessType {
+    Pattern,
+    Url,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#process-a-base-url-string
+fn process_base_url_string(input: &str, kind: ProcessType) -> String {
+    match kind {
+        ProcessType::Pattern => parser::escape_pattern_string(input),
+        ProcessType::Url => input.to_string(),
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#is-an-absolute-pathname
+fn is_absolute_pathname(input: &str, kind: ProcessType) -> bool {
+    input.starts_with('/') || (kind == ProcessType::Pattern && (input.starts_with("\\/") || input.starts_with("{/")))
+}
+
+
+// https://urlpattern.spec.whatwg.org/#hostname-pattern-is-an-ipv6-address
+fn hostname_pattern_is_ipv6_address(input: &str) -> bool {
+    input.len() >= 2 && (input.starts_with('[') || input.starts_with("{[") || input.starts_with("\\["))
+}
+
+
+impl URLPatternInit {
+    // https://urlpattern.spec.whatwg.org/#process-a-urlpatterninit
+    // Components are copied from the base URL up to the first one given. URL inputs are canonicalized,
+    // with every component present.
+    fn process(&self, kind: ProcessType) -> Result<Self, PatternError> {
+        let mut result: Self = match kind {
+            ProcessType::Pattern => Self::default(),
+            ProcessType::Url => {
+                Self {
+                    protocol: Some(String::new()),
+                    username: Some(String::new()),
+                    password: Some(String::new()),
+                    hostname: Some(String::new()),
+                    port: Some(String::new()),
+                    pathname: Some(String::new()),
+                    search: Some(String::new()),
+                    hash: Some(String::new()),
+                    base_url: None,
+                }
+            }
+        };
+        if let Some(base) = &self.base_url {
+            let process = |value: &str| -> Option<String> { Some(process_base_url_string(value, kind)) };
+            let before_username: bool = self.protocol.is_none() && self.hostname.is_none() && self.port.is_none();
+            if self.protocol.is_none() {
+                result.protocol = process(base.scheme());
+            }
+            if kind != ProcessType::Pattern && before_username && self.username.is_none() {
+                result.username = process(base.username());
+            }
+            if kind != ProcessType::Pattern && before_username && self.username.is_none() && self.password.is_none() {
+                result.password = process(base.password());
+            }
+            if self.protocol.is_none() && self.hostname.is_none() {
+                result.hostname = process(&base.host_str().unwrap_or_default());
+            }
+            if before_username {
+                result.port = process(
+                    &base
+                        .port()
+                        .map(|port: u16| port.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+            if before_username && self.pathname.is_none() {
+                result.pathname = process(&base.path());
+            }
+            if before_username && self.pathname.is_none() && self.search.is_none() {
+                result.search = process(base.query().unwrap_or_default());
+            }
+            if before_username && self.pathname.is_none() && self.search.is_none() && self.hash.is_none() {
+                result.hash = process(base.fragment().unwrap_or_default());
+            }
+        }
+        let is_url: bool = kind == ProcessType::Url;
+        if let Some(protocol) = &self.protocol {
+            let protocol: &str = protocol.strip_suffix(':').unwrap_or(protocol);
+            result.protocol = Some(if is_url {
+                canonicalize::protocol(protocol)?
+            } else {
+                protocol.to_string()
+            });
+        }
+        if let Some(username) = &self.username {
+            result.username = Some(if is_url {
+                canonicalize::username(username)?
+            } else {
+                username.clone()
+            });
+        }
+        if let Some(password) = &self.password {
+            result.password = Some(if is_url {
+                canonicalize::password(password)?
+            } else {
+                password.clone()
+            });
+        }
+        if let Some(hostname) = &self.hostname {
+            result.hostname = Some(if is_url {
+                canonicalize::hostname(hostname)?
+            } else {
+                hostname.clone()
+            });
+        }
+        if let Some(port) = &self.port {
+            result.port = Some(if is_url {
+                canonicalize::port(port, result.protocol.as_deref())?
+            } else {
+                port.clone()
+            });
+        }
+        if let Some(pathname) = &self.pathname {
+            let mut pathname: String = pathname.clone();
+            if let Some(base) = self
+                .base_url
+                .as_ref()
+                .filter(|base: &&URL| !base.has_opaque_path())
+            {
+                if !is_absolute_pathname(&pathname, kind) {
+                    let base_path: String = process_base_url_string(&base.path(), kind);
+                    if let Some(slash) = base_path.rfind('/') {
+                        pathname = format!("{}{}", &base_path[..=slash], pathname);
+                    }
+                }
+            }
+            // Non-special URLs with a leading `/` have a hierarchical path too.
+            let is_hierarchical: bool = result
+                .protocol
+                .as_deref()
+                .is_some_and(|protocol: &str| protocol.is_empty() || url::is_special_scheme(protocol))
+                || pathname.starts_with('/');
+            result.pathname = Some(match kind {
+                ProcessType::Pattern => pathname,
+                ProcessType::Url if is_hierarchical => canonicalize::pathname(&pathname)?,
+                ProcessType::Url => canonicalize::opaque_pathname(&pathname)?,
+            });
+        }
+        if let Some(search) = &self.search {
+            let search: &str = search.strip_prefix('?').unwrap_or(search);
+            result.search = Some(if is_url {
+                canonicalize::search(search)?
+            } else {
+                search.to_string()
+            });
+        }
+        if let Some(hash) = &self.hash {
+            let hash: &str = hash.strip_prefix('#').unwrap_or(hash);
+            result.hash = Some(if is_url {
+                canonicalize::hash(hash)?
+            } else {
+                hash.to_string()
+            });
+        }
+        Ok(result)
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#urlpattern
+// e.g. `https://*.example.com/users/:id` matches `https://api.example.com/users/42` with `id` bound to `42`.
+#[derive(Clone, Debug)]
+pub struct URLPattern {
+    protocol: Component,
+    username: Component,
+    password: Component,
+    hostname: Component,
+    port: Component,
+    pathname: Component,
+    search: Component,
+    hash: Component,
+}
+
+impl URLPattern {
+    pub fn parse(input: &str) -> Result<Self, PatternError> {
+        Self::parse_with_options(input, None, URLPatternOptions::default())
+    }
+
+    // Components missing from `input` before the first one given are taken from `base`, e.g.
+    // `/users/:id` with `https://example.com` matches `https://example.com/users/42`.
+    pub fn parse_with_base(input: &str, base: &URL) -> Result<Self, PatternError> {
+        Self::parse_with_options(input, Some(base), URLPatternOptions::default())
+    }
+
+    // https://urlpattern.spec.whatwg.org/#url-pattern-create
+    pub fn parse_with_options(input: &str, base: Option<&URL>, options: URLPatternOptions) -> Result<Self, PatternError> {
+        let mut init: URLPatternInit = constructor::parse_constructor_string(input)?;
+        if base.is_none() && init.protocol.is_none() {
+            return Err(PatternError::BaseURLRequired);
+        }
+        init.base_url = base.cloned();
+        Self::from_init_with_options(&init, options)
+    }
+
+    pub fn from_init(init: &URLPatternInit) -> Result<Self, PatternError> {
+        Self::from_init_with_options(init, URLPatternOptions::default())
+    }
+
+    // https://urlpattern.spec.whatwg.org/#url-pattern-create
+    pub fn from_init_with_options(init: &URLPatternInit, options: URLPatternOptions) -> Result<Self, PatternError> {
+        let mut init: URLPatternInit = init.process(ProcessType::Pattern)?;
+        if let Some(protocol) = init
+            .protocol
+            .as_deref()
+            .filter(|protocol: &&str| url::is_special_scheme(protocol))
+        {
+            let default_port: Option<String> = url::default_port(protocol).map(|port: u16| port.to_string());
+            if default_port.is_some() && init.port == default_port {
+                init.port = Some(String::new());
+            }
+        }
+        let compile = |input: &Option<String>, callback: EncodingCallback, options: &Options| {
+            Component::compile(input.as_deref().unwrap_or("*"), callback, options)
+        };
+        let protocol: Component = compile(&init.protocol, &canonicalize::protocol, &Options::default())?;
+        let hostname: Component = if init
+            .hostname
+            .as_deref()
+            .is_some_and(hostname_pattern_is_ipv6_address)
+        {
+            compile(
+                &init.hostname,
+                &canonicalize::ipv6_hostname,
+                &Options::HOSTNAME,
+            )?
+        } else {
+            compile(&init.hostname, &canonicalize::hostname, &Options::HOSTNAME)?
+        };
+        let ignore_case: Options = Options {
+            ignore_case: options.ignore_case,
+            ..Options::default()
+        };
+        let is_hierarchical: bool = init.protocol.as_deref() == Some("")
+            || protocol.matches_special_scheme()
+            || init
+                .pathname
+                .as_deref()
+                .is_some_and(|pathname: &str| pathname.starts_with('/'));
+        let pathname: Component = if is_hierarchical {
+            let options: Options = Options {
+                ignore_case: options.ignore_case,
+                ..Options::PATHNAME
+            };
+            compile(&init.pathname, &canonicalize::pathname, &options)?
+        } else {
+            compile(&init.pathname, &canonicalize::opaque_pathname, &ignore_case)?
+        };
+        Ok(Self {
+            protocol,
+            username: compile(&init.username, &canonicalize::username, &Options::default())?,
+            password: compile(&init.password, &canonicalize::password, &Options::default())?,
+            hostname,
+            port: compile(
+                &init.port,
+                &|port: &str| canonicalize::port(port, None),
+                &Options::default(),
+            )?,
+            pathname,
+            search: compile(&init.search, &canonicalize::search, &ignore_case)?,
+            hash: compile(&init.hash, &canonicalize::hash, &ignore_case)?,
+        })
+    }
+
+    // The normalized pattern of each component, e.g. `/users/:id`.
+    pub fn protocol(&self) -> &str {
+        &self.protocol.pattern
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username.pattern
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password.pattern
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname.pattern
+    }
+
+    pub fn port(&self) -> &str {
+        &self.port.pattern
+    }
+
+    pub fn pathname(&self) -> &str {
+        &self.pathname.pattern
+    }
+
+    pub fn search(&self) -> &str {
+        &self.search.pattern
+    }
+
+    pub fn hash(&self) -> &str {
+        &self.hash.pattern
+    }
+
+    // https://urlpattern.spec.whatwg.org/#dom-urlpattern-hasregexpgroups
+    pub fn has_regexp_groups(&self) -> bool {
+        self.components()
+            .iter()
+            .any(|component: &&Component| component.has_regexp_groups)
+    }
+
+    // https://urlpattern.spec.whatwg.org/#dom-urlpattern-test
+    pub fn test(&self, input: &URL) -> bool {
+        self.exec(input).is_some()
+    }
+
+    // https://urlpattern.spec.whatwg.org/#dom-urlpattern-exec
+    pub fn exec(&self, input: &URL) -> Option<URLPatternResult> {
+        self.exec_components([
+            input.scheme().to_string(),
+            input.username().to_string(),
+            input.password().to_string(),
+            input.host_str().unwrap_or_default(),
+            input
+                .port()
+                .map(|port: u16| port.to_string())
+                .unwrap_or_default(),
+            input.path(),
+            input.query().unwrap_or_default().to_string(),
+            input.fragment().unwrap_or_default().to_string(),
+        ])
+    }
+
+    // Components of `input` are canonicalized as URL components; missing ones are empty. An invalid
+    // component, e.g. a hostname with a space, never matches.
+    pub fn test_init(&self, input: &URLPatternInit) -> bool {
+        self.exec_init(input).is_some()
+    }
+
+    pub fn exec_init(&self, input: &URLPatternInit) -> Option<URLPatternResult> {
+        let input: URLPatternInit = input.process(ProcessType::Url).ok()?;
+        self.exec_components(
+            [
+                input.protocol,
+                input.username,
+                input.password,
+                input.hostname,
+                input.port,
+                input.pathname,
+                input.search,
+                input.hash,
+            ]
+            .map(Option::unwrap_or_default),
+        )
+    }
+
+    fn components(&self) -> [&Component; 8] {
+        [
+            &self.protocol,
+            &self.username,
+            &self.password,
+            &self.hostname,
+            &self.port,
+            &self.pathname,
+            &self.search,
+            &self.hash,
+        ]
+    }
+
+    // https://urlpattern.spec.whatwg.org/#url-pattern-match
+    fn exec_components(&self, input: [String; 8]) -> Option<URLPatternResult> {
+        let [protocol, username, password, hostname, port, pathname, search, hash] = input;
+        Some(URLPatternResult {
+            protocol: self.protocol.exec(&protocol)?,
+            username: self.username.exec(&username)?,
+            password: self.password.exec(&password)?,
+            hostname: self.hostname.exec(&hostname)?,
+            port: self.port.exec(&port)?,
+            pathname: self.pathname.exec(&pathname)?,
+            search: self.search.exec(&search)?,
+            hash: self.hash.exec(&hash)?,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://urlpattern.spec.whatwg.org/#canon-encoding-callbacks
+// Fixed text in a pattern is canonicalized like the matching URL component, so `café.com` matches
+// `xn--caf-dma.com`. Components are parsed as if they belonged to an `http:` URL.
+
+use super::PatternError;
+use crate::percent_encoding::{utf8_percent_encode, AsciiSet, C0_CONTROL, FRAGMENT, SPECIAL_QUERY, USERINFO};
+use crate::url::URL;
+
+
+fn dummy_url() -> URL {
+    URL::parse("http://dummy.test").unwrap()
+}
+
+
+// The basic URL parser removes these before parsing.
+fn encode(value: &str, set: &AsciiSet) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c: &char| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    utf8_percent_encode(&value, set)
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-protocol
+pub(crate) fn protocol(value: &str) -> Result<String, PatternError> {
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+    match URL::parse(&format!("{}://dummy.test", value)) {
+        Ok(url) => Ok(url.scheme().to_string()),
+        Err(_) => Err(PatternError::InvalidComponent("protocol")),
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-username
+pub(crate) fn username(value: &str) -> Result<String, PatternError> {
+    Ok(encode(value, &USERINFO))
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-password
+pub(crate) fn password(value: &str) -> Result<String, PatternError> {
+    Ok(encode(value, &USERINFO))
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-hostname
+// The parser stops at the first `/`, `?`, `#` or `\`. A `:` before it would start a port, which is
+// an error here.
+pub(crate) fn hostname(value: &str) -> Result<String, PatternError> {
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+    let host: &str = &value[..value.find(['/', '?', '#', '\\']).unwrap_or(value.len())];
+    let mut inside_brackets: bool = false;
+    for c in host.chars() {
+        match c {
+            '[' => inside_brackets = true,
+            ']' => inside_brackets = false,
+            ':' if !inside_brackets => return Err(PatternError::InvalidComponent("hostname")),
+            _ => {}
+        }
+    }
+    let mut url: URL = dummy_url();
+    url.try_set_hostname(value)
+        .map_err(|_| PatternError::InvalidComponent("hostname"))?;
+    Ok(url.host_str().unwrap_or_default())
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-an-ipv6-hostname
+pub(crate) fn ipv6_hostname(value: &str) -> Result<String, PatternError> {
+    if !value
+        .chars()
+        .all(|c: char| c.is_ascii_hexdigit() || matches!(c, '[' | ']' | ':'))
+    {
+        return Err(PatternError::InvalidComponent("hostname"));
+    }
+    Ok(value.to_ascii_lowercase())
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-port
+// With a protocol, its default port canonicalizes to the empty string.
+pub(crate) fn port(value: &str, protocol: Option<&str>) -> Result<String, PatternError> {
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+    let mut url: URL = protocol
+        .filter(|protocol: &&str| !protocol.is_empty())
+        .and_then(|protocol: &str| URL::parse(&format!("{}://dummy.test", protocol)).ok())
+        .unwrap_or_else(|| URL::parse("dummy://dummy.test").unwrap());
+    url.try_set_port(value)
+        .map_err(|_| PatternError::InvalidComponent("port"))?;
+    Ok(url
+        .port()
+        .map(|port: u16| port.to_string())
+        .unwrap_or_default())
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-pathname
+// A relative pathname is parsed behind `/-` so that its first segment is not treated as `.` or `..`
+// of the root.
+pub(crate) fn pathname(value: &str) -> Result<String, PatternError> {
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+    let leading_slash: bool = value.starts_with('/');
+    let mut url: URL = dummy_url();
+    url.set_pathname(&if leading_slash {
+        value.to_string()
+    } else {
+        format!("/-{}", value)
+    });
+    let path: String = url.path();
+    match path.strip_prefix("/-") {
+        Some(path) if !leading_slash => Ok(path.to_string()),
+        _ => Ok(path),
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-an-opaque-pathname
+pub(crate) fn opaque_pathname(value: &str) -> Result<String, PatternError> {
+    Ok(encode(value, &C0_CONTROL))
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-search
+pub(crate) fn search(value: &str) -> Result<String, PatternError> {
+    Ok(encode(value, &SPECIAL_QUERY))
+}
+
+
+// https://urlpattern.spec.whatwg.org/#canonicalize-a-hash
+pub(crate) fn hash(value: &str) -> Result<String, PatternError> {
+    Ok(encode(value, &FRAGMENT))
+}
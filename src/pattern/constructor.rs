@@ -0,0 +1,305 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://urlpattern.spec.whatwg.org/#constructor-string-parsing
+// Splits a pattern like `https://*.example.com/users/:id` into its components.
+
+use super::parser::Options;
+use super::tokenizer::{self, Policy, Token, TokenType};
+use super::{canonicalize, Component, PatternError, URLPatternInit};
+
+
+// https://urlpattern.spec.whatwg.org/#constructor-string-parser-state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Init,
+    Protocol,
+    Authority,
+    Username,
+    Password,
+    Hostname,
+    Port,
+    Pathname,
+    Search,
+    Hash,
+    Done,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#constructor-string-parser
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token<'a>>,
+    result: URLPatternInit,
+    component_start: usize,
+    token_index: usize,
+    token_increment: usize,
+    group_depth: usize,
+    hostname_ipv6_bracket_depth: usize,
+    protocol_matches_special_scheme: bool,
+    state: State,
+}
+
+impl<'a> Parser<'a> {
+    // https://urlpattern.spec.whatwg.org/#get-a-safe-token
+    fn safe_token(&self, index: usize) -> Token<'a> {
+        self.tokens
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| *self.tokens.last().unwrap())
+    }
+
+    // https://urlpattern.spec.whatwg.org/#is-a-non-special-pattern-char
+    fn is_non_special_pattern_char(&self, index: usize, value: &str) -> bool {
+        let token: Token = self.safe_token(index);
+        token.value == value
+            && matches!(
+                token.kind,
+                TokenType::Char | TokenType::EscapedChar | TokenType::InvalidChar
+            )
+    }
+
+    fn is_char(&self, value: &str) -> bool {
+        self.is_non_special_pattern_char(self.token_index, value)
+    }
+
+    // https://urlpattern.spec.whatwg.org/#is-a-search-prefix
+    fn is_search_prefix(&self) -> bool {
+        if self.is_char("?") {
+            return true;
+        }
+        if self.tokens[self.token_index].value != "?" {
+            return false;
+        }
+        let Some(previous) = self.token_index.checked_sub(1) else {
+            return true;
+        };
+        !matches!(
+            self.safe_token(previous).kind,
+            TokenType::Name | TokenType::Regexp | TokenType::Close | TokenType::Asterisk
+        )
+    }
+
+    // https://urlpattern.spec.whatwg.org/#next-is-authority-slashes
+    fn next_is_authority_slashes(&self) -> bool {
+        self.is_non_special_pattern_char(self.token_index + 1, "/")
+            && self.is_non_special_pattern_char(self.token_index + 2, "/")
+    }
+
+    // https://urlpattern.spec.whatwg.org/#make-a-component-string
+    fn component_string(&self) -> String {
+        let end: usize = self.tokens[self.token_index].index;
+        let start: usize = self.safe_token(self.component_start).index;
+        self.input[start..end].to_string()
+    }
+
+    // https://urlpattern.spec.whatwg.org/#rewind
+    fn rewind(&mut self) {
+        self.token_index = self.component_start;
+        self.token_increment = 0;
+    }
+
+    // https://urlpattern.spec.whatwg.org/#rewind-and-set-state
+    fn rewind_and_set_state(&mut self, state: State) {
+        self.rewind();
+        self.state = state;
+    }
+
+    // https://urlpattern.spec.whatwg.org/#change-state
+    fn change_state(&mut self, state: State, skip: usize) {
+        let value: Option<String> = match self.state {
+            State::Init | State::Authority | State::Done => None,
+            _ => Some(self.component_string()),
+        };
+        let result: &mut URLPatternInit = &mut self.result;
+        match self.state {
+            State::Protocol => result.protocol = value,
+            State::Username => result.username = value,
+            State::Password => result.password = value,
+            State::Hostname => result.hostname = value,
+            State::Port => result.port = value,
+            State::Pathname => result.pathname = value,
+            State::Search => result.search = value,
+            State::Hash => result.hash = value,
+            State::Init | State::Authority | State::Done => {}
+        }
+        if self.state != State::Init && state != State::Done {
+            let before_hostname: bool = matches!(
+                self.state,
+                State::Protocol | State::Authority | State::Username | State::Password
+            );
+            let before_pathname: bool = before_hostname || matches!(self.state, State::Hostname | State::Port);
+            if before_hostname
+                && matches!(
+                    state,
+                    State::Port | State::Pathname | State::Search | State::Hash
+                )
+                && result.hostname.is_none()
+            {
+                result.hostname = Some(String::new());
+            }
+            if before_pathname && matches!(state, State::Search | State::Hash) && result.pathname.is_none() {
+                result.pathname = Some(
+                    if self.protocol_matches_special_scheme {
+                        "/"
+                    } else {
+                        ""
+                    }
+                    .to_string(),
+                );
+            }
+            if (before_pathname || self.state == State::Pathname) && state == State::Hash && result.search.is_none() {
+                result.search = Some(String::new());
+            }
+        }
+        self.state = state;
+        self.token_index += skip;
+        self.component_start = self.token_index;
+        self.token_increment = 0;
+    }
+
+    // https://urlpattern.spec.whatwg.org/#compute-protocol-matches-a-special-scheme-flag
+    fn compute_protocol_matches_special_scheme(&mut self) -> Result<(), PatternError> {
+        let protocol: Component = Component::compile(
+            &self.component_string(),
+            &canonicalize::protocol,
+            &Options::default(),
+        )?;
+        self.protocol_matches_special_scheme = protocol.matches_special_scheme();
+        Ok(())
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#parse-a-constructor-string
+pub(crate) fn parse_constructor_string(input: &str) -> Result<URLPatternInit, PatternError> {
+    let mut parser: Parser = Parser {
+        input,
+        tokens: tokenizer::tokenize(input, Policy::Lenient)?,
+        result: URLPatternInit::default(),
+        component_start: 0,
+        token_index: 0,
+        token_increment: 1,
+        group_depth: 0,
+        hostname_ipv6_bracket_depth: 0,
+        protocol_matches_special_scheme: false,
+        state: State::Init,
+    };
+    while parser.token_index < parser.tokens.len() {
+        parser.token_increment = 1;
+        if parser.tokens[parser.token_index].kind == TokenType::End {
+            if parser.state == State::Init {
+                parser.rewind();
+                if parser.is_char("#") {
+                    parser.change_state(State::Hash, 1);
+                } else if parser.is_search_prefix() {
+                    parser.change_state(State::Search, 1);
+                } else {
+                    parser.change_state(State::Pathname, 0);
+                }
+                parser.token_index += parser.token_increment;
+                continue;
+            }
+            if parser.state == State::Authority {
+                parser.rewind_and_set_state(State::Hostname);
+                parser.token_index += parser.token_increment;
+                continue;
+            }
+            parser.change_state(State::Done, 0);
+            break;
+        }
+        if parser.tokens[parser.token_index].kind == TokenType::Open {
+            parser.group_depth += 1;
+            parser.token_index += parser.token_increment;
+            continue;
+        }
+        if parser.group_depth > 0 {
+            if parser.tokens[parser.token_index].kind == TokenType::Close {
+                parser.group_depth -= 1;
+            } else {
+                parser.token_index += parser.token_increment;
+                continue;
+            }
+        }
+        match parser.state {
+            State::Init => {
+                if parser.is_char(":") {
+                    parser.rewind_and_set_state(State::Protocol);
+                }
+            }
+            State::Protocol => {
+                if parser.is_char(":") {
+                    parser.compute_protocol_matches_special_scheme()?;
+                    if parser.next_is_authority_slashes() {
+                        parser.change_state(State::Authority, 3);
+                    } else if parser.protocol_matches_special_scheme {
+                        parser.change_state(State::Authority, 1);
+                    } else {
+                        parser.change_state(State::Pathname, 1);
+                    }
+                }
+            }
+            State::Authority => {
+                if parser.is_char("@") {
+                    parser.rewind_and_set_state(State::Username);
+                } else if parser.is_char("/") || parser.is_search_prefix() || parser.is_char("#") {
+                    parser.rewind_and_set_state(State::Hostname);
+                }
+            }
+            State::Username => {
+                if parser.is_char(":") {
+                    parser.change_state(State::Password, 1);
+                } else if parser.is_char("@") {
+                    parser.change_state(State::Hostname, 1);
+                }
+            }
+            State::Password => {
+                if parser.is_char("@") {
+                    parser.change_state(State::Hostname, 1);
+                }
+            }
+            State::Hostname => {
+                if parser.is_char("[") {
+                    parser.hostname_ipv6_bracket_depth += 1;
+                } else if parser.is_char("]") {
+                    parser.hostname_ipv6_bracket_depth = parser.hostname_ipv6_bracket_depth.saturating_sub(1);
+                } else if parser.is_char(":") && parser.hostname_ipv6_bracket_depth == 0 {
+                    parser.change_state(State::Port, 1);
+                } else if parser.is_char("/") {
+                    parser.change_state(State::Pathname, 0);
+                } else if parser.is_search_prefix() {
+                    parser.change_state(State::Search, 1);
+                } else if parser.is_char("#") {
+                    parser.change_state(State::Hash, 1);
+                }
+            }
+            State::Port => {
+                if parser.is_char("/") {
+                    parser.change_state(State::Pathname, 0);
+                } else if parser.is_search_prefix() {
+                    parser.change_state(State::Search, 1);
+                } else if parser.is_char("#") {
+                    parser.change_state(State::Hash, 1);
+                }
+            }
+            State::Pathname => {
+                if parser.is_search_prefix() {
+                    parser.change_state(State::Search, 1);
+                } else if parser.is_char("#") {
+                    parser.change_state(State::Hash, 1);
+                }
+            }
+            State::Search => {
+                if parser.is_char("#") {
+                    parser.change_state(State::Hash, 1);
+                }
+            }
+            State::Hash | State::Done => {}
+        }
+        parser.token_index += parser.token_increment;
+    }
+    if parser.result.hostname.is_some() && parser.result.port.is_none() {
+        parser.result.port = Some(String::new());
+    }
+    Ok(parser.result)
+}
@@ -0,0 +1,461 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://urlpattern.spec.whatwg.org/#parsing-patterns
+
+use super::tokenizer::{self, is_valid_name_code_point, Policy, Token, TokenType};
+use super::PatternError;
+
+
+// https://urlpattern.spec.whatwg.org/#full-wildcard-regexp-value
+const FULL_WILDCARD_REGEXP: &str = ".*";
+
+
+// The canonicalization applied to fixed text, e.g. punycoding a hostname.
+pub(crate) type EncodingCallback<'a> = &'a dyn Fn(&str) -> Result<String, PatternError>;
+
+
+// https://urlpattern.spec.whatwg.org/#options-header
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    pub(crate) delimiter: Option<char>,
+    pub(crate) prefix: Option<char>,
+    pub(crate) ignore_case: bool,
+}
+
+impl Options {
+    // https://urlpattern.spec.whatwg.org/#hostname-options
+    pub(crate) const HOSTNAME: Self = Self {
+        delimiter: Some('.'),
+        prefix: None,
+        ignore_case: false,
+    };
+    // https://urlpattern.spec.whatwg.org/#pathname-options
+    pub(crate) const PATHNAME: Self = Self {
+        delimiter: Some('/'),
+        prefix: Some('/'),
+        ignore_case: false,
+    };
+
+    fn is_prefix(&self, value: &str) -> bool {
+        let mut chars = value.chars();
+        chars.next().is_some_and(|c: char| Some(c) == self.prefix) && chars.next().is_none()
+    }
+
+    // https://urlpattern.spec.whatwg.org/#generate-a-segment-wildcard-regexp
+    // `[^]` is not valid in the `regex` crate, so no delimiter gives `.+?`.
+    pub(crate) fn segment_wildcard_regexp(&self) -> String {
+        match self.delimiter {
+            Some(delimiter) => format!("[^{}]+?", escape_regexp_string(&delimiter.to_string())),
+            None => ".+?".to_string(),
+        }
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#part-type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PartType {
+    FixedText,
+    Regexp,
+    SegmentWildcard,
+    FullWildcard,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#part-modifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Modifier {
+    None,
+    Optional,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+impl Modifier {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Optional => "?",
+            Self::ZeroOrMore => "*",
+            Self::OneOrMore => "+",
+        }
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#part
+#[derive(Clone, Debug)]
+pub(crate) struct Part {
+    pub(crate) kind: PartType,
+    pub(crate) value: String,
+    pub(crate) modifier: Modifier,
+    pub(crate) name: String,
+    pub(crate) prefix: String,
+    pub(crate) suffix: String,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#escape-a-regexp-string
+// `/` is left as is; the `regex` crate does not need it escaped.
+pub(crate) fn escape_regexp_string(input: &str) -> String {
+    let mut output: String = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(
+            c,
+            '.' | '+' | '*' | '?' | '^' | '$' | '{' | '}' | '(' | ')' | '[' | ']' | '|' | '\\'
+        ) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
+
+
+// https://urlpattern.spec.whatwg.org/#escape-a-pattern-string
+pub(crate) fn escape_pattern_string(input: &str) -> String {
+    let mut output: String = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '+' | '*' | '?' | ':' | '{' | '}' | '(' | ')' | '\\') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
+
+
+// https://urlpattern.spec.whatwg.org/#pattern-parser
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    encoding_callback: EncodingCallback<'a>,
+    segment_wildcard_regexp: String,
+    parts: Vec<Part>,
+    pending_fixed_value: String,
+    index: usize,
+    next_numeric_name: usize,
+}
+
+impl<'a> Parser<'a> {
+    // https://urlpattern.spec.whatwg.org/#try-to-consume-a-token
+    fn try_consume(&mut self, kind: TokenType) -> Option<Token<'a>> {
+        let token: Token = self.tokens[self.index];
+        if token.kind != kind {
+            return None;
+        }
+        self.index += 1;
+        Some(token)
+    }
+
+    // https://urlpattern.spec.whatwg.org/#try-to-consume-a-regexp-or-wildcard-token
+    fn try_consume_regexp_or_wildcard(&mut self, name: Option<Token>) -> Option<Token<'a>> {
+        let token: Option<Token> = self.try_consume(TokenType::Regexp);
+        if name.is_none() && token.is_none() {
+            return self.try_consume(TokenType::Asterisk);
+        }
+        token
+    }
+
+    // https://urlpattern.spec.whatwg.org/#try-to-consume-a-modifier-token
+    fn try_consume_modifier(&mut self) -> Option<Token<'a>> {
+        self.try_consume(TokenType::OtherModifier)
+            .or_else(|| self.try_consume(TokenType::Asterisk))
+    }
+
+    // https://urlpattern.spec.whatwg.org/#consume-a-required-token
+    fn consume_required(&mut self, kind: TokenType) -> Result<Token<'a>, PatternError> {
+        let index: usize = self.tokens[self.index].index;
+        self.try_consume(kind)
+            .ok_or(PatternError::UnexpectedToken(index))
+    }
+
+    // https://urlpattern.spec.whatwg.org/#consume-text
+    fn consume_text(&mut self) -> String {
+        let mut result: String = String::new();
+        while let Some(token) = self
+            .try_consume(TokenType::Char)
+            .or_else(|| self.try_consume(TokenType::EscapedChar))
+        {
+            result += token.value;
+        }
+        result
+    }
+
+    // https://urlpattern.spec.whatwg.org/#maybe-add-a-part-from-the-pending-fixed-value
+    fn flush_pending_fixed_value(&mut self) -> Result<(), PatternError> {
+        if self.pending_fixed_value.is_empty() {
+            return Ok(());
+        }
+        let value: String = (self.encoding_callback)(&std::mem::take(&mut self.pending_fixed_value))?;
+        self.parts.push(Part {
+            kind: PartType::FixedText,
+            value,
+            modifier: Modifier::None,
+            name: String::new(),
+            prefix: String::new(),
+            suffix: String::new(),
+        });
+        Ok(())
+    }
+
+    // https://urlpattern.spec.whatwg.org/#add-a-part
+    fn add_part(
+        &mut self,
+        prefix: &str,
+        name: Option<Token>,
+        regexp_or_wildcard: Option<Token>,
+        suffix: &str,
+        modifier: Option<Token>,
+    ) -> Result<(), PatternError> {
+        let modifier: Modifier = match modifier.map(|token: Token| token.value) {
+            Some("?") => Modifier::Optional,
+            Some("*") => Modifier::ZeroOrMore,
+            Some("+") => Modifier::OneOrMore,
+            _ => Modifier::None,
+        };
+        if name.is_none() && regexp_or_wildcard.is_none() && modifier == Modifier::None {
+            self.pending_fixed_value += prefix;
+            return Ok(());
+        }
+        self.flush_pending_fixed_value()?;
+        if name.is_none() && regexp_or_wildcard.is_none() {
+            if prefix.is_empty() {
+                return Ok(());
+            }
+            self.parts.push(Part {
+                kind: PartType::FixedText,
+                value: (self.encoding_callback)(prefix)?,
+                modifier,
+                name: String::new(),
+                prefix: String::new(),
+                suffix: String::new(),
+            });
+            return Ok(());
+        }
+        let regexp_value: String = match regexp_or_wildcard {
+            None => self.segment_wildcard_regexp.clone(),
+            Some(token) if token.kind == TokenType::Asterisk => FULL_WILDCARD_REGEXP.to_string(),
+            Some(token) => token.value.to_string(),
+        };
+        let (kind, value) = if regexp_value == self.segment_wildcard_regexp {
+            (PartType::SegmentWildcard, String::new())
+        } else if regexp_value == FULL_WILDCARD_REGEXP {
+            (PartType::FullWildcard, String::new())
+        } else {
+            (PartType::Regexp, regexp_value)
+        };
+        let name: String = match name {
+            Some(token) => token.value.to_string(),
+            None => {
+                self.next_numeric_name += 1;
+                (self.next_numeric_name - 1).to_string()
+            }
+        };
+        if self.parts.iter().any(|part: &Part| part.name == name) {
+            return Err(PatternError::DuplicateName(name));
+        }
+        self.parts.push(Part {
+            kind,
+            value,
+            modifier,
+            name,
+            prefix: (self.encoding_callback)(prefix)?,
+            suffix: (self.encoding_callback)(suffix)?,
+        });
+        Ok(())
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#parse-a-pattern-string
+pub(crate) fn parse_pattern_string(
+    input: &str,
+    options: &Options,
+    encoding_callback: EncodingCallback,
+) -> Result<Vec<Part>, PatternError> {
+    let mut parser: Parser = Parser {
+        tokens: tokenizer::tokenize(input, Policy::Strict)?,
+        encoding_callback,
+        segment_wildcard_regexp: options.segment_wildcard_regexp(),
+        parts: Vec::new(),
+        pending_fixed_value: String::new(),
+        index: 0,
+        next_numeric_name: 0,
+    };
+    while parser.index < parser.tokens.len() {
+        let char_token: Option<Token> = parser.try_consume(TokenType::Char);
+        let name: Option<Token> = parser.try_consume(TokenType::Name);
+        let regexp_or_wildcard: Option<Token> = parser.try_consume_regexp_or_wildcard(name);
+        if name.is_some() || regexp_or_wildcard.is_some() {
+            let mut prefix: &str = char_token.map_or("", |token: Token| token.value);
+            if !prefix.is_empty() && !options.is_prefix(prefix) {
+                parser.pending_fixed_value += prefix;
+                prefix = "";
+            }
+            parser.flush_pending_fixed_value()?;
+            let modifier: Option<Token> = parser.try_consume_modifier();
+            parser.add_part(prefix, name, regexp_or_wildcard, "", modifier)?;
+            continue;
+        }
+        if let Some(token) = char_token.or_else(|| parser.try_consume(TokenType::EscapedChar)) {
+            parser.pending_fixed_value += token.value;
+            continue;
+        }
+        if parser.try_consume(TokenType::Open).is_some() {
+            let prefix: String = parser.consume_text();
+            let name: Option<Token> = parser.try_consume(TokenType::Name);
+            let regexp_or_wildcard: Option<Token> = parser.try_consume_regexp_or_wildcard(name);
+            let suffix: String = parser.consume_text();
+            parser.consume_required(TokenType::Close)?;
+            let modifier: Option<Token> = parser.try_consume_modifier();
+            parser.add_part(&prefix, name, regexp_or_wildcard, &suffix, modifier)?;
+            continue;
+        }
+        parser.flush_pending_fixed_value()?;
+        parser.consume_required(TokenType::End)?;
+    }
+    Ok(parser.parts)
+}
+
+
+// https://urlpattern.spec.whatwg.org/#generate-a-regular-expression-and-name-list
+pub(crate) fn generate_regular_expression(parts: &[Part], options: &Options) -> (String, Vec<String>) {
+    let mut result: String = if options.ignore_case {
+        "(?i)^".to_string()
+    } else {
+        "^".to_string()
+    };
+    let mut names: Vec<String> = Vec::new();
+    for part in parts {
+        let modifier: &str = part.modifier.as_str();
+        if part.kind == PartType::FixedText {
+            if part.modifier == Modifier::None {
+                result += &escape_regexp_string(&part.value);
+            } else {
+                result += &format!("(?:{}){}", escape_regexp_string(&part.value), modifier);
+            }
+            continue;
+        }
+        names.push(part.name.clone());
+        let value: String = match part.kind {
+            PartType::SegmentWildcard => options.segment_wildcard_regexp(),
+            PartType::FullWildcard => FULL_WILDCARD_REGEXP.to_string(),
+            _ => part.value.clone(),
+        };
+        let is_repeated: bool = matches!(part.modifier, Modifier::ZeroOrMore | Modifier::OneOrMore);
+        if part.prefix.is_empty() && part.suffix.is_empty() {
+            if is_repeated {
+                result += &format!("((?:{}){})", value, modifier);
+            } else {
+                result += &format!("({}){}", value, modifier);
+            }
+            continue;
+        }
+        let prefix: String = escape_regexp_string(&part.prefix);
+        let suffix: String = escape_regexp_string(&part.suffix);
+        if !is_repeated {
+            result += &format!("(?:{}({}){}){}", prefix, value, suffix, modifier);
+            continue;
+        }
+        result += &format!(
+            "(?:{}((?:{})(?:{}{}(?:{}))*){})",
+            prefix, value, suffix, prefix, value, suffix
+        );
+        if part.modifier == Modifier::ZeroOrMore {
+            result.push('?');
+        }
+    }
+    result.push('$');
+    (result, names)
+}
+
+
+fn is_numeric_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_digit())
+}
+
+
+// https://urlpattern.spec.whatwg.org/#generate-a-pattern-string
+pub(crate) fn generate_pattern_string(parts: &[Part], options: &Options) -> String {
+    let mut result: String = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        let previous: Option<&Part> = i.checked_sub(1).map(|i: usize| &parts[i]);
+        let next: Option<&Part> = parts.get(i + 1);
+        if part.kind == PartType::FixedText {
+            if part.modifier == Modifier::None {
+                result += &escape_pattern_string(&part.value);
+            } else {
+                result += &format!(
+                    "{{{}}}{}",
+                    escape_pattern_string(&part.value),
+                    part.modifier.as_str()
+                );
+            }
+            continue;
+        }
+        let custom_name: bool = !is_numeric_name(&part.name);
+        let mut needs_grouping: bool =
+            !part.suffix.is_empty() || (!part.prefix.is_empty() && !options.is_prefix(&part.prefix));
+        if !needs_grouping && custom_name && part.kind == PartType::SegmentWildcard && part.modifier == Modifier::None {
+            if let Some(next) = next.filter(|next: &&Part| next.prefix.is_empty() && next.suffix.is_empty()) {
+                needs_grouping = if next.kind == PartType::FixedText {
+                    next.value
+                        .chars()
+                        .next()
+                        .is_some_and(|c: char| is_valid_name_code_point(c, false))
+                } else {
+                    is_numeric_name(&next.name)
+                };
+            }
+        }
+        if !needs_grouping && part.prefix.is_empty() {
+            needs_grouping = previous.is_some_and(|previous: &Part| {
+                previous.kind == PartType::FixedText
+                    && previous
+                        .value
+                        .chars()
+                        .last()
+                        .is_some_and(|c: char| Some(c) == options.prefix)
+            });
+        }
+        if needs_grouping {
+            result.push('{');
+        }
+        result += &escape_pattern_string(&part.prefix);
+        if custom_name {
+            result += &format!(":{}", part.name);
+        }
+        match part.kind {
+            PartType::Regexp => result += &format!("({})", part.value),
+            PartType::SegmentWildcard if !custom_name => result += &format!("({})", options.segment_wildcard_regexp()),
+            PartType::FullWildcard => {
+                let is_plain: bool = previous.map_or(true, |previous: &Part| {
+                    previous.kind == PartType::FixedText || previous.modifier != Modifier::None
+                });
+                if !custom_name && (is_plain || needs_grouping || !part.prefix.is_empty()) {
+                    result.push('*');
+                } else {
+                    result += &format!("({})", FULL_WILDCARD_REGEXP);
+                }
+            }
+            _ => {}
+        }
+        if part.kind == PartType::SegmentWildcard
+            && custom_name
+            && part
+                .suffix
+                .chars()
+                .next()
+                .is_some_and(|c: char| is_valid_name_code_point(c, false))
+        {
+            result.push('\\');
+        }
+        result += &escape_pattern_string(&part.suffix);
+        if needs_grouping {
+            result.push('}');
+        }
+        result += part.modifier.as_str();
+    }
+    result
+}
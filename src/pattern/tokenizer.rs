@@ -0,0 +1,198 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://urlpattern.spec.whatwg.org/#tokenizing
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::PatternError;
+
+
+// https://urlpattern.spec.whatwg.org/#token-type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TokenType {
+    Open,
+    Close,
+    Regexp,
+    Name,
+    Char,
+    EscapedChar,
+    OtherModifier,
+    Asterisk,
+    End,
+    InvalidChar,
+}
+
+
+// `index` is the byte offset of the token in the input, `value` its text without syntax, e.g. a
+// name without `:`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Token<'a> {
+    pub(crate) kind: TokenType,
+    pub(crate) index: usize,
+    pub(crate) value: &'a str,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#tokenize-policy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Policy {
+    Strict,
+    Lenient,
+}
+
+
+// https://urlpattern.spec.whatwg.org/#is-a-valid-name-code-point
+pub(crate) fn is_valid_name_code_point(c: char, first: bool) -> bool {
+    static ID_START: OnceLock<Regex> = OnceLock::new();
+    static ID_CONTINUE: OnceLock<Regex> = OnceLock::new();
+    let mut buffer: [u8; 4] = [0; 4];
+    let c: &str = c.encode_utf8(&mut buffer);
+    if first {
+        ID_START
+            .get_or_init(|| Regex::new(r"^[\p{ID_Start}$_]$").unwrap())
+            .is_match(c)
+    } else {
+        ID_CONTINUE
+            .get_or_init(|| Regex::new("^[\\p{ID_Continue}$\u{200c}\u{200d}]$").unwrap())
+            .is_match(c)
+    }
+}
+
+
+struct Tokenizer<'a> {
+    input: &'a str,
+    policy: Policy,
+    tokens: Vec<Token<'a>>,
+    // The start of the token being read.
+    index: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn char_at(&self, index: usize) -> Option<char> {
+        self.input[index..].chars().next()
+    }
+
+    // https://urlpattern.spec.whatwg.org/#add-a-token
+    // The token's value is `input[value_start..value_end]`; the next token starts at `next`.
+    fn add(&mut self, kind: TokenType, next: usize, value_start: usize, value_end: usize) {
+        self.tokens.push(Token {
+            kind,
+            index: self.index,
+            value: &self.input[value_start..value_end],
+        });
+        self.index = next;
+    }
+
+    // https://urlpattern.spec.whatwg.org/#process-a-tokenizing-error
+    fn error(&mut self, next: usize, value_start: usize) -> Result<(), PatternError> {
+        if self.policy == Policy::Strict {
+            return Err(PatternError::Tokenize(value_start));
+        }
+        self.add(TokenType::InvalidChar, next, value_start, next);
+        Ok(())
+    }
+
+    // Reads a `:name` starting at `self.index`.
+    fn name(&mut self) -> Result<(), PatternError> {
+        let start: usize = self.index + 1;
+        let mut end: usize = start;
+        while let Some(c) = self
+            .char_at(end)
+            .filter(|c: &char| is_valid_name_code_point(*c, end == start))
+        {
+            end += c.len_utf8();
+        }
+        if end == start {
+            return self.error(start, self.index);
+        }
+        self.add(TokenType::Name, end, start, end);
+        Ok(())
+    }
+
+    // Reads a `(regexp)` starting at `self.index`. Only ASCII is allowed, and nested groups must be
+    // non-capturing.
+    fn regexp(&mut self) -> Result<(), PatternError> {
+        let start: usize = self.index + 1;
+        let mut position: usize = start;
+        let mut depth: usize = 1;
+        let bytes: &[u8] = self.input.as_bytes();
+        while position < bytes.len() {
+            let c: u8 = bytes[position];
+            if !c.is_ascii() || (position == start && c == b'?') {
+                return self.error(start, self.index);
+            }
+            match c {
+                b'\\' => {
+                    if position + 1 == bytes.len() || !bytes[position + 1].is_ascii() {
+                        return self.error(start, self.index);
+                    }
+                    position += 2;
+                    continue;
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        position += 1;
+                        break;
+                    }
+                }
+                b'(' => {
+                    depth += 1;
+                    if bytes.get(position + 1) != Some(&b'?') {
+                        return self.error(start, self.index);
+                    }
+                }
+                _ => {}
+            }
+            position += 1;
+        }
+        if depth != 0 || position - start == 1 {
+            return self.error(start, self.index);
+        }
+        self.add(TokenType::Regexp, position, start, position - 1);
+        Ok(())
+    }
+}
+
+
+// https://urlpattern.spec.whatwg.org/#tokenize
+pub(crate) fn tokenize(input: &str, policy: Policy) -> Result<Vec<Token<'_>>, PatternError> {
+    let mut tokenizer: Tokenizer = Tokenizer {
+        input,
+        policy,
+        tokens: Vec::new(),
+        index: 0,
+    };
+    while let Some(c) = tokenizer.char_at(tokenizer.index) {
+        let index: usize = tokenizer.index;
+        let next: usize = index + c.len_utf8();
+        match c {
+            '*' => tokenizer.add(TokenType::Asterisk, next, index, next),
+            '+' | '?' => tokenizer.add(TokenType::OtherModifier, next, index, next),
+            '\\' => {
+                match tokenizer.char_at(next) {
+                    Some(escaped) => {
+                        tokenizer.add(
+                            TokenType::EscapedChar,
+                            next + escaped.len_utf8(),
+                            next,
+                            next + escaped.len_utf8(),
+                        )
+                    }
+                    None => tokenizer.error(next, index)?,
+                }
+            }
+            '{' => tokenizer.add(TokenType::Open, next, index, next),
+            '}' => tokenizer.add(TokenType::Close, next, index, next),
+            ':' => tokenizer.name()?,
+            '(' => tokenizer.regexp()?,
+            _ => tokenizer.add(TokenType::Char, next, index, next),
+        }
+    }
+    let end: usize = input.len();
+    tokenizer.add(TokenType::End, end, end, end);
+    Ok(tokenizer.tokens)
+}
@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://url.spec.whatwg.org/#percent-encoded-bytes
+
+use std::fmt::Write;
+
+
+// Non-ASCII bytes are always encoded; only ASCII bytes are tracked here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AsciiSet {
+    mask: [u32; 4],
+}
+
+impl AsciiSet {
+    pub const fn add(&self, byte: u8) -> Self {
+        let mut mask: [u32; 4] = self.mask;
+        mask[byte as usize / 32] |= 1 << (byte % 32);
+        Self { mask }
+    }
+
+    pub const fn contains(&self, byte: u8) -> bool {
+        byte >= 0x80 || self.mask[byte as usize / 32] & (1 << (byte % 32)) != 0
+    }
+
+    pub const fn remove(&self, byte: u8) -> Self {
+        let mut mask: [u32; 4] = self.mask;
+        mask[byte as usize / 32] &= !(1 << (byte % 32));
+        Self { mask }
+    }
+}
+
+
+// Only non-ASCII bytes are encoded, e.g. to map an IRI to a URI.
+pub const NON_ASCII: AsciiSet = AsciiSet { mask: [0; 4] };
+
+pub const C0_CONTROL: AsciiSet = AsciiSet {
+    mask: [0xffff_ffff, 0, 0, 0x8000_0000],
+};
+
+pub const FRAGMENT: AsciiSet = C0_CONTROL.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+
+pub const QUERY: AsciiSet = C0_CONTROL.add(b' ').add(b'"').add(b'#').add(b'<').add(b'>');
+
+pub const SPECIAL_QUERY: AsciiSet = QUERY.add(b'\'');
+
+pub const PATH: AsciiSet = QUERY.add(b'?').add(b'`').add(b'{').add(b'}');
+
+// A single path segment: `/`, `\` and `%` are encoded too, so the segment decodes back to the same
+// value.
+pub const PATH_SEGMENT: AsciiSet = PATH.add(b'/').add(b'%').add(b'\\');
+
+pub const USERINFO: AsciiSet = PATH
+    .add(b'/')
+    .add(b':')
+    .add(b';')
+    .add(b'=')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'|');
+
+pub const COMPONENT: AsciiSet = USERINFO.add(b'$').add(b'%').add(b'&').add(b'+').add(b',');
+
+pub const FORM_URLENCODED: AsciiSet = COMPONENT.add(b'!').add(b'\'').add(b'(').add(b')').add(b'~');
+
+
+pub fn percent_encode(input: &[u8], set: &AsciiSet) -> String {
+    let mut output: String = String::with_capacity(input.len());
+    for &byte in input {
+        if set.contains(byte) {
+            write!(output, "%{:02X}", byte).unwrap();
+        } else {
+            output.push(byte as char);
+        }
+    }
+    output
+}
+
+
+pub fn utf8_percent_encode(input: &str, set: &AsciiSet) -> String {
+    percent_encode(input.as_bytes(), set)
+}
+
+
+pub fn percent_decode(input: &[u8]) -> Vec<u8> {
+    let mut output: Vec<u8> = Vec::with_capacity(input.len());
+    let mut i: usize = 0;
+    while i < input.len() {
+        if input[i] == b'%' && i + 2 < input.len() {
+            if let (Some(high), Some(low)) = (hex_value(input[i + 1]), hex_value(input[i + 2])) {
+                output.push(high << 4 | low);
+                i += 3;
+                continue;
+            }
+        }
+        output.push(input[i]);
+        i += 1;
+    }
+    output
+}
+
+
+pub fn percent_decode_str(input: &str) -> String {
+    String::from_utf8_lossy(&percent_decode(input.as_bytes())).into_owned()
+}
+
+
+pub(crate) fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        _ => None,
+    }
+}
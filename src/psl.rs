@@ -0,0 +1,209 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://publicsuffix.org/list/
+// The default list is built from `data/public_suffix_list.dat` (MPL-2.0) by the build script.
+// Domains are expected in their ASCII form, lowercased, as in `Host::Domain`.
+
+mod trie;
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::{fs, io};
+
+use trie::{Node, Rule};
+
+use crate::url::Host;
+
+include!(concat!(env!("OUT_DIR"), "/psl_trie.rs"));
+
+
+static BUILTIN: List = List {
+    nodes: Cow::Borrowed(NODES),
+};
+
+static LOADED: RwLock<Option<Arc<List>>> = RwLock::new(None);
+
+
+// Which sections of the list are used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    // Both the ICANN and the private section, e.g. `github.io` is a public suffix.
+    #[default]
+    All,
+    // Only the ICANN section, e.g. `github.io` is a registrable domain.
+    Icann,
+}
+
+
+#[derive(Clone, Debug)]
+pub struct List {
+    nodes: Cow<'static, [Node]>,
+}
+
+impl List {
+    // The list embedded at build time.
+    pub fn builtin() -> &'static Self {
+        &BUILTIN
+    }
+
+    // Parses a list in the format of `public_suffix_list.dat`.
+    pub fn parse(source: &str) -> Self {
+        Self {
+            nodes: Cow::Owned(trie::build(source)),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    // e.g. `co.uk` for `www.example.co.uk`. A trailing dot is kept.
+    // Returns `None` for an empty domain or one starting with a dot.
+    pub fn public_suffix<'a>(&self, domain: &'a str, mode: Mode) -> Option<&'a str> {
+        let start: usize = self.public_suffix_start(domain, mode)?;
+        Some(&domain[start..])
+    }
+
+    // e.g. `example.co.uk` for `www.example.co.uk`.
+    // Returns `None` if the domain is a public suffix or the label before it is empty.
+    pub fn registrable_domain<'a>(&self, domain: &'a str, mode: Mode) -> Option<&'a str> {
+        let start: usize = self.public_suffix_start(domain, mode)?.checked_sub(1)?;
+        let start: usize = domain[..start].rfind('.').map_or(0, |i: usize| i + 1);
+        if domain[start..].starts_with('.') {
+            return None;
+        }
+        Some(&domain[start..])
+    }
+
+    pub fn is_public_suffix(&self, domain: &str, mode: Mode) -> bool {
+        self.public_suffix_start(domain, mode) == Some(0)
+    }
+
+    fn public_suffix_start(&self, domain: &str, mode: Mode) -> Option<usize> {
+        let name: &str = domain.strip_suffix('.').unwrap_or(domain);
+        if name.is_empty() || name.starts_with('.') {
+            return None;
+        }
+        let labels: Vec<&str> = name.rsplit('.').collect();
+        let mut rule: usize = 1;
+        let mut exception: Option<usize> = None;
+        self.walk(0, &labels, 1, mode, &mut rule, &mut exception);
+        // An exception rule overrides the others, and its leftmost label is not part of the suffix.
+        let len: usize = exception.map_or(rule, |depth: usize| depth - 1);
+        Some(
+            name.rmatch_indices('.')
+                .nth(len - 1)
+                .map_or(0, |(i, _): (usize, &str)| i + 1),
+        )
+    }
+
+    fn child(&self, index: usize, label: &str) -> Option<usize> {
+        let node: &Node = &self.nodes[index];
+        let first: usize = node.first_child as usize;
+        let children: &[Node] = &self.nodes[first..first + node.child_count as usize];
+        children
+            .binary_search_by(|child: &Node| child.label.as_ref().cmp(label))
+            .ok()
+            .map(|i: usize| first + i)
+    }
+
+    // https://github.com/publicsuffix/list/wiki/Format#algorithm
+    // Records the number of labels of the longest matching rule and exception rule.
+    fn walk(
+        &self,
+        index: usize,
+        labels: &[&str],
+        depth: usize,
+        mode: Mode,
+        rule: &mut usize,
+        exception: &mut Option<usize>,
+    ) {
+        let Some((&label, rest)) = labels.split_first() else {
+            return;
+        };
+        let candidates: &[&str] = if label == "*" { &["*"] } else { &[label, "*"] };
+        for candidate in candidates {
+            let Some(child) = self.child(index, candidate) else {
+                continue;
+            };
+            match self.nodes[child].rule {
+                Some(Rule {
+                    is_private: true, ..
+                }) if mode == Mode::Icann => (),
+                Some(Rule {
+                    is_exception: true, ..
+                }) => *exception = (*exception).max(Some(depth)),
+                Some(_) => *rule = (*rule).max(depth),
+                None => (),
+            }
+            self.walk(child, rest, depth + 1, mode, rule, exception);
+        }
+    }
+}
+
+
+// Replaces the list used by `Host`, e.g. with a fresher copy.
+pub fn set_list(list: List) {
+    *LOADED.write().unwrap() = Some(Arc::new(list));
+}
+
+
+pub fn load_from_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    set_list(List::from_file(path)?);
+    Ok(())
+}
+
+
+// Goes back to `List::builtin()`.
+pub fn reset_list() {
+    *LOADED.write().unwrap() = None;
+}
+
+
+fn with_list<T>(f: impl FnOnce(&List) -> T) -> T {
+    match &*LOADED.read().unwrap() {
+        Some(list) => f(list),
+        None => f(&BUILTIN),
+    }
+}
+
+
+// https://url.spec.whatwg.org/#host-public-suffix
+// These use the list set with `set_list`, and are `None` or `false` for hosts that are not domains.
+impl Host {
+    pub fn public_suffix(&self) -> Option<&str> {
+        self.public_suffix_with(Mode::All)
+    }
+
+    pub fn public_suffix_with(&self, mode: Mode) -> Option<&str> {
+        let Self::Domain(domain) = self else {
+            return None;
+        };
+        with_list(|list: &List| list.public_suffix(domain, mode))
+    }
+
+    // https://url.spec.whatwg.org/#host-registrable-domain
+    pub fn registrable_domain(&self) -> Option<&str> {
+        self.registrable_domain_with(Mode::All)
+    }
+
+    pub fn registrable_domain_with(&self, mode: Mode) -> Option<&str> {
+        let Self::Domain(domain) = self else {
+            return None;
+        };
+        with_list(|list: &List| list.registrable_domain(domain, mode))
+    }
+
+    pub fn is_public_suffix(&self) -> bool {
+        self.is_public_suffix_with(Mode::All)
+    }
+
+    pub fn is_public_suffix_with(&self, mode: Mode) -> bool {
+        match self {
+            Self::Domain(domain) => with_list(|list: &List| list.is_public_suffix(domain, mode)),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Builds the flattened rule trie. The build script includes this file too, to embed the default
+// list.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::punycode;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rule {
+    pub(crate) is_exception: bool,
+    pub(crate) is_private: bool,
+}
+
+
+// Labels are stored in reverse order, from the TLD down, and in their ASCII form.
+// The children of a node are contiguous and sorted by label, the root being the first node.
+#[derive(Clone, Debug)]
+pub(crate) struct Node {
+    pub(crate) label: Cow<'static, str>,
+    pub(crate) first_child: u32,
+    pub(crate) child_count: u32,
+    pub(crate) rule: Option<Rule>,
+}
+
+
+#[derive(Default)]
+struct Tree {
+    children: BTreeMap<String, Tree>,
+    rule: Option<Rule>,
+}
+
+
+fn to_ascii_label(label: &str) -> Option<String> {
+    if label.is_ascii() {
+        Some(label.to_ascii_lowercase())
+    } else {
+        Some(format!("xn--{}", punycode::encode(&label.to_lowercase())?))
+    }
+}
+
+
+// https://github.com/publicsuffix/list/wiki/Format
+// Rules that cannot be converted to ASCII are skipped.
+pub(crate) fn build(source: &str) -> Vec<Node> {
+    let mut root: Tree = Tree::default();
+    let mut is_private: bool = false;
+    for line in source.lines() {
+        let line: &str = line.trim();
+        if line.starts_with("// ===BEGIN PRIVATE DOMAINS===") {
+            is_private = true;
+        } else if line.starts_with("// ===END PRIVATE DOMAINS===") {
+            is_private = false;
+        }
+        let Some(rule) = line
+            .split_whitespace()
+            .next()
+            .filter(|rule: &&str| !rule.starts_with("//"))
+        else {
+            continue;
+        };
+        let (rule, is_exception) = match rule.strip_prefix('!') {
+            Some(rule) => (rule, true),
+            None => (rule, false),
+        };
+        let Some(labels) = rule
+            .rsplit('.')
+            .map(to_ascii_label)
+            .collect::<Option<Vec<String>>>()
+        else {
+            continue;
+        };
+        let mut tree: &mut Tree = &mut root;
+        for label in labels {
+            tree = tree.children.entry(label).or_default();
+        }
+        tree.rule = Some(Rule {
+            is_exception,
+            is_private,
+        });
+    }
+
+    let mut nodes: Vec<Node> = vec![Node {
+        label: Cow::Borrowed(""),
+        first_child: 0,
+        child_count: 0,
+        rule: None,
+    }];
+    let mut queue: VecDeque<(usize, &Tree)> = VecDeque::from([(0, &root)]);
+    while let Some((index, tree)) = queue.pop_front() {
+        nodes[index].first_child = nodes.len() as u32;
+        nodes[index].child_count = tree.children.len() as u32;
+        for (label, child) in &tree.children {
+            queue.push_back((nodes.len(), child));
+            nodes.push(Node {
+                label: Cow::Owned(label.clone()),
+                first_child: 0,
+                child_count: 0,
+                rule: child.rule,
+            });
+        }
+    }
+    nodes
+}
@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Serde bridge following the encoding rules of `#[derive(QueryParams)]`:
+// `?key=value&...`, sequences and tuples as comma lists, `None` and empty values skipped.
+// Keys and list elements are percent-encoded with the component percent-encode set.
+// Missing strings and sequences deserialize as empty.
+// Values carry no type information, so `#[serde(flatten)]` only deserializes string fields.
+
+mod de;
+mod ser;
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+pub use de::from_query_params;
+pub use ser::to_query_params;
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    // The field named by `serde::de::Error::missing_field`.
+    missing_field: Option<&'static str>,
+}
+
+impl Error {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            missing_field: None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.message)
+    }
+}
+
+impl error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::new(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            missing_field: Some(field),
+            ..Self::new(format!("missing field `{}`", field))
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::Error;
+use crate::percent_encoding::percent_decode_str;
+
+
+// Empty values are skipped when serializing, so a field that is still missing is retried with an
+// empty value: strings and sequences then deserialize as empty, and any other type keeps the
+// missing field error.
+pub fn from_query_params<T: DeserializeOwned>(query: &str) -> Result<T, Error> {
+    let query: &str = query.strip_prefix('?').unwrap_or(query);
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair: &&str| !pair.is_empty())
+        .map(|pair: &str| -> (String, String) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode_str(key), value.to_string())
+        })
+        .collect();
+    let mut missing: Option<Error> = None;
+    loop {
+        let deserializer: MapDeserializer<_, Error> = MapDeserializer::new(pairs.iter().map(
+            |(key, raw): &(String, String)| -> (String, ValueDeserializer) {
+                (key.clone(), ValueDeserializer { raw: raw.clone() })
+            },
+        ));
+        match T::deserialize(deserializer) {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                match err.missing_field {
+                    Some(field) if !pairs.iter().any(|(key, _): &(String, String)| key == field) => {
+                        pairs.push((field.to_string(), String::new()));
+                        missing = Some(err);
+                    }
+                    _ => return Err(missing.unwrap_or(err)),
+                }
+            }
+        }
+    }
+}
+
+
+// The raw, still percent-encoded value of one pair.
+// Commas are split before decoding so that encoded commas survive inside list elements.
+struct ValueDeserializer {
+    raw: String,
+}
+
+impl ValueDeserializer {
+    fn decoded(&self) -> String {
+        percent_decode_str(&self.raw)
+    }
+
+    fn parse<T: FromStr>(&self, expected: &str) -> Result<T, Error> {
+        let value: String = self.decoded();
+        value
+            .parse()
+            .map_err(|_| Error::new(format!("invalid {}: {:?}", expected, value)))
+    }
+
+    fn elements(&self) -> SeqDeserializer<std::vec::IntoIter<ValueDeserializer>, Error> {
+        let elements: Vec<ValueDeserializer> = if self.raw.is_empty() {
+            Vec::new()
+        } else {
+            self.raw
+                .split(',')
+                .map(|raw: &str| {
+                    ValueDeserializer {
+                        raw: raw.to_string(),
+                    }
+                })
+                .collect()
+        };
+        SeqDeserializer::new(elements.into_iter())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident($ty:ty),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf identifier ignored_any map struct
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool(bool),
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_f32 => visit_f32(f32),
+        deserialize_f64 => visit_f64(f64),
+        deserialize_char => visit_char(char),
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.decoded())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.raw.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut elements: SeqDeserializer<_, Error> = self.elements();
+        let value: V::Value = visitor.visit_seq(&mut elements)?;
+        elements.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _: &'static str, _: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(self.decoded()))
+    }
+}
@@ -0,0 +1,398 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::ser::{
+    Impossible,
+    Serialize,
+    SerializeMap,
+    SerializeSeq,
+    SerializeStruct,
+    SerializeTuple,
+    SerializeTupleStruct,
+    Serializer,
+};
+
+use super::Error;
+use crate::QueryParams;
+
+
+pub fn to_query_params<T: ?Sized + Serialize>(value: &T) -> Result<String, Error> {
+    let mut pairs: Vec<(String, Vec<String>)> = Vec::new();
+    value.serialize(PairsSerializer { pairs: &mut pairs })?;
+    Ok(Pairs(pairs).to_query_params())
+}
+
+
+// Leaves the encoding to `QueryParams::to_query_params`, so that it cannot drift from the derive's.
+struct Pairs(Vec<(String, Vec<String>)>);
+
+impl QueryParams for Pairs {
+    fn to_query_pairs(&self) -> Vec<(String, Vec<String>)> {
+        self.0.clone()
+    }
+}
+
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Error> {
+                Err(Error::new(concat!("unsupported type: ", stringify!($method))))
+            }
+        )*
+    };
+}
+
+
+// Top level: a struct or a map, each entry becomes one `key=value` pair.
+struct PairsSerializer<'a> {
+    pairs: &'a mut Vec<(String, Vec<String>)>,
+}
+
+impl<'a> Serializer for PairsSerializer<'a> {
+    type Error = Error;
+    type Ok = ();
+    type SerializeMap = PairsMapSerializer<'a>;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeStruct = PairsMapSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+
+    unsupported! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Error> {
+        Err(Error::new("unsupported type: serialize_newtype_variant"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(PairsMapSerializer {
+            pairs: self.pairs,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Error> {
+        Ok(PairsMapSerializer {
+            pairs: self.pairs,
+            key: None,
+        })
+    }
+}
+
+
+struct PairsMapSerializer<'a> {
+    pairs: &'a mut Vec<(String, Vec<String>)>,
+    key: Option<String>,
+}
+
+impl PairsMapSerializer<'_> {
+    fn push<T: ?Sized + Serialize>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        if let Some(values) = value.serialize(ValueSerializer { nested: false })? {
+            self.pairs.push((key, values));
+        }
+        Ok(())
+    }
+}
+
+impl SerializeMap for PairsMapSerializer<'_> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        let key: Option<String> = key
+            .serialize(ValueSerializer { nested: true })?
+            .and_then(|mut key: Vec<String>| key.pop())
+            .filter(|key: &String| !key.is_empty());
+        match key {
+            Some(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            None => Err(Error::new("map keys must not be empty")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key: String = self
+            .key
+            .take()
+            .ok_or_else(|| Error::new("serialize_value called before serialize_key"))?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl SerializeStruct for PairsMapSerializer<'_> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+
+// The elements of a single value, one unless it is a list; `None` means the pair is skipped.
+// Nested values are the elements of a comma list and cannot be lists themselves.
+struct ValueSerializer {
+    nested: bool,
+}
+
+impl ValueSerializer {
+    // An empty value skips its pair, but stays an element of a list.
+    fn display<T: ToString>(&self, value: T) -> Result<Option<Vec<String>>, Error> {
+        let value: String = value.to_string();
+        if value.is_empty() && !self.nested {
+            Ok(None)
+        } else {
+            Ok(Some(vec![value]))
+        }
+    }
+
+    fn list(&self) -> Result<ListSerializer, Error> {
+        if self.nested {
+            Err(Error::new("nested sequences are not supported"))
+        } else {
+            Ok(ListSerializer { values: Vec::new() })
+        }
+    }
+}
+
+impl Serializer for ValueSerializer {
+    type Error = Error;
+    type Ok = Option<Vec<String>>;
+    type SerializeMap = Impossible<Option<Vec<String>>, Error>;
+    type SerializeSeq = ListSerializer;
+    type SerializeStruct = Impossible<Option<Vec<String>>, Error>;
+    type SerializeStructVariant = Impossible<Option<Vec<String>>, Error>;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = Impossible<Option<Vec<String>>, Error>;
+
+    unsupported! {
+        serialize_bytes(&[u8]) -> Option<Vec<String>>;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_char(self, value: char) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Option<Vec<String>>, Error> {
+        self.display(value)
+    }
+
+    fn serialize_none(self) -> Result<Option<Vec<String>>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Option<Vec<String>>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<Vec<String>>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Option<Vec<String>>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str) -> Result<Option<Vec<String>>, Error> {
+        self.display(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Option<Vec<String>>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Option<Vec<String>>, Error> {
+        Err(Error::new("unsupported type: serialize_newtype_variant"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<ListSerializer, Error> {
+        self.list()
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<ListSerializer, Error> {
+        self.list()
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<ListSerializer, Error> {
+        self.list()
+    }
+}
+
+
+struct ListSerializer {
+    values: Vec<String>,
+}
+
+impl ListSerializer {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer { nested: true })? {
+            self.values.extend(value);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Vec<String>>, Error> {
+        if self.values.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(self.values))
+        }
+    }
+}
+
+impl SerializeSeq for ListSerializer {
+    type Error = Error;
+    type Ok = Option<Vec<String>>;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Vec<String>>, Error> {
+        self.finish()
+    }
+}
+
+impl SerializeTuple for ListSerializer {
+    type Error = Error;
+    type Ok = Option<Vec<String>>;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Vec<String>>, Error> {
+        self.finish()
+    }
+}
+
+impl SerializeTupleStruct for ListSerializer {
+    type Error = Error;
+    type Ok = Option<Vec<String>>;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Vec<String>>, Error> {
+        self.finish()
+    }
+}
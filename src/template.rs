@@ -0,0 +1,621 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://www.rfc-editor.org/rfc/rfc6570
+// URI Templates up to level 4. A template is parsed once and can be expanded with different
+// variables.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::percent_encoding::hex_value;
+use crate::QueryParams;
+
+mod matching;
+
+pub use matching::{Bindings, NoMatch};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TemplateErrorKind {
+    UnclosedExpression,
+    // A `}` outside of an expression.
+    UnopenedExpression,
+    InvalidLiteral,
+    // One of the operators reserved for future extensions, `=,!@|`.
+    InvalidOperator,
+    InvalidVarname,
+    // The max-length of a prefix modifier must be 1 to 9999.
+    InvalidPrefix,
+    // A prefix modifier on a list or associative array, found when expanding.
+    PrefixOnComposite,
+}
+
+impl TemplateErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnclosedExpression => "unclosed expression",
+            Self::UnopenedExpression => "unopened expression",
+            Self::InvalidLiteral => "invalid literal",
+            Self::InvalidOperator => "invalid operator",
+            Self::InvalidVarname => "invalid varname",
+            Self::InvalidPrefix => "invalid prefix",
+            Self::PrefixOnComposite => "prefix on composite value",
+        }
+    }
+}
+
+
+// The span is the byte range of the offending part of the template.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TemplateError {
+    pub kind: TemplateErrorKind,
+    pub span: Range<usize>,
+}
+
+impl TemplateError {
+    fn new(kind: TemplateErrorKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} at {}..{}",
+            self.kind.name(),
+            self.span.start,
+            self.span.end
+        )
+    }
+}
+
+impl Error for TemplateError {}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#section-2.3
+// Empty lists and associative arrays are treated as undefined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    List(Vec<String>),
+    Assoc(Vec<(String, String)>),
+}
+
+impl Value {
+    fn is_undefined(&self) -> bool {
+        match self {
+            Self::String(_) => false,
+            Self::List(items) => items.is_empty(),
+            Self::Assoc(pairs) => pairs.is_empty(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<String>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Self::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+
+// The variables a template is expanded with. Missing names are undefined.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Variables {
+    values: HashMap<String, Value>,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Each pair of `QueryParams::to_query_pairs` becomes a variable: a string if it has one element, a
+    // list otherwise.
+    pub fn from_query_params<T: QueryParams + ?Sized>(params: &T) -> Self {
+        params
+            .to_query_pairs()
+            .into_iter()
+            .map(
+                |(key, mut values): (String, Vec<String>)| -> (String, Value) {
+                    if values.len() == 1 {
+                        (key, Value::String(values.remove(0)))
+                    } else {
+                        (key, Value::List(values))
+                    }
+                },
+            )
+            .collect()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Value>) -> &mut Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.values.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Variables {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut variables: Self = Self::new();
+        for (name, value) in iter {
+            variables.insert(name, value);
+        }
+        variables
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> From<HashMap<K, V>> for Variables {
+    fn from(map: HashMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> From<BTreeMap<K, V>> for Variables {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#appendix-A
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    Path,
+    PathParameter,
+    Query,
+    QueryContinuation,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Self::Reserved),
+            '#' => Some(Self::Fragment),
+            '.' => Some(Self::Label),
+            '/' => Some(Self::Path),
+            ';' => Some(Self::PathParameter),
+            '?' => Some(Self::Query),
+            '&' => Some(Self::QueryContinuation),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn first(self) -> &'static str {
+        match self {
+            Self::Simple | Self::Reserved => "",
+            Self::Fragment => "#",
+            Self::Label => ".",
+            Self::Path => "/",
+            Self::PathParameter => ";",
+            Self::Query => "?",
+            Self::QueryContinuation => "&",
+        }
+    }
+
+    pub(crate) fn separator(self) -> &'static str {
+        match self {
+            Self::Simple | Self::Reserved | Self::Fragment => ",",
+            Self::Label => ".",
+            Self::Path => "/",
+            Self::PathParameter => ";",
+            Self::Query | Self::QueryContinuation => "&",
+        }
+    }
+
+    pub(crate) fn is_named(self) -> bool {
+        matches!(
+            self,
+            Self::PathParameter | Self::Query | Self::QueryContinuation
+        )
+    }
+
+    pub(crate) fn if_empty(self) -> &'static str {
+        match self {
+            Self::Query | Self::QueryContinuation => "=",
+            _ => "",
+        }
+    }
+
+    // Whether reserved characters and percent-encoded triplets are copied as is.
+    pub(crate) fn allows_reserved(self) -> bool {
+        matches!(self, Self::Reserved | Self::Fragment)
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Modifier {
+    None,
+    Prefix(usize),
+    Explode,
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct VarSpec {
+    pub(crate) name: String,
+    pub(crate) modifier: Modifier,
+    // The byte range in the template.
+    pub(crate) span: Range<usize>,
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Part {
+    Literal(String),
+    Expression(Operator, Vec<VarSpec>),
+}
+
+
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+}
+
+
+fn is_reserved(c: char) -> bool {
+    matches!(
+        c,
+        ':' | '/' | '?' | '#' | '[' | ']' | '@' | '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '='
+    )
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#section-2.1
+fn is_literal_char(c: char) -> bool {
+    !c.is_control()
+        && !matches!(
+            c,
+            ' ' | '"' | '\'' | '%' | '<' | '>' | '\\' | '^' | '`' | '{' | '|' | '}'
+        )
+}
+
+
+fn is_pct_encoded(input: &str) -> bool {
+    let bytes: &[u8] = input.as_bytes();
+    bytes.len() >= 3 && bytes[0] == b'%' && hex_value(bytes[1]).is_some() && hex_value(bytes[2]).is_some()
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#section-3.2.1
+// Characters outside of the allowed set are UTF-8 percent-encoded. With `allow_reserved`, reserved
+// characters and percent-encoded triplets are kept.
+pub(crate) fn encode(input: &str, allow_reserved: bool) -> String {
+    let mut output: String = String::with_capacity(input.len());
+    for (i, c) in input.char_indices() {
+        if is_unreserved(c) || (allow_reserved && (is_reserved(c) || (c == '%' && is_pct_encoded(&input[i..])))) {
+            output.push(c);
+        } else {
+            let mut buffer: [u8; 4] = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                output += &format!("%{:02X}", byte);
+            }
+        }
+    }
+    output
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#section-2.3
+fn parse_varspec(input: &str, offset: usize) -> Result<VarSpec, TemplateError> {
+    let (name, modifier) = if let Some(name) = input.strip_suffix('*') {
+        (name, Modifier::Explode)
+    } else if let Some((name, length)) = input.split_once(':') {
+        let span: Range<usize> = offset + name.len() + 1..offset + input.len();
+        let is_valid: bool =
+            (1..=4).contains(&length.len()) && !length.starts_with('0') && length.bytes().all(|b: u8| b.is_ascii_digit());
+        if !is_valid {
+            return Err(TemplateError::new(TemplateErrorKind::InvalidPrefix, span));
+        }
+        (name, Modifier::Prefix(length.parse().unwrap()))
+    } else {
+        (input, Modifier::None)
+    };
+    // varname = varchar *( ["."] varchar ), varchar = ALPHA / DIGIT / "_" / pct-encoded
+    let mut i: usize = 0;
+    let mut previous_dot: bool = true;
+    while i < name.len() {
+        let rest: &str = &name[i..];
+        if is_pct_encoded(rest) {
+            i += 3;
+            previous_dot = false;
+            continue;
+        }
+        let c: char = rest.chars().next().unwrap();
+        let is_valid: bool = if c == '.' {
+            !previous_dot
+        } else {
+            c.is_ascii_alphanumeric() || c == '_'
+        };
+        if !is_valid {
+            return Err(TemplateError::new(
+                TemplateErrorKind::InvalidVarname,
+                offset + i..offset + i + c.len_utf8(),
+            ));
+        }
+        previous_dot = c == '.';
+        i += c.len_utf8();
+    }
+    if previous_dot {
+        return Err(TemplateError::new(
+            TemplateErrorKind::InvalidVarname,
+            offset..offset + name.len(),
+        ));
+    }
+    Ok(VarSpec {
+        name: name.to_string(),
+        modifier,
+        span: offset..offset + input.len(),
+    })
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#section-2.2
+fn parse_expression(input: &str, offset: usize) -> Result<Part, TemplateError> {
+    let (operator, list, list_offset) = match input.chars().next() {
+        Some(c @ ('=' | ',' | '!' | '@' | '|')) => {
+            return Err(TemplateError::new(
+                TemplateErrorKind::InvalidOperator,
+                offset..offset + c.len_utf8(),
+            ));
+        }
+        Some(c) => {
+            match Operator::from_char(c) {
+                Some(operator) => (operator, &input[1..], offset + 1),
+                None => (Operator::Simple, input, offset),
+            }
+        }
+        None => (Operator::Simple, input, offset),
+    };
+    let mut varspecs: Vec<VarSpec> = Vec::new();
+    let mut start: usize = 0;
+    for varspec in list.split(',') {
+        varspecs.push(parse_varspec(varspec, list_offset + start)?);
+        start += varspec.len() + 1;
+    }
+    Ok(Part::Expression(operator, varspecs))
+}
+
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    pub(crate) parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, TemplateError> {
+        let mut parts: Vec<Part> = Vec::new();
+        let mut rest: &str = input;
+        while !rest.is_empty() {
+            let offset: usize = input.len() - rest.len();
+            if let Some(expression) = rest.strip_prefix('{') {
+                let end: usize = expression
+                    .find('}')
+                    .ok_or_else(|| TemplateError::new(TemplateErrorKind::UnclosedExpression, offset..input.len()))?;
+                parts.push(parse_expression(&expression[..end], offset + 1)?);
+                rest = &expression[end + 1..];
+                continue;
+            }
+            let end: usize = rest.find('{').unwrap_or(rest.len());
+            let literal: &str = &rest[..end];
+            for (i, c) in literal.char_indices() {
+                if !(is_literal_char(c) || c == '%' && is_pct_encoded(&literal[i..])) {
+                    let kind: TemplateErrorKind = if c == '}' {
+                        TemplateErrorKind::UnopenedExpression
+                    } else {
+                        TemplateErrorKind::InvalidLiteral
+                    };
+                    return Err(TemplateError::new(
+                        kind,
+                        offset + i..offset + i + c.len_utf8(),
+                    ));
+                }
+            }
+            parts.push(Part::Literal(literal.to_string()));
+            rest = &rest[end..];
+        }
+        Ok(Self {
+            source: input.to_string(),
+            parts,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    // The names of all variables, in order of appearance.
+    pub fn variable_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for part in &self.parts {
+            if let Part::Expression(_, varspecs) = part {
+                names.extend(
+                    varspecs
+                        .iter()
+                        .map(|varspec: &VarSpec| varspec.name.as_str()),
+                );
+            }
+        }
+        names
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc6570#section-3
+    // Fails if a prefix modifier meets a list or associative array (section 2.4.1).
+    pub fn expand(&self, variables: &Variables) -> Result<String, TemplateError> {
+        let mut output: String = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => output += &encode(literal, true),
+                Part::Expression(operator, varspecs) => expand_expression(*operator, varspecs, variables, &mut output)?,
+            }
+        }
+        Ok(output)
+    }
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc6570#appendix-A
+fn expand_expression(
+    operator: Operator,
+    varspecs: &[VarSpec],
+    variables: &Variables,
+    output: &mut String,
+) -> Result<(), TemplateError> {
+    let allow_reserved: bool = operator.allows_reserved();
+    let mut is_first: bool = true;
+    for varspec in varspecs {
+        let Some(value) = variables
+            .get(&varspec.name)
+            .filter(|value: &&Value| !value.is_undefined())
+        else {
+            continue;
+        };
+        *output += if is_first {
+            operator.first()
+        } else {
+            operator.separator()
+        };
+        is_first = false;
+        // `name` is encoded by the caller: variable names keep their pct-encoded triplets, keys follow the
+        // operator.
+        let named = |output: &mut String, name: &str, value: &str| {
+            *output += name;
+            *output += if value.is_empty() {
+                operator.if_empty()
+            } else {
+                "="
+            };
+        };
+        match (value, varspec.modifier) {
+            (Value::List(_) | Value::Assoc(_), Modifier::Prefix(_)) => {
+                return Err(TemplateError::new(
+                    TemplateErrorKind::PrefixOnComposite,
+                    varspec.span.clone(),
+                ));
+            }
+            (Value::String(value), modifier) => {
+                let value: &str = match modifier {
+                    Modifier::Prefix(length) => {
+                        value
+                            .char_indices()
+                            .nth(length)
+                            .map_or(value, |(i, _)| &value[..i])
+                    }
+                    _ => value,
+                };
+                if operator.is_named() {
+                    named(output, &encode(&varspec.name, true), value);
+                }
+                *output += &encode(value, allow_reserved);
+            }
+            (Value::List(items), Modifier::Explode) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        *output += operator.separator();
+                    }
+                    if operator.is_named() {
+                        named(output, &encode(&varspec.name, true), item);
+                    }
+                    *output += &encode(item, allow_reserved);
+                }
+            }
+            (Value::Assoc(pairs), Modifier::Explode) => {
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        *output += operator.separator();
+                    }
+                    if operator.is_named() {
+                        named(output, &encode(key, allow_reserved), value);
+                    } else {
+                        *output += &format!("{}=", encode(key, allow_reserved));
+                    }
+                    *output += &encode(value, allow_reserved);
+                }
+            }
+            (Value::List(items), _) => {
+                if operator.is_named() {
+                    *output += &format!("{}=", encode(&varspec.name, true));
+                }
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item: &String| encode(item, allow_reserved))
+                    .collect();
+                *output += &items.join(",");
+            }
+            (Value::Assoc(pairs), _) => {
+                if operator.is_named() {
+                    *output += &format!("{}=", encode(&varspec.name, true));
+                }
+                let items: Vec<String> = pairs
+                    .iter()
+                    .map(|(key, value): &(String, String)| {
+                        format!(
+                            "{},{}",
+                            encode(key, allow_reserved),
+                            encode(value, allow_reserved)
+                        )
+                    })
+                    .collect();
+                *output += &items.join(",");
+            }
+        }
+    }
+    Ok(())
+}
+
+
+impl Debug for Template {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_tuple("Template").field(&self.source).finish()
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.source)
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(input: &str) -> Result<Self, TemplateError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<&str> for Template {
+    type Error = TemplateError;
+
+    fn try_from(input: &str) -> Result<Self, TemplateError> {
+        Self::parse(input)
+    }
+}
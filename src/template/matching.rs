@@ -0,0 +1,458 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Reverse matching of level 1 to 3 templates: the variables a URL was expanded from.
+// Lists expand like comma-joined strings, so they are bound as such. Query expressions (`?` and
+// `&`) accept their pairs in any order; every other part is matched in template order. When several
+// bindings fit, the one with the most non-empty values wins.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use super::{encode, is_pct_encoded, is_reserved, is_unreserved, Modifier, Operator, Part, Template, VarSpec};
+use crate::percent_encoding::percent_decode_str;
+use crate::url::URL;
+
+
+// Why a URL does not match a template. Positions are byte offsets into the matched part of the URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NoMatch {
+    // The template uses a prefix or explode modifier (level 4), which cannot be reversed.
+    UnsupportedModifier(String),
+    // The URL differs from the literal `expected` at this position.
+    Literal { position: usize, expected: String },
+    // The URL continues after the end of the template at this position.
+    TrailingInput(usize),
+}
+
+impl Display for NoMatch {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnsupportedModifier(name) => write!(fmt, "unsupported modifier on {}", name),
+            Self::Literal { position, expected } => write!(fmt, "expected {:?} at {}", expected, position),
+            Self::TrailingInput(position) => write!(fmt, "trailing input at {}", position),
+        }
+    }
+}
+
+impl Error for NoMatch {}
+
+impl NoMatch {
+    fn position(&self) -> usize {
+        match self {
+            Self::UnsupportedModifier(_) => 0,
+            Self::Literal { position, .. } | Self::TrailingInput(position) => *position,
+        }
+    }
+}
+
+
+// The variables bound by a match, in template order. Values are kept as they appear in the URL.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings {
+    values: Vec<(String, String)>,
+}
+
+impl Bindings {
+    // The percent-decoded value of a variable.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.get_raw(name).map(percent_decode_str)
+    }
+
+    pub fn get_raw(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, value): &(String, String)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // Reads the bindings like a query with `crate::query::from_query_params`, so comma-separated values
+    // fill sequence fields and unbound variables are missing.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T, crate::query::Error> {
+        let query: Vec<String> = self
+            .values
+            .iter()
+            .map(|(name, value): &(String, String)| format!("{}={}", name, value.replace('&', "%26")))
+            .collect();
+        crate::query::from_query_params(&query.join("&"))
+    }
+}
+
+
+impl Template {
+    pub fn match_url(&self, url: &URL) -> Option<Bindings> {
+        self.try_match_url(url).ok()
+    }
+
+    // A template starting with a scheme is matched against the whole URL, any other against the path,
+    // query and fragment, e.g. `/users/{id}{?page}`.
+    pub fn try_match_url(&self, url: &URL) -> Result<Bindings, NoMatch> {
+        for part in &self.parts {
+            if let Part::Expression(_, varspecs) = part {
+                if let Some(varspec) = varspecs
+                    .iter()
+                    .find(|varspec: &&VarSpec| varspec.modifier != Modifier::None)
+                {
+                    return Err(NoMatch::UnsupportedModifier(varspec.name.clone()));
+                }
+            }
+        }
+        let input: String = if self.is_absolute() {
+            url.href().to_string()
+        } else {
+            let mut input: String = url.path();
+            if let Some(query) = url.query() {
+                input += &format!("?{}", query);
+            }
+            if let Some(fragment) = url.fragment() {
+                input += &format!("#{}", fragment);
+            }
+            input
+        };
+        let mut matcher: Matcher = Matcher::new(&self.parts, &input);
+        match matcher.best(0, 0, false, &mut Vec::new()) {
+            Some(values) => Ok(Bindings { values }),
+            None => Err(matcher.furthest.unwrap()),
+        }
+    }
+
+    fn is_absolute(&self) -> bool {
+        let Some(Part::Literal(literal)) = self.parts.first() else {
+            return false;
+        };
+        literal
+            .split_once(':')
+            .is_some_and(|(scheme, _): (&str, &str)| {
+                scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && scheme
+                        .chars()
+                        .all(|c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            })
+    }
+}
+
+
+// A part split into the pieces matched one at a time. Each variable of a non-query expression is
+// its own step, so the matcher never has to enumerate every way an expression can split its input.
+enum Step<'a> {
+    Literal(String),
+    // `is_first` marks the first variable of its expression.
+    Variable {
+        operator: Operator,
+        name: &'a str,
+        is_first: bool,
+    },
+    Query(Operator, &'a [VarSpec]),
+}
+
+
+// The best bindings of the remaining steps, by step, offset, whether the current expression has
+// bound a variable yet and the values already bound to variables the remaining steps use.
+type MemoKey = (usize, usize, bool, Vec<(String, String)>);
+
+
+// Finds the best way the steps can match `input`. Results are memoized, so the work is polynomial
+// in the length of the input and the number of variables.
+struct Matcher<'a> {
+    steps: Vec<Step<'a>>,
+    // The variable names used by each step and the ones after it.
+    names: Vec<Vec<&'a str>>,
+    input: &'a str,
+    memo: HashMap<MemoKey, Option<Vec<(String, String)>>>,
+    furthest: Option<NoMatch>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(parts: &'a [Part], input: &'a str) -> Self {
+        let mut steps: Vec<Step> = Vec::new();
+        for part in parts {
+            match part {
+                Part::Literal(literal) => steps.push(Step::Literal(encode(literal, true))),
+                Part::Expression(operator @ (Operator::Query | Operator::QueryContinuation), varspecs) => {
+                    steps.push(Step::Query(*operator, varspecs))
+                }
+                Part::Expression(operator, varspecs) => {
+                    for (i, varspec) in varspecs.iter().enumerate() {
+                        steps.push(Step::Variable {
+                            operator: *operator,
+                            name: &varspec.name,
+                            is_first: i == 0,
+                        });
+                    }
+                }
+            }
+        }
+        let mut names: Vec<Vec<&str>> = vec![Vec::new(); steps.len() + 1];
+        for (i, step) in steps.iter().enumerate().rev() {
+            let mut used: Vec<&str> = names[i + 1].clone();
+            match step {
+                Step::Literal(_) => {}
+                Step::Variable { name, .. } => used.push(name),
+                Step::Query(_, varspecs) => {
+                    used.extend(
+                        varspecs
+                            .iter()
+                            .map(|varspec: &VarSpec| varspec.name.as_str()),
+                    )
+                }
+            }
+            names[i] = used;
+        }
+        Self {
+            steps,
+            names,
+            input,
+            memo: HashMap::new(),
+            furthest: None,
+        }
+    }
+
+    fn fail(&mut self, err: NoMatch) {
+        if self.furthest.as_ref().map_or(true, |furthest: &NoMatch| {
+            err.position() > furthest.position()
+        }) {
+            self.furthest = Some(err);
+        }
+    }
+
+    // The variables the steps from `step` on bind on top of `values`, choosing the binding with the
+    // most non-empty values.
+    fn best(
+        &mut self,
+        step: usize,
+        offset: usize,
+        is_started: bool,
+        values: &mut Vec<(String, String)>,
+    ) -> Option<Vec<(String, String)>> {
+        if step == self.steps.len() {
+            if offset < self.input.len() {
+                self.fail(NoMatch::TrailingInput(offset));
+                return None;
+            }
+            return Some(Vec::new());
+        }
+        let is_started: bool = match self.steps[step] {
+            Step::Variable { is_first, .. } => is_started && !is_first,
+            _ => false,
+        };
+        let mut context: Vec<(String, String)> = values
+            .iter()
+            .filter(|(name, _): &&(String, String)| self.names[step].contains(&name.as_str()))
+            .cloned()
+            .collect();
+        context.sort();
+        let key: MemoKey = (step, offset, is_started, context);
+        if let Some(result) = self.memo.get(&key) {
+            return result.clone();
+        }
+        let input: &str = &self.input[offset..];
+        let mut best: Option<Vec<(String, String)>> = None;
+        match &self.steps[step] {
+            Step::Literal(expected) => {
+                match input.strip_prefix(expected.as_str()) {
+                    Some(_) => best = self.best(step + 1, offset + expected.len(), false, values),
+                    None => {
+                        let common: usize = expected
+                            .bytes()
+                            .zip(input.bytes())
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        let err: NoMatch = NoMatch::Literal {
+                            position: offset + common,
+                            expected: expected.clone(),
+                        };
+                        self.fail(err);
+                    }
+                }
+            }
+            Step::Variable { operator, name, .. } => {
+                let (operator, name): (Operator, &str) = (*operator, name);
+                let prefix: &str = if is_started {
+                    operator.separator()
+                } else {
+                    operator.first()
+                };
+                let mut candidates: Candidates = Vec::new();
+                if let Some(rest) = input.strip_prefix(prefix) {
+                    if operator.is_named() {
+                        if let Some(rest) = rest.strip_prefix(encode(name, true).as_str()) {
+                            match rest.strip_prefix('=') {
+                                Some(value) => {
+                                    for end in value_ends(operator, value) {
+                                        let length: usize = input.len() - value.len() + end;
+                                        candidates.push((length, vec![(name.to_string(), value[..end].to_string())]));
+                                    }
+                                }
+                                None => {
+                                    candidates.push((
+                                        input.len() - rest.len(),
+                                        vec![(name.to_string(), String::new())],
+                                    ))
+                                }
+                            }
+                        }
+                    } else {
+                        for end in value_ends(operator, rest) {
+                            let length: usize = input.len() - rest.len() + end;
+                            candidates.push((length, vec![(name.to_string(), rest[..end].to_string())]));
+                        }
+                    }
+                }
+                best = self.best_candidate(step, offset, candidates, true, values);
+                // The variable is undefined.
+                if let Some(rest) = self.best(step + 1, offset, is_started, values) {
+                    if best.as_ref().map_or(true, |best: &Vec<(String, String)>| {
+                        score(&rest) > score(best)
+                    }) {
+                        best = Some(rest);
+                    }
+                }
+            }
+            Step::Query(operator, varspecs) => {
+                let mut candidates: Candidates = Vec::new();
+                query_candidates(*operator, varspecs, input, &mut candidates);
+                best = self.best_candidate(step, offset, candidates, false, values);
+            }
+        }
+        self.memo.insert(key, best.clone());
+        best
+    }
+
+    // The best of the candidates for a step, each followed by the best match of the remaining steps.
+    fn best_candidate(
+        &mut self,
+        step: usize,
+        offset: usize,
+        candidates: Candidates,
+        is_started: bool,
+        values: &mut Vec<(String, String)>,
+    ) -> Option<Vec<(String, String)>> {
+        let mut best: Option<Vec<(String, String)>> = None;
+        for (length, bound) in candidates {
+            let is_consistent: bool = bound.iter().all(|(name, value)| {
+                values
+                    .iter()
+                    .all(|(other, other_value)| other != name || other_value == value)
+            });
+            if !is_consistent {
+                continue;
+            }
+            let start: usize = values.len();
+            for (name, value) in bound {
+                if !values
+                    .iter()
+                    .any(|(other, _): &(String, String)| other == &name)
+                {
+                    values.push((name, value));
+                }
+            }
+            let added: Vec<(String, String)> = values[start..].to_vec();
+            let rest: Option<Vec<(String, String)>> = self.best(step + 1, offset + length, is_started, values);
+            values.truncate(start);
+            if let Some(rest) = rest {
+                let candidate: Vec<(String, String)> = added.into_iter().chain(rest).collect();
+                if best.as_ref().map_or(true, |best: &Vec<(String, String)>| {
+                    score(&candidate) > score(best)
+                }) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        best
+    }
+}
+
+
+// Bindings with more non-empty values, then more values, are preferred.
+fn score(values: &[(String, String)]) -> (usize, usize) {
+    (
+        values
+            .iter()
+            .filter(|(_, value): &&(String, String)| !value.is_empty())
+            .count(),
+        values.len(),
+    )
+}
+
+
+// The length of input a step consumes and the variables it binds.
+type Candidates = Vec<(usize, Vec<(String, String)>)>;
+
+
+// The byte offsets at which a value in `input` can end, longest first.
+// Unreserved characters and `,` (lists) are allowed, plus reserved characters for `+` and `#`.
+fn value_ends(operator: Operator, input: &str) -> Vec<usize> {
+    let mut ends: Vec<usize> = vec![0];
+    let mut i: usize = 0;
+    while i < input.len() {
+        let rest: &str = &input[i..];
+        let c: char = rest.chars().next().unwrap();
+        if is_pct_encoded(rest) {
+            i += 3;
+        } else if is_unreserved(c) || c == ',' || (operator.allows_reserved() && is_reserved(c)) {
+            i += 1;
+        } else {
+            break;
+        }
+        ends.push(i);
+    }
+    ends.reverse();
+    ends
+}
+
+
+// Reads `name=value` pairs of the expression's variables in any order. Each prefix of the pairs is
+// a candidate.
+fn query_candidates(operator: Operator, varspecs: &[VarSpec], input: &str, candidates: &mut Candidates) {
+    let mut bound: Vec<(String, String)> = Vec::new();
+    let mut consumed: usize = 0;
+    candidates.push((0, Vec::new()));
+    loop {
+        let prefix: &str = if bound.is_empty() {
+            operator.first()
+        } else {
+            operator.separator()
+        };
+        let Some(rest) = input[consumed..].strip_prefix(prefix) else {
+            break;
+        };
+        let pair: &str = &rest[..rest.find(['&', '#']).unwrap_or(rest.len())];
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let is_known: bool = varspecs
+            .iter()
+            .any(|varspec: &VarSpec| encode(&varspec.name, true) == name);
+        if !is_known
+            || bound
+                .iter()
+                .any(|(other, _): &(String, String)| encode(other, true) == name)
+        {
+            break;
+        }
+        if value_ends(operator, value)[0] != value.len() {
+            break;
+        }
+        let varspec: &VarSpec = varspecs
+            .iter()
+            .find(|varspec: &&VarSpec| encode(&varspec.name, true) == name)
+            .unwrap();
+        bound.push((varspec.name.clone(), value.to_string()));
+        consumed += prefix.len() + pair.len();
+        candidates.push((consumed, bound.clone()));
+    }
+}
@@ -0,0 +1,796 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://www.rfc-editor.org/rfc/rfc3986
+// A strict parser for the generic URI syntax. Unlike `url::URL`, nothing is fixed up: input that
+// does not match the ABNF is rejected, and a parsed reference serializes back to its input.
+
+mod iri;
+
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::net::Ipv6Addr;
+use std::ops::{Deref, Range};
+use std::str::FromStr;
+
+pub use iri::Iri;
+
+use crate::percent_encoding::hex_value;
+use crate::url::{ParseError, URL};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UriErrorKind {
+    MissingScheme,
+    InvalidScheme,
+    InvalidUserinfo,
+    InvalidHost,
+    InvalidPort,
+    InvalidPath,
+    // A relative-path reference whose first segment contains `:`, which would be read as a scheme.
+    ColonInFirstSegment,
+    InvalidQuery,
+    InvalidFragment,
+    InvalidPercentEncoding,
+}
+
+impl UriErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MissingScheme => "missing scheme",
+            Self::InvalidScheme => "invalid scheme",
+            Self::InvalidUserinfo => "invalid userinfo",
+            Self::InvalidHost => "invalid host",
+            Self::InvalidPort => "invalid port",
+            Self::InvalidPath => "invalid path",
+            Self::ColonInFirstSegment => "colon in first path segment",
+            Self::InvalidQuery => "invalid query",
+            Self::InvalidFragment => "invalid fragment",
+            Self::InvalidPercentEncoding => "invalid percent-encoding",
+        }
+    }
+}
+
+
+// The span is the byte range of the offending part of the input.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UriError {
+    pub kind: UriErrorKind,
+    pub span: Range<usize>,
+}
+
+impl UriError {
+    fn new(kind: UriErrorKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Display for UriError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} at {}..{}",
+            self.kind.name(),
+            self.span.start,
+            self.span.end
+        )
+    }
+}
+
+impl Error for UriError {}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-2.3
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-2.2
+fn is_sub_delim(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'='
+    )
+}
+
+
+fn is_pchar(byte: u8) -> bool {
+    is_unreserved(byte) || is_sub_delim(byte) || matches!(byte, b':' | b'@')
+}
+
+
+fn is_scheme_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3987#section-2.2
+// The bidi formatting characters LRM, RLM, LRE, RLE, PDF, LRO and RLO are excluded, as IRIs must
+// not contain them (section 4.1).
+fn is_ucschar(c: char) -> bool {
+    let is_bidi_format: bool = matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}');
+    (matches!(c, '\u{a0}'..='\u{d7ff}' | '\u{f900}'..='\u{fdcf}' | '\u{fdf0}'..='\u{ffef}') && !is_bidi_format)
+        || (matches!(c, '\u{10000}'..='\u{dffff}' | '\u{e1000}'..='\u{effff}') && (c as u32 & 0xffff) < 0xfffe)
+}
+
+
+fn is_iprivate(c: char) -> bool {
+    matches!(c, '\u{e000}'..='\u{f8ff}') || (c >= '\u{f0000}' && (c as u32 & 0xffff) < 0xfffe)
+}
+
+
+// IRIs extend the URI grammar with non-ASCII characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Grammar {
+    Uri,
+    Iri,
+}
+
+impl Grammar {
+    // The non-ASCII characters allowed in components other than the query.
+    fn non_ascii(self) -> fn(char) -> bool {
+        match self {
+            Self::Uri => |_: char| false,
+            Self::Iri => is_ucschar,
+        }
+    }
+
+    fn non_ascii_in_query(self) -> fn(char) -> bool {
+        match self {
+            Self::Uri => |_: char| false,
+            Self::Iri => |c: char| is_ucschar(c) || is_iprivate(c),
+        }
+    }
+}
+
+
+// Checks that `input` is `*( allowed / pct-encoded )`, with `non_ascii` deciding on non-ASCII
+// characters. `offset` is the position of `input` in the whole input, for the error span.
+fn check(
+    input: &str,
+    offset: usize,
+    allowed: fn(u8) -> bool,
+    non_ascii: fn(char) -> bool,
+    kind: UriErrorKind,
+) -> Result<(), UriError> {
+    let bytes: &[u8] = input.as_bytes();
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if bytes.len() > i + 2 && hex_value(bytes[i + 1]).is_some() && hex_value(bytes[i + 2]).is_some() {
+                i += 3;
+                continue;
+            }
+            return Err(UriError::new(
+                UriErrorKind::InvalidPercentEncoding,
+                offset + i..offset + i + 1,
+            ));
+        }
+        let c: char = input[i..].chars().next().unwrap();
+        if !(if c.is_ascii() {
+            allowed(bytes[i])
+        } else {
+            non_ascii(c)
+        }) {
+            return Err(UriError::new(kind, offset + i..offset + i + c.len_utf8()));
+        }
+        i += c.len_utf8();
+    }
+    Ok(())
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-3.2.2
+fn check_host(host: &str, offset: usize, grammar: Grammar) -> Result<(), UriError> {
+    let Some(literal) = host.strip_prefix('[') else {
+        return check(
+            host,
+            offset,
+            |b: u8| is_unreserved(b) || is_sub_delim(b),
+            grammar.non_ascii(),
+            UriErrorKind::InvalidHost,
+        );
+    };
+    let error: UriError = UriError::new(UriErrorKind::InvalidHost, offset..offset + host.len());
+    let literal: &str = literal.strip_suffix(']').ok_or_else(|| error.clone())?;
+    if let Some(future) = literal.strip_prefix(['v', 'V']) {
+        // IPvFuture = "v" 1*HEXDIG "." 1*( unreserved / sub-delims / ":" )
+        let (version, address) = future.split_once('.').ok_or_else(|| error.clone())?;
+        let is_valid: bool = !version.is_empty()
+            && version.bytes().all(|b: u8| b.is_ascii_hexdigit())
+            && !address.is_empty()
+            && address
+                .bytes()
+                .all(|b: u8| is_unreserved(b) || is_sub_delim(b) || b == b':');
+        return if is_valid { Ok(()) } else { Err(error) };
+    }
+    literal.parse::<Ipv6Addr>().map(|_| ()).map_err(|_| error)
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Authority {
+    userinfo: Option<String>,
+    host: String,
+    port: Option<String>,
+}
+
+impl Authority {
+    // https://www.rfc-editor.org/rfc/rfc3986#section-3.2
+    fn parse(input: &str, offset: usize, grammar: Grammar) -> Result<Self, UriError> {
+        let (userinfo, host_port, host_offset) = match input.find('@') {
+            Some(at) => (Some(&input[..at]), &input[at + 1..], offset + at + 1),
+            None => (None, input, offset),
+        };
+        if let Some(userinfo) = userinfo {
+            check(
+                userinfo,
+                offset,
+                |b: u8| is_unreserved(b) || is_sub_delim(b) || b == b':',
+                grammar.non_ascii(),
+                UriErrorKind::InvalidUserinfo,
+            )?;
+        }
+        // A `:` inside an IP literal is not a port delimiter.
+        let host_end: usize = match host_port.strip_prefix('[') {
+            Some(literal) => {
+                literal
+                    .find(']')
+                    .map_or(host_port.len(), |end: usize| end + 2)
+            }
+            None => 0,
+        };
+        let (host, port) = match host_port[host_end..].find(':') {
+            Some(colon) => {
+                (
+                    &host_port[..host_end + colon],
+                    Some(&host_port[host_end + colon + 1..]),
+                )
+            }
+            None => (host_port, None),
+        };
+        check_host(host, host_offset, grammar)?;
+        if let Some(port) = port {
+            let port_offset: usize = host_offset + host.len() + 1;
+            if let Some(i) = port.find(|c: char| !c.is_ascii_digit()) {
+                let len: usize = port[i..].chars().next().map_or(1, char::len_utf8);
+                return Err(UriError::new(
+                    UriErrorKind::InvalidPort,
+                    port_offset + i..port_offset + i + len,
+                ));
+            }
+        }
+        Ok(Self {
+            userinfo: userinfo.map(str::to_string),
+            host: host.to_string(),
+            port: port.map(str::to_string),
+        })
+    }
+}
+
+impl Display for Authority {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        if let Some(userinfo) = &self.userinfo {
+            write!(fmt, "{}@", userinfo)?;
+        }
+        write!(fmt, "{}", self.host)?;
+        if let Some(port) = &self.port {
+            write!(fmt, ":{}", port)?;
+        }
+        Ok(())
+    }
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-4.1
+// A URI or a relative reference.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct UriRef {
+    serialization: String,
+    scheme: Option<String>,
+    authority: Option<Authority>,
+    path: String,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+impl UriRef {
+    fn new(
+        scheme: Option<String>,
+        authority: Option<Authority>,
+        path: String,
+        query: Option<String>,
+        fragment: Option<String>,
+    ) -> Self {
+        let mut reference: Self = Self {
+            serialization: String::new(),
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        };
+        reference.serialization = reference.recompose();
+        reference
+    }
+
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        Self::parse_with(input, Grammar::Uri)
+    }
+
+    fn parse_with(input: &str, grammar: Grammar) -> Result<Self, UriError> {
+        let mut scheme: Option<String> = None;
+        let mut rest: &str = input;
+        let mut offset: usize = 0;
+        // Anything before the first `:` that starts like a scheme must be one.
+        if let Some(colon) = input
+            .find([':', '/', '?', '#'])
+            .filter(|&i: &usize| input.as_bytes()[i] == b':')
+        {
+            let candidate: &str = &input[..colon];
+            if candidate.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if let Some(i) = candidate.find(|c: char| !is_scheme_char(c)) {
+                    let len: usize = candidate[i..].chars().next().map_or(1, char::len_utf8);
+                    return Err(UriError::new(UriErrorKind::InvalidScheme, i..i + len));
+                }
+                scheme = Some(candidate.to_string());
+                rest = &input[colon + 1..];
+                offset = colon + 1;
+            }
+        }
+
+        let mut fragment: Option<String> = None;
+        if let Some(hash) = rest.find('#') {
+            let value: &str = &rest[hash + 1..];
+            let allowed: fn(u8) -> bool = |b: u8| is_pchar(b) || b == b'/' || b == b'?';
+            check(
+                value,
+                offset + hash + 1,
+                allowed,
+                grammar.non_ascii(),
+                UriErrorKind::InvalidFragment,
+            )?;
+            fragment = Some(value.to_string());
+            rest = &rest[..hash];
+        }
+        let mut query: Option<String> = None;
+        if let Some(question) = rest.find('?') {
+            let value: &str = &rest[question + 1..];
+            let allowed: fn(u8) -> bool = |b: u8| is_pchar(b) || b == b'/' || b == b'?';
+            check(
+                value,
+                offset + question + 1,
+                allowed,
+                grammar.non_ascii_in_query(),
+                UriErrorKind::InvalidQuery,
+            )?;
+            query = Some(value.to_string());
+            rest = &rest[..question];
+        }
+
+        let mut authority: Option<Authority> = None;
+        if let Some(after) = rest.strip_prefix("//") {
+            let end: usize = after.find('/').unwrap_or(after.len());
+            authority = Some(Authority::parse(&after[..end], offset + 2, grammar)?);
+            rest = &after[end..];
+            offset += 2 + end;
+        }
+        if scheme.is_none() && authority.is_none() && !rest.starts_with('/') {
+            let first_segment: &str = rest.split('/').next().unwrap_or_default();
+            if let Some(colon) = first_segment.find(':') {
+                return Err(UriError::new(
+                    UriErrorKind::ColonInFirstSegment,
+                    offset + colon..offset + colon + 1,
+                ));
+            }
+        }
+        check(
+            rest,
+            offset,
+            |b: u8| is_pchar(b) || b == b'/',
+            grammar.non_ascii(),
+            UriErrorKind::InvalidPath,
+        )?;
+        Ok(Self::new(
+            scheme,
+            authority,
+            rest.to_string(),
+            query,
+            fragment,
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.serialization
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.scheme.is_none()
+    }
+
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    pub fn authority(&self) -> Option<String> {
+        self.authority
+            .as_ref()
+            .map(|authority: &Authority| authority.to_string())
+    }
+
+    pub fn userinfo(&self) -> Option<&str> {
+        self.authority.as_ref()?.userinfo.as_deref()
+    }
+
+    // IP literals keep their brackets.
+    pub fn host(&self) -> Option<&str> {
+        self.authority
+            .as_ref()
+            .map(|authority: &Authority| authority.host.as_str())
+    }
+
+    // May be empty, as in `http://example.com:/`.
+    pub fn port(&self) -> Option<&str> {
+        self.authority.as_ref()?.port.as_deref()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3986#section-5.2.2
+    // The strict variant: a reference with the base's scheme is not treated as relative.
+    pub fn resolve(&self, base: &Uri) -> Uri {
+        let base: &UriRef = &base.0;
+        let (scheme, authority, path, query) = if self.scheme.is_some() {
+            (
+                self.scheme.clone(),
+                self.authority.clone(),
+                remove_dot_segments(&self.path),
+                self.query.clone(),
+            )
+        } else if self.authority.is_some() {
+            (
+                base.scheme.clone(),
+                self.authority.clone(),
+                remove_dot_segments(&self.path),
+                self.query.clone(),
+            )
+        } else if self.path.is_empty() {
+            (
+                base.scheme.clone(),
+                base.authority.clone(),
+                base.path.clone(),
+                self.query.clone().or_else(|| base.query.clone()),
+            )
+        } else if self.path.starts_with('/') {
+            (
+                base.scheme.clone(),
+                base.authority.clone(),
+                remove_dot_segments(&self.path),
+                self.query.clone(),
+            )
+        } else {
+            (
+                base.scheme.clone(),
+                base.authority.clone(),
+                remove_dot_segments(&merge(base, &self.path)),
+                self.query.clone(),
+            )
+        };
+        Uri(Self::new(
+            scheme,
+            authority,
+            path,
+            query,
+            self.fragment.clone(),
+        ))
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3986#section-6.2.2
+    // Lowercases the scheme and host, uppercases percent-encodings, decodes percent-encoded unreserved
+    // characters and removes dot segments. Dot segments are kept in relative-path references, where
+    // they are meaningful.
+    pub fn normalize(&self) -> Self {
+        let authority: Option<Authority> = self.authority.as_ref().map(|authority: &Authority| {
+            Authority {
+                userinfo: authority
+                    .userinfo
+                    .as_deref()
+                    .map(|userinfo: &str| normalize_percent_encoding(userinfo, false)),
+                host: normalize_percent_encoding(&authority.host, true),
+                port: authority.port.clone(),
+            }
+        });
+        let mut path: String = normalize_percent_encoding(&self.path, false);
+        if self.scheme.is_some() || self.authority.is_some() || path.starts_with('/') {
+            path = remove_dot_segments(&path);
+        }
+        Self::new(
+            self.scheme.as_deref().map(str::to_ascii_lowercase),
+            authority,
+            path,
+            self.query
+                .as_deref()
+                .map(|query: &str| normalize_percent_encoding(query, false)),
+            self.fragment
+                .as_deref()
+                .map(|fragment: &str| normalize_percent_encoding(fragment, false)),
+        )
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3986#section-5.3
+    fn recompose(&self) -> String {
+        let mut output: String = String::new();
+        if let Some(scheme) = &self.scheme {
+            output += &format!("{}:", scheme);
+        }
+        if let Some(authority) = &self.authority {
+            output += &format!("//{}", authority);
+        }
+        output += &self.path;
+        if let Some(query) = &self.query {
+            output += &format!("?{}", query);
+        }
+        if let Some(fragment) = &self.fragment {
+            output += &format!("#{}", fragment);
+        }
+        output
+    }
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-5.2.3
+fn merge(base: &UriRef, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        return format!("/{}", path);
+    }
+    match base.path.rfind('/') {
+        Some(end) => format!("{}{}", &base.path[..=end], path),
+        None => path.to_string(),
+    }
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
+pub(crate) fn remove_dot_segments(path: &str) -> String {
+    let mut input: &str = path;
+    let mut output: String = String::with_capacity(path.len());
+    while !input.is_empty() {
+        if let Some(rest) = input
+            .strip_prefix("../")
+            .or_else(|| input.strip_prefix("./"))
+        {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input == "/.." { "/" } else { &input[3..] };
+            output.truncate(output.rfind('/').unwrap_or(0));
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let end: usize = input[1..].find('/').map_or(input.len(), |i: usize| i + 1);
+            output += &input[..end];
+            input = &input[end..];
+        }
+    }
+    output
+}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-6.2.2.1
+pub(crate) fn normalize_percent_encoding(input: &str, lowercase: bool) -> String {
+    let bytes: &[u8] = input.as_bytes();
+    let mut output: String = String::with_capacity(input.len());
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                let byte: u8 = high << 4 | low;
+                if is_unreserved(byte) {
+                    output.push(if lowercase {
+                        byte.to_ascii_lowercase()
+                    } else {
+                        byte
+                    } as char);
+                } else {
+                    output += &format!("%{:02X}", byte);
+                }
+                i += 3;
+                continue;
+            }
+        }
+        output.push(if lowercase {
+            bytes[i].to_ascii_lowercase()
+        } else {
+            bytes[i]
+        } as char);
+        i += 1;
+    }
+    output
+}
+
+
+impl AsRef<str> for UriRef {
+    fn as_ref(&self) -> &str {
+        &self.serialization
+    }
+}
+
+impl Debug for UriRef {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_tuple("UriRef")
+            .field(&self.serialization)
+            .finish()
+    }
+}
+
+impl Display for UriRef {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.serialization)
+    }
+}
+
+impl FromStr for UriRef {
+    type Err = UriError;
+
+    fn from_str(input: &str) -> Result<Self, UriError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<&str> for UriRef {
+    type Error = UriError;
+
+    fn try_from(input: &str) -> Result<Self, UriError> {
+        Self::parse(input)
+    }
+}
+
+impl From<Uri> for UriRef {
+    fn from(uri: Uri) -> Self {
+        uri.0
+    }
+}
+
+
+// Where `url::URL` and the RFC 3986 grammar disagree about the same string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Disagreement {
+    // The URL parser rejects the URI, e.g. `http://[v1.x]/` or `http://example.com:99999/`.
+    Rejected(ParseError),
+    // The URL parser accepts the URI but serializes it differently, e.g. `HTTP://example.com` to
+    // `http://example.com/`. The URL it produced is attached.
+    Rewritten(Box<URL>),
+}
+
+impl Display for Disagreement {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Rejected(err) => write!(fmt, "rejected by the URL parser: {}", err),
+            Self::Rewritten(url) => write!(fmt, "rewritten by the URL parser as {}", url),
+        }
+    }
+}
+
+impl Error for Disagreement {}
+
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-3
+// A reference with a scheme.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Uri(UriRef);
+
+impl Uri {
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        Self::try_from(UriRef::parse(input)?)
+    }
+
+    // Parses `input` as a reference and resolves it against this URI.
+    pub fn join(&self, input: &str) -> Result<Self, UriError> {
+        Ok(UriRef::parse(input)?.resolve(self))
+    }
+
+    pub fn scheme(&self) -> &str {
+        self.0.scheme.as_deref().unwrap_or_default()
+    }
+
+    pub fn normalize(&self) -> Self {
+        Self(self.0.normalize())
+    }
+
+    // The error span points into the URL's serialization.
+    pub fn from_url(url: &URL) -> Result<Self, UriError> {
+        Self::parse(url.href())
+    }
+
+    // Fails unless the URL parser keeps this URI as is.
+    pub fn to_url(&self) -> Result<URL, Disagreement> {
+        let url: URL = URL::parse(self.as_str()).map_err(Disagreement::Rejected)?;
+        if url.href() != self.as_str() {
+            return Err(Disagreement::Rewritten(Box::new(url)));
+        }
+        Ok(url)
+    }
+}
+
+impl Deref for Uri {
+    type Target = UriRef;
+
+    fn deref(&self) -> &UriRef {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Uri {
+    fn as_ref(&self) -> &str {
+        &self.0.serialization
+    }
+}
+
+impl Debug for Uri {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_tuple("Uri").field(&self.0.serialization).finish()
+    }
+}
+
+impl Display for Uri {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0.serialization)
+    }
+}
+
+impl FromStr for Uri {
+    type Err = UriError;
+
+    fn from_str(input: &str) -> Result<Self, UriError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<&str> for Uri {
+    type Error = UriError;
+
+    fn try_from(input: &str) -> Result<Self, UriError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<UriRef> for Uri {
+    type Error = UriError;
+
+    fn try_from(reference: UriRef) -> Result<Self, UriError> {
+        if reference.scheme.is_none() {
+            return Err(UriError::new(UriErrorKind::MissingScheme, 0..0));
+        }
+        Ok(Self(reference))
+    }
+}
+
+impl TryFrom<&URL> for Uri {
+    type Error = UriError;
+
+    fn try_from(url: &URL) -> Result<Self, UriError> {
+        Self::from_url(url)
+    }
+}
+
+impl TryFrom<&Uri> for URL {
+    type Error = Disagreement;
+
+    fn try_from(uri: &Uri) -> Result<Self, Disagreement> {
+        uri.to_url()
+    }
+}
@@ -0,0 +1,236 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://www.rfc-editor.org/rfc/rfc3987
+
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::{self, FromStr};
+
+use super::{is_iprivate, is_ucschar, Authority, Grammar, Uri, UriError, UriErrorKind, UriRef};
+use crate::idna::{self, Config};
+use crate::percent_encoding::{hex_value, utf8_percent_encode, NON_ASCII};
+
+
+// An IRI with a scheme, kept in its Unicode form.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Iri(UriRef);
+
+impl Iri {
+    pub fn parse(input: &str) -> Result<Self, UriError> {
+        let reference: UriRef = UriRef::parse_with(input, Grammar::Iri)?;
+        if reference.scheme.is_none() {
+            return Err(UriError::new(UriErrorKind::MissingScheme, 0..0));
+        }
+        Ok(Self(reference))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0.serialization
+    }
+
+    pub fn scheme(&self) -> &str {
+        self.0.scheme.as_deref().unwrap_or_default()
+    }
+
+    pub fn authority(&self) -> Option<String> {
+        self.0.authority()
+    }
+
+    pub fn userinfo(&self) -> Option<&str> {
+        self.0.userinfo()
+    }
+
+    pub fn host(&self) -> Option<&str> {
+        self.0.host()
+    }
+
+    pub fn port(&self) -> Option<&str> {
+        self.0.port()
+    }
+
+    pub fn path(&self) -> &str {
+        self.0.path()
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.0.query()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.0.fragment()
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3987#section-3.1
+    // Non-ASCII characters are UTF-8 percent-encoded, except in a host, which is converted with IDNA.
+    // A host that IDNA rejects is percent-encoded instead.
+    pub fn to_uri(&self) -> Uri {
+        let encode = |input: &str| -> String { utf8_percent_encode(input, &NON_ASCII) };
+        let authority: Option<Authority> = self.0.authority.as_ref().map(|authority: &Authority| {
+            Authority {
+                userinfo: authority.userinfo.as_deref().map(encode),
+                host: if authority.host.is_ascii() {
+                    authority.host.clone()
+                } else {
+                    idna::domain_to_ascii(&authority.host).unwrap_or_else(|| encode(&authority.host))
+                },
+                port: authority.port.clone(),
+            }
+        });
+        Uri(UriRef::new(
+            self.0.scheme.clone(),
+            authority,
+            encode(&self.0.path),
+            self.0.query.as_deref().map(encode),
+            self.0.fragment.as_deref().map(encode),
+        ))
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3987#section-3.2
+    // The reverse of `to_uri`, for display. Percent-encoded UTF-8 is decoded where the IRI grammar
+    // allows the character, and `xn--` labels are converted back to Unicode. Percent-encoded ASCII
+    // is kept as is.
+    pub fn from_uri(uri: &Uri) -> Self {
+        let authority: Option<Authority> = uri.0.authority.as_ref().map(|authority: &Authority| {
+            Authority {
+                userinfo: authority
+                    .userinfo
+                    .as_deref()
+                    .map(|userinfo: &str| decode(userinfo, is_ucschar)),
+                host: host_to_unicode(&authority.host),
+                port: authority.port.clone(),
+            }
+        });
+        Self(UriRef::new(
+            uri.0.scheme.clone(),
+            authority,
+            decode(&uri.0.path, is_ucschar),
+            uri.0
+                .query
+                .as_deref()
+                .map(|query: &str| decode(query, |c: char| is_ucschar(c) || is_iprivate(c))),
+            uri.0
+                .fragment
+                .as_deref()
+                .map(|fragment: &str| decode(fragment, is_ucschar)),
+        ))
+    }
+}
+
+
+fn host_to_unicode(host: &str) -> String {
+    let host: String = decode(host, is_ucschar);
+    // Labels can hold decoded non-ASCII characters, so the prefix is compared as bytes.
+    let is_ace = |label: &str| -> bool {
+        label
+            .as_bytes()
+            .get(..4)
+            .is_some_and(|prefix: &[u8]| prefix.eq_ignore_ascii_case(b"xn--"))
+    };
+    if !host.split('.').any(is_ace) {
+        return host;
+    }
+    match Config::default().to_unicode(&host) {
+        (unicode, false) => unicode,
+        (_, true) => host,
+    }
+}
+
+
+// Decodes runs of percent-encoded non-ASCII bytes that are valid UTF-8 and whose characters
+// `allowed` accepts. Everything else keeps its original form.
+fn decode(input: &str, allowed: fn(char) -> bool) -> String {
+    let bytes: &[u8] = input.as_bytes();
+    let mut output: String = String::with_capacity(input.len());
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        let start: usize = i;
+        let mut decoded: Vec<u8> = Vec::new();
+        while bytes.len() > i + 2 && bytes[i] == b'%' {
+            match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                (Some(high), Some(low)) if high >= 8 => decoded.push(high << 4 | low),
+                _ => break,
+            }
+            i += 3;
+        }
+        if decoded.is_empty() {
+            let c: char = input[i..].chars().next().unwrap();
+            output.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+        // Each decoded byte came from three bytes of `input`, starting at `start`.
+        let mut position: usize = 0;
+        while position < decoded.len() {
+            let (valid, invalid_len): (&str, usize) = match str::from_utf8(&decoded[position..]) {
+                Ok(valid) => (valid, 0),
+                Err(err) => {
+                    (
+                        str::from_utf8(&decoded[position..position + err.valid_up_to()]).unwrap(),
+                        err.error_len()
+                            .unwrap_or(decoded.len() - position - err.valid_up_to()),
+                    )
+                }
+            };
+            for c in valid.chars() {
+                if allowed(c) {
+                    output.push(c);
+                } else {
+                    let original: usize = start + position * 3;
+                    output += &input[original..original + c.len_utf8() * 3];
+                }
+                position += c.len_utf8();
+            }
+            let original: usize = start + position * 3;
+            output += &input[original..original + invalid_len * 3];
+            position += invalid_len;
+        }
+    }
+    output
+}
+
+
+impl AsRef<str> for Iri {
+    fn as_ref(&self) -> &str {
+        &self.0.serialization
+    }
+}
+
+impl Debug for Iri {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_tuple("Iri").field(&self.0.serialization).finish()
+    }
+}
+
+impl Display for Iri {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0.serialization)
+    }
+}
+
+impl FromStr for Iri {
+    type Err = UriError;
+
+    fn from_str(input: &str) -> Result<Self, UriError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<&str> for Iri {
+    type Error = UriError;
+
+    fn try_from(input: &str) -> Result<Self, UriError> {
+        Self::parse(input)
+    }
+}
+
+impl From<&Iri> for Uri {
+    fn from(iri: &Iri) -> Self {
+        iri.to_uri()
+    }
+}
+
+impl From<&Uri> for Iri {
+    fn from(uri: &Uri) -> Self {
+        Self::from_uri(uri)
+    }
+}
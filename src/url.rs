@@ -1,10 +1,558 @@
 // SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
 // SPDX-License-Identifier: Apache-2.0
 
-pub struct URL {
+// https://url.spec.whatwg.org/
+
+mod builder;
+mod file;
+mod host;
+mod normalize;
+mod origin;
+mod parser;
+mod path_segments;
+mod redact;
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::str::FromStr;
+
+pub use builder::{DotSegmentError, URLBuilder};
+pub use host::Host;
+pub use normalize::NormalizeOptions;
+pub use origin::{OpaqueOrigin, Origin};
+use parser::State;
+pub use path_segments::{OpaquePathError, PathSegments, PathSegmentsMut};
+pub use redact::{RedactOptions, Redacted};
+
+use crate::percent_encoding::{utf8_percent_encode, USERINFO};
+
+
+// Declares `ParseError` with one span-carrying variant per fatal validation error.
+macro_rules! parse_errors {
+    ($($variant:ident => $name:literal,)*) => {
+        // A validation error that makes the URL parser return failure.
+        // Each variant holds the byte range of the offending part of the input.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum ParseError {
+            $($variant(Range<usize>),)*
+        }
+
+        impl ParseError {
+            // The error names used by the URL Standard.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant(_) => $name,)*
+                }
+            }
+
+            pub fn span(&self) -> Range<usize> {
+                match self {
+                    $(Self::$variant(span) => span.clone(),)*
+                }
+            }
+
+            // Moves the span by `offset` bytes, e.g. from host-relative to URL-relative.
+            pub(crate) fn offset(self, offset: usize) -> Self {
+                match self {
+                    $(Self::$variant(span) => Self::$variant(span.start + offset..span.end + offset),)*
+                }
+            }
+        }
+    };
+}
+
+parse_errors! {
+    DomainToAscii => "domain-to-ASCII",
+    DomainInvalidCodePoint => "domain-invalid-code-point",
+    HostInvalidCodePoint => "host-invalid-code-point",
+    Ipv4TooManyParts => "IPv4-too-many-parts",
+    Ipv4NonNumericPart => "IPv4-non-numeric-part",
+    Ipv4OutOfRangePart => "IPv4-out-of-range-part",
+    Ipv6Unclosed => "IPv6-unclosed",
+    Ipv6InvalidCompression => "IPv6-invalid-compression",
+    Ipv6TooManyPieces => "IPv6-too-many-pieces",
+    Ipv6MultipleCompression => "IPv6-multiple-compression",
+    Ipv6InvalidCodePoint => "IPv6-invalid-code-point",
+    Ipv6TooFewPieces => "IPv6-too-few-pieces",
+    Ipv4InIpv6TooManyPieces => "IPv4-in-IPv6-too-many-pieces",
+    Ipv4InIpv6InvalidCodePoint => "IPv4-in-IPv6-invalid-code-point",
+    Ipv4InIpv6OutOfRangePart => "IPv4-in-IPv6-out-of-range-part",
+    Ipv4InIpv6TooFewParts => "IPv4-in-IPv6-too-few-parts",
+    MissingSchemeNonRelativeUrl => "missing-scheme-non-relative-URL",
+    HostMissing => "host-missing",
+    PortOutOfRange => "port-out-of-range",
+    PortInvalid => "port-invalid",
+    InvalidScheme => "invalid-scheme",
+}
+
+impl Display for ParseError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        let span: Range<usize> = self.span();
+        write!(fmt, "{} at {}..{}", self.name(), span.start, span.end)
+    }
+}
+
+impl Error for ParseError {}
+
+
+// A validation error the URL parser recovers from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    InvalidUrlUnit,
+    SpecialSchemeMissingFollowingSolidus,
+    InvalidReverseSolidus,
+    InvalidCredentials,
+    FileInvalidWindowsDriveLetter,
+    FileInvalidWindowsDriveLetterHost,
+    Ipv4EmptyPart,
+    Ipv4NonDecimalPart,
+    Ipv4OutOfRangePart,
+}
+
+impl ViolationKind {
+    // The error names used by the URL Standard.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InvalidUrlUnit => "invalid-URL-unit",
+            Self::SpecialSchemeMissingFollowingSolidus => "special-scheme-missing-following-solidus",
+            Self::InvalidReverseSolidus => "invalid-reverse-solidus",
+            Self::InvalidCredentials => "invalid-credentials",
+            Self::FileInvalidWindowsDriveLetter => "file-invalid-Windows-drive-letter",
+            Self::FileInvalidWindowsDriveLetterHost => "file-invalid-Windows-drive-letter-host",
+            Self::Ipv4EmptyPart => "IPv4-empty-part",
+            Self::Ipv4NonDecimalPart => "IPv4-non-decimal-part",
+            Self::Ipv4OutOfRangePart => "IPv4-out-of-range-part",
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    pub span: Range<usize>,
+}
+
+impl Violation {
+    pub(crate) fn new(kind: ViolationKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} at {}..{}",
+            self.kind.name(),
+            self.span.start,
+            self.span.end
+        )
+    }
+}
+
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Path {
+    Opaque(String),
+    Segments(Vec<String>),
+}
+
+
+#[derive(Clone)]
+pub struct URL {
+    serialization: String,
+    scheme: String,
+    username: String,
+    password: String,
+    host: Option<Host>,
+    port: Option<u16>,
+    path: Path,
+    query: Option<String>,
+    fragment: Option<String>,
 }
 
 impl URL {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        parser::parse(input, None)
+    }
+
+    pub fn parse_with_base(input: &str, base: &Self) -> Result<Self, ParseError> {
+        parser::parse(input, Some(base))
+    }
+
+    pub fn join(&self, input: &str) -> Result<Self, ParseError> {
+        parser::parse(input, Some(self))
+    }
+
+    // Parses like `parse` but also returns the validation errors the parser recovered from,
+    // in the order they were encountered.
+    pub fn parse_with_violations(input: &str, base: Option<&Self>) -> Result<(Self, Vec<Violation>), ParseError> {
+        let mut violations: Vec<Violation> = Vec::new();
+        let url: Self = parser::parse_with_violations(input, base, &mut violations)?;
+        Ok((url, violations))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.serialization
+    }
+
+    pub fn href(&self) -> &str {
+        &self.serialization
+    }
+
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    pub fn is_special(&self) -> bool {
+        is_special_scheme(&self.scheme)
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    pub fn includes_credentials(&self) -> bool {
+        !self.username.is_empty() || !self.password.is_empty()
+    }
+
+    pub fn host(&self) -> Option<&Host> {
+        self.host.as_ref()
+    }
+
+    pub fn host_str(&self) -> Option<String> {
+        self.host.as_ref().map(|host: &Host| host.to_string())
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    pub fn port_or_known_default(&self) -> Option<u16> {
+        self.port.or_else(|| default_port(&self.scheme))
+    }
+
+    // https://url.spec.whatwg.org/#url-opaque-path
+    // e.g. `mailto:`, `data:`, `javascript:` and `urn:` URLs. They cannot be used as a base for
+    // relative URLs.
+    pub fn has_opaque_path(&self) -> bool {
+        matches!(self.path, Path::Opaque(_))
+    }
+
+    // https://url.spec.whatwg.org/#url-path-serializer
+    pub fn path(&self) -> String {
+        match &self.path {
+            Path::Opaque(path) => path.clone(),
+            Path::Segments(segments) => {
+                segments
+                    .iter()
+                    .fold(String::new(), |acc, segment| acc + "/" + segment)
+            }
+        }
+    }
+
+    // The percent-encoded segments of the path.
+    pub fn path_segments(&self) -> Result<PathSegments<'_>, OpaquePathError> {
+        match &self.path {
+            Path::Segments(segments) => Ok(PathSegments::new(segments)),
+            Path::Opaque(_) => Err(OpaquePathError),
+        }
+    }
+
+    pub fn path_segments_mut(&mut self) -> Result<PathSegmentsMut<'_>, OpaquePathError> {
+        if self.has_opaque_path() {
+            return Err(OpaquePathError);
+        }
+        Ok(PathSegmentsMut::new(self))
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-href
+    pub fn set_href(&mut self, value: &str) -> Result<(), ParseError> {
+        *self = Self::parse(value)?;
+        Ok(())
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-protocol
+    pub fn set_protocol(&mut self, value: &str) {
+        self.parse_with_override(&format!("{}:", value), State::SchemeStart);
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-username
+    pub fn set_username(&mut self, value: &str) {
+        if self.cannot_have_credentials_or_port() {
+            return;
+        }
+        self.username = utf8_percent_encode(value, &USERINFO);
+        self.update_serialization();
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-password
+    pub fn set_password(&mut self, value: &str) {
+        if self.cannot_have_credentials_or_port() {
+            return;
+        }
+        self.password = utf8_percent_encode(value, &USERINFO);
+        self.update_serialization();
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-host
+    pub fn set_host(&mut self, value: &str) {
+        if self.has_opaque_path() {
+            return;
+        }
+        self.parse_with_override(value, State::Host);
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-hostname
+    pub fn set_hostname(&mut self, value: &str) {
+        if self.has_opaque_path() {
+            return;
+        }
+        self.parse_with_override(value, State::Hostname);
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-port
+    pub fn set_port(&mut self, value: &str) {
+        if self.cannot_have_credentials_or_port() {
+            return;
+        }
+        if value.is_empty() {
+            self.port = None;
+            self.update_serialization();
+        } else {
+            self.parse_with_override(value, State::Port);
+        }
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-pathname
+    pub fn set_pathname(&mut self, value: &str) {
+        if self.has_opaque_path() {
+            return;
+        }
+        self.path = Path::Segments(Vec::new());
+        self.parse_with_override(value, State::PathStart);
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-search
+    pub fn set_search(&mut self, value: &str) {
+        if value.is_empty() {
+            self.query = None;
+            self.strip_trailing_spaces_from_opaque_path();
+            self.update_serialization();
+            return;
+        }
+        self.query = Some(String::new());
+        self.parse_with_override(value.strip_prefix('?').unwrap_or(value), State::Query);
+    }
+
+    // https://url.spec.whatwg.org/#dom-url-hash
+    pub fn set_hash(&mut self, value: &str) {
+        if value.is_empty() {
+            self.fragment = None;
+            self.strip_trailing_spaces_from_opaque_path();
+            self.update_serialization();
+            return;
+        }
+        self.fragment = Some(String::new());
+        self.parse_with_override(value.strip_prefix('#').unwrap_or(value), State::Fragment);
+    }
+
+    // Runs the basic URL parser on this URL with a state override.
+    // Setters ignore failures, keeping whatever was changed before the failure.
+    fn parse_with_override(&mut self, input: &str, state_override: State) {
+        let _ = parser::parse_with_override(input, self, state_override);
+    }
+
+    // The hostname and port setters without the setter checks, reporting failure.
+    // Used to canonicalize URL pattern components.
+    #[cfg(feature = "pattern")]
+    pub(crate) fn try_set_hostname(&mut self, value: &str) -> Result<(), ParseError> {
+        parser::parse_with_override(value, self, State::Hostname)
+    }
+
+    #[cfg(feature = "pattern")]
+    pub(crate) fn try_set_port(&mut self, value: &str) -> Result<(), ParseError> {
+        parser::parse_with_override(value, self, State::Port)
+    }
+
+    // https://url.spec.whatwg.org/#cannot-have-a-username-password-port
+    fn cannot_have_credentials_or_port(&self) -> bool {
+        self.host.as_ref().map_or(true, Host::is_empty) || self.scheme == "file"
+    }
+
+    // https://url.spec.whatwg.org/#potentially-strip-trailing-spaces-from-an-opaque-path
+    fn strip_trailing_spaces_from_opaque_path(&mut self) {
+        if self.fragment.is_some() || self.query.is_some() {
+            return;
+        }
+        if let Path::Opaque(path) = &mut self.path {
+            path.truncate(path.trim_end_matches(' ').len());
+        }
+    }
+
+    // https://url.spec.whatwg.org/#concept-url-serializer
+    fn serialize(&self, exclude_fragment: bool) -> String {
+        let mut output: String = format!("{}:", self.scheme);
+        if let Some(host) = &self.host {
+            output += "//";
+            if self.includes_credentials() {
+                output += &self.username;
+                if !self.password.is_empty() {
+                    output += &format!(":{}", self.password);
+                }
+                output.push('@');
+            }
+            output += &host.to_string();
+            if let Some(port) = self.port {
+                output += &format!(":{}", port);
+            }
+        }
+        if let Path::Segments(segments) = &self.path {
+            if self.host.is_none() && segments.len() > 1 && segments[0].is_empty() {
+                output += "/.";
+            }
+        }
+        output += &self.path();
+        if let Some(query) = &self.query {
+            output += &format!("?{}", query);
+        }
+        if !exclude_fragment {
+            if let Some(fragment) = &self.fragment {
+                output += &format!("#{}", fragment);
+            }
+        }
+        output
+    }
+
+    fn update_serialization(&mut self) {
+        self.serialization = self.serialize(false);
+    }
+}
+
+impl AsRef<str> for URL {
+    fn as_ref(&self) -> &str {
+        &self.serialization
+    }
+}
+
+impl Debug for URL {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_tuple("URL").field(&self.serialization).finish()
+    }
+}
+
+impl Display for URL {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.serialization)
+    }
+}
+
+impl FromStr for URL {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, ParseError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<&str> for URL {
+    type Error = ParseError;
+
+    fn try_from(input: &str) -> Result<Self, ParseError> {
+        Self::parse(input)
+    }
+}
+
+impl TryFrom<String> for URL {
+    type Error = ParseError;
+
+    fn try_from(input: String) -> Result<Self, ParseError> {
+        Self::parse(&input)
+    }
+}
+
+impl PartialEq for URL {
+    fn eq(&self, other: &Self) -> bool {
+        self.serialization == other.serialization
+    }
+}
+
+impl Eq for URL {}
+
+impl PartialOrd for URL {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for URL {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.serialization.cmp(&other.serialization)
+    }
+}
+
+impl Hash for URL {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.serialization.hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for URL {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.serialization)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for URL {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct URLVisitor;
+
+        impl serde::de::Visitor<'_> for URLVisitor {
+            type Value = URL;
+
+            fn expecting(&self, fmt: &mut Formatter) -> fmt::Result {
+                write!(fmt, "a URL string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, input: &str) -> Result<URL, E> {
+                URL::parse(input).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(URLVisitor)
+    }
+}
+
+
+// https://url.spec.whatwg.org/#special-scheme
+pub fn is_special_scheme(scheme: &str) -> bool {
+    matches!(scheme, "ftp" | "file" | "http" | "https" | "ws" | "wss")
+}
+
 
+pub fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "ftp" => Some(21),
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None,
+    }
 }
@@ -0,0 +1,195 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Builds a URL from unencoded components, e.g.
+// `URLBuilder::new().scheme("https").host("example.com").path_segments(["a", "b c"])?.build()`.
+// Each component is percent-encoded with its encode set plus `%`, so values are taken literally.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use super::{is_special_scheme, Host, ParseError, URL};
+use crate::percent_encoding::{utf8_percent_encode, AsciiSet, COMPONENT, FRAGMENT, PATH, PATH_SEGMENT, USERINFO};
+use crate::QueryParams;
+
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct URLBuilder {
+    scheme: String,
+    username: String,
+    password: String,
+    host: Option<String>,
+    port: Option<u16>,
+    // Encoded.
+    path: String,
+    // Encoded pairs without the leading `?`.
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+impl URLBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn scheme(mut self, scheme: &str) -> Self {
+        self.scheme = scheme.to_string();
+        self
+    }
+
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = username.to_string();
+        self
+    }
+
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = password.to_string();
+        self
+    }
+
+    // A domain, an IPv4 address or a bracketed IPv6 address.
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    // Replaces the path. `/` separates segments.
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = encode(path, &PATH);
+        self
+    }
+
+    // Replaces the path. `/` within a segment is encoded. `.` and `..` are rejected, as the parser
+    // would remove them and the path would no longer have the given segments.
+    pub fn path_segments<I: IntoIterator<Item = S>, S: AsRef<str>>(mut self, segments: I) -> Result<Self, DotSegmentError> {
+        let mut path: String = String::new();
+        for segment in segments {
+            let segment: &str = segment.as_ref();
+            if matches!(segment, "." | "..") {
+                return Err(DotSegmentError);
+            }
+            path += &format!("/{}", utf8_percent_encode(segment, &PATH_SEGMENT));
+        }
+        self.path = path;
+        Ok(self)
+    }
+
+    // Appends `key=value` to the query, encoded like `crate::query::to_query_params`.
+    pub fn query_pair(self, key: &str, value: &str) -> Self {
+        let pair: String = format!(
+            "{}={}",
+            utf8_percent_encode(key, &COMPONENT),
+            utf8_percent_encode(value, &COMPONENT)
+        );
+        self.append_query(&pair)
+    }
+
+    // Appends the pairs of a `#[derive(QueryParams)]` struct to the query, encoded like
+    // `QueryParams::to_query_params`: the elements of a list one by one, joined with `,`.
+    pub fn query<T: QueryParams + ?Sized>(self, params: &T) -> Self {
+        params.to_query_pairs().iter().fold(
+            self,
+            |builder: Self, (key, values): &(String, Vec<String>)| {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value: &String| utf8_percent_encode(value, &COMPONENT))
+                    .collect();
+                let pair: String = format!(
+                    "{}={}",
+                    utf8_percent_encode(key, &COMPONENT),
+                    values.join(",")
+                );
+                builder.append_query(&pair)
+            },
+        )
+    }
+
+    pub fn fragment(mut self, fragment: &str) -> Self {
+        self.fragment = Some(encode(fragment, &FRAGMENT));
+        self
+    }
+
+    // Errors from the scheme and host carry spans into that component, any other into the serialized
+    // URL.
+    pub fn build(&self) -> Result<URL, ParseError> {
+        let is_valid_scheme: bool = self.scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && self
+                .scheme
+                .chars()
+                .all(|c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !is_valid_scheme {
+            return Err(ParseError::InvalidScheme(0..self.scheme.len()));
+        }
+        let scheme: String = self.scheme.to_ascii_lowercase();
+        let mut input: String = format!("{}:", scheme);
+        match &self.host {
+            Some(host) => {
+                // Parsed on its own so that e.g. `/` cannot move the rest of the host into the path.
+                let host: Host = if is_special_scheme(&scheme) {
+                    Host::parse(host)?
+                } else {
+                    Host::parse_opaque(host)?
+                };
+                input += "//";
+                if !self.username.is_empty() || !self.password.is_empty() {
+                    input += &encode(&self.username, &USERINFO);
+                    if !self.password.is_empty() {
+                        input += &format!(":{}", encode(&self.password, &USERINFO));
+                    }
+                    input.push('@');
+                }
+                input += &host.to_string();
+                if let Some(port) = self.port {
+                    input += &format!(":{}", port);
+                }
+                if !self.path.is_empty() && !self.path.starts_with('/') {
+                    input.push('/');
+                }
+            }
+            None if scheme == "file" => input += "//",
+            None if is_special_scheme(&scheme) => return Err(ParseError::HostMissing(input.len()..input.len())),
+            // A path starting with `//` would be read as a host.
+            None if self.path.starts_with("//") => input += "/.",
+            None => {}
+        }
+        input += &self.path;
+        if let Some(query) = &self.query {
+            input += &format!("?{}", query);
+        }
+        if let Some(fragment) = &self.fragment {
+            input += &format!("#{}", fragment);
+        }
+        URL::parse(&input)
+    }
+
+    fn append_query(mut self, pairs: &str) -> Self {
+        match &mut self.query {
+            Some(query) => *query += &format!("&{}", pairs),
+            None => self.query = Some(pairs.to_string()),
+        }
+        self
+    }
+}
+
+
+// A segment given to `URLBuilder::path_segments` is `.` or `..`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DotSegmentError;
+
+impl Display for DotSegmentError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "path segment is `.` or `..`")
+    }
+}
+
+impl Error for DotSegmentError {}
+
+
+fn encode(input: &str, set: &AsciiSet) -> String {
+    utf8_percent_encode(input, &set.add(b'%'))
+}
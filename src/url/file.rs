@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Conversions between `file:` URLs and filesystem paths.
+// The POSIX and Windows variants work on any platform; the `Path` based ones use the current
+// platform's rules.
+
+#[cfg(not(windows))]
+use std::ffi::{OsStr, OsString};
+use std::path::{Path as FsPath, PathBuf};
+
+use super::{Host, Path, URL};
+use crate::percent_encoding::{percent_decode, percent_encode, AsciiSet, PATH};
+
+
+// `%` is encoded so that file names round-trip, `\` and `|` so that the parser does not reinterpret
+// them.
+const SEGMENT: AsciiSet = PATH.add(b'%').add(b'/').add(b'\\').add(b'|');
+
+
+#[cfg(unix)]
+fn os_str_to_bytes(input: &OsStr) -> Option<&[u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(input.as_bytes())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_str_to_bytes(input: &OsStr) -> Option<&[u8]> {
+    input.to_str().map(str::as_bytes)
+}
+
+
+#[cfg(unix)]
+fn os_string_from_bytes(input: Vec<u8>) -> Option<OsString> {
+    use std::os::unix::ffi::OsStringExt;
+    Some(OsString::from_vec(input))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_string_from_bytes(input: Vec<u8>) -> Option<OsString> {
+    String::from_utf8(input).ok().map(OsString::from)
+}
+
+
+fn from_posix_bytes(path: &[u8], is_directory: bool) -> Option<URL> {
+    let path: &[u8] = path.strip_prefix(b"/")?;
+    let mut url: String = String::from("file://");
+    for segment in path.split(|&b: &u8| b == b'/') {
+        url.push('/');
+        url += &percent_encode(segment, &SEGMENT);
+    }
+    if is_directory && !url.ends_with('/') {
+        url.push('/');
+    }
+    URL::parse(&url).ok()
+}
+
+
+fn from_windows(path: &str, is_directory: bool) -> Option<URL> {
+    // Verbatim paths (`\\?\`) only use backslashes as separators.
+    let (host, rest, is_verbatim) = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        let (host, rest) = rest.split_once('\\').unwrap_or((rest, ""));
+        (host, rest, true)
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        ("", rest, true)
+    } else if let Some(rest) = path.strip_prefix(r"\\").or_else(|| path.strip_prefix("//")) {
+        if rest.starts_with(['.', '?']) {
+            return None;
+        }
+        let (host, rest) = rest.split_once(['\\', '/']).unwrap_or((rest, ""));
+        (host, rest, false)
+    } else {
+        ("", path, false)
+    };
+    let is_separator = |c: char| -> bool { c == '\\' || (!is_verbatim && c == '/') };
+    let mut url: String = format!("file://{}", host);
+    let mut segments = rest.split(is_separator);
+    if host.is_empty() {
+        let drive: &str = segments.next()?;
+        let bytes: &[u8] = drive.as_bytes();
+        if bytes.len() != 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' || drive.len() == rest.len() {
+            return None;
+        }
+        url = format!("{}/{}", url, drive);
+    } else if host.contains(['/', '\\']) {
+        return None;
+    }
+    for segment in segments {
+        url.push('/');
+        url += &percent_encode(segment.as_bytes(), &SEGMENT);
+    }
+    if is_directory && !url.ends_with('/') {
+        url.push('/');
+    }
+    URL::parse(&url).ok()
+}
+
+
+impl URL {
+    // Converts an absolute path using the current platform's rules.
+    // Returns `None` for relative paths.
+    pub fn from_file_path<P: AsRef<FsPath>>(path: P) -> Option<Self> {
+        Self::from_native_path(path.as_ref(), false)
+    }
+
+    // Like `from_file_path`, but the URL always ends with a slash so that it can be used as a base.
+    pub fn from_directory_path<P: AsRef<FsPath>>(path: P) -> Option<Self> {
+        Self::from_native_path(path.as_ref(), true)
+    }
+
+    #[cfg(windows)]
+    fn from_native_path(path: &FsPath, is_directory: bool) -> Option<Self> {
+        from_windows(path.to_str()?, is_directory)
+    }
+
+    #[cfg(not(windows))]
+    fn from_native_path(path: &FsPath, is_directory: bool) -> Option<Self> {
+        from_posix_bytes(os_str_to_bytes(path.as_os_str())?, is_directory)
+    }
+
+    // Returns `None` if this is not a `file:` URL or it cannot be represented as a path on this
+    // platform.
+    #[cfg(windows)]
+    pub fn to_file_path(&self) -> Option<PathBuf> {
+        self.to_windows_path().map(PathBuf::from)
+    }
+
+    #[cfg(not(windows))]
+    pub fn to_file_path(&self) -> Option<PathBuf> {
+        os_string_from_bytes(self.posix_path_bytes()?).map(PathBuf::from)
+    }
+
+    // e.g. `/tmp/a b` to `file:///tmp/a%20b`.
+    pub fn from_posix_path(path: &str) -> Option<Self> {
+        from_posix_bytes(path.as_bytes(), false)
+    }
+
+    pub fn from_posix_directory_path(path: &str) -> Option<Self> {
+        from_posix_bytes(path.as_bytes(), true)
+    }
+
+    // Returns `None` for URLs with a host, as POSIX paths cannot name one.
+    pub fn to_posix_path(&self) -> Option<String> {
+        String::from_utf8(self.posix_path_bytes()?).ok()
+    }
+
+    fn posix_path_bytes(&self) -> Option<Vec<u8>> {
+        if self.scheme != "file" || !self.host.as_ref().map_or(true, Host::is_empty) {
+            return None;
+        }
+        let Path::Segments(segments) = &self.path else {
+            return None;
+        };
+        let mut output: Vec<u8> = Vec::new();
+        for segment in segments {
+            let decoded: Vec<u8> = percent_decode(segment.as_bytes());
+            if decoded.contains(&b'/') || decoded.contains(&0) {
+                return None;
+            }
+            output.push(b'/');
+            output.extend(decoded);
+        }
+        if output.is_empty() {
+            output.push(b'/');
+        }
+        Some(output)
+    }
+
+    // Accepts drive paths (`C:\dir`), UNC paths (`\\server\share`) and their verbatim forms
+    // (`\\?\C:\dir`, `\\?\UNC\server\share`). Forward slashes are accepted as separators except in
+    // verbatim paths.
+    pub fn from_windows_path(path: &str) -> Option<Self> {
+        from_windows(path, false)
+    }
+
+    pub fn from_windows_directory_path(path: &str) -> Option<Self> {
+        from_windows(path, true)
+    }
+
+    // URLs with a host become UNC paths; others must start with a drive letter.
+    pub fn to_windows_path(&self) -> Option<String> {
+        if self.scheme != "file" {
+            return None;
+        }
+        let Path::Segments(segments) = &self.path else {
+            return None;
+        };
+        let mut decoded: Vec<String> = Vec::with_capacity(segments.len());
+        for segment in segments {
+            let segment: String = String::from_utf8(percent_decode(segment.as_bytes())).ok()?;
+            if segment.contains(['/', '\\', '\0']) {
+                return None;
+            }
+            decoded.push(segment);
+        }
+        match self.host.as_ref().filter(|host: &&Host| !host.is_empty()) {
+            Some(host) => Some(format!(r"\\{}\{}", host, decoded.join(r"\"))),
+            None => {
+                let drive: &[u8] = segments.first()?.as_bytes();
+                if drive.len() != 2 || !drive[0].is_ascii_alphabetic() || drive[1] != b':' {
+                    return None;
+                }
+                let mut output: String = decoded.join(r"\");
+                if decoded.len() == 1 {
+                    output.push('\\');
+                }
+                Some(output)
+            }
+        }
+    }
+}
@@ -0,0 +1,365 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://url.spec.whatwg.org/#hosts-(domains-and-ip-addresses)
+
+use std::fmt::{self, Display, Formatter};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+
+use super::parser::is_url_code_point;
+use super::{ParseError, Violation, ViolationKind};
+use crate::idna;
+use crate::percent_encoding::{percent_decode, utf8_percent_encode, C0_CONTROL};
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Host {
+    Domain(String),
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+    Opaque(String),
+    Empty,
+}
+
+impl Host {
+    // https://url.spec.whatwg.org/#concept-host-parser
+    // Error spans are byte ranges into `input`.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, false, &mut Vec::new())
+    }
+
+    pub fn parse_opaque(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, true, &mut Vec::new())
+    }
+
+    pub(crate) fn parse_with(input: &str, is_opaque: bool, violations: &mut Vec<Violation>) -> Result<Self, ParseError> {
+        if let Some(address) = input.strip_prefix('[') {
+            return match address.strip_suffix(']') {
+                Some(address) => {
+                    Ok(Self::Ipv6(
+                        parse_ipv6(address).map_err(|err: ParseError| err.offset(1))?,
+                    ))
+                }
+                None => Err(ParseError::Ipv6Unclosed(0..input.len())),
+            };
+        }
+        if is_opaque {
+            return parse_opaque_host(input, violations);
+        }
+        let domain: String = String::from_utf8_lossy(&percent_decode(input.as_bytes())).into_owned();
+        // The domain no longer lines up with the input once mapped, so errors cover the whole host.
+        let span: Range<usize> = 0..input.len();
+        let ascii_domain: String = match idna::domain_to_ascii(&domain) {
+            Some(ascii_domain) if !ascii_domain.is_empty() => ascii_domain,
+            _ => return Err(ParseError::DomainToAscii(span)),
+        };
+        if ascii_domain.chars().any(is_forbidden_domain_code_point) {
+            return Err(ParseError::DomainInvalidCodePoint(span));
+        }
+        if ends_in_a_number(&ascii_domain) {
+            return Ok(Self::Ipv4(parse_ipv4(&ascii_domain, span, violations)?));
+        }
+        Ok(Self::Domain(ascii_domain))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+}
+
+impl Display for Host {
+    // https://url.spec.whatwg.org/#concept-host-serializer
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Domain(domain) => write!(fmt, "{}", domain),
+            Self::Ipv4(address) => write!(fmt, "{}", address),
+            Self::Ipv6(address) => write!(fmt, "[{}]", serialize_ipv6(address)),
+            Self::Opaque(host) => write!(fmt, "{}", host),
+            Self::Empty => Ok(()),
+        }
+    }
+}
+
+
+pub(crate) fn is_forbidden_host_code_point(c: char) -> bool {
+    matches!(
+        c,
+        '\0' | '\t' | '\n' | '\r' | ' ' | '#' | '/' | ':' | '<' | '>' | '?' | '@' | '[' | '\\' | ']' | '^' | '|'
+    )
+}
+
+
+pub(crate) fn is_forbidden_domain_code_point(c: char) -> bool {
+    is_forbidden_host_code_point(c) || matches!(c, '\0'..='\x1f' | '%' | '\x7f')
+}
+
+
+// https://url.spec.whatwg.org/#concept-opaque-host-parser
+fn parse_opaque_host(input: &str, violations: &mut Vec<Violation>) -> Result<Host, ParseError> {
+    if input.is_empty() {
+        return Ok(Host::Empty);
+    }
+    if let Some((i, c)) = input
+        .char_indices()
+        .find(|&(_, c): &(usize, char)| is_forbidden_host_code_point(c))
+    {
+        return Err(ParseError::HostInvalidCodePoint(i..i + c.len_utf8()));
+    }
+    for (i, c) in input.char_indices() {
+        let is_invalid_percent: bool = c == '%'
+            && !input
+                .as_bytes()
+                .get(i + 1..i + 3)
+                .is_some_and(|hex: &[u8]| hex.iter().all(u8::is_ascii_hexdigit));
+        if is_invalid_percent || (c != '%' && !is_url_code_point(c)) {
+            violations.push(Violation::new(
+                ViolationKind::InvalidUrlUnit,
+                i..i + c.len_utf8(),
+            ));
+        }
+    }
+    Ok(Host::Opaque(utf8_percent_encode(input, &C0_CONTROL)))
+}
+
+
+// https://url.spec.whatwg.org/#ends-in-a-number-checker
+fn ends_in_a_number(input: &str) -> bool {
+    let mut parts: Vec<&str> = input.split('.').collect();
+    if parts.last() == Some(&"") {
+        if parts.len() == 1 {
+            return false;
+        }
+        parts.pop();
+    }
+    let last: &str = parts.last().unwrap();
+    if !last.is_empty() && last.bytes().all(|b: u8| b.is_ascii_digit()) {
+        return true;
+    }
+    parse_ipv4_number(last).is_some()
+}
+
+
+// https://url.spec.whatwg.org/#ipv4-number-parser
+// Returns the number and whether the input was not in decimal notation.
+fn parse_ipv4_number(mut input: &str) -> Option<(u64, bool)> {
+    if input.is_empty() {
+        return None;
+    }
+    let mut radix: u32 = 10;
+    if input.starts_with("0x") || input.starts_with("0X") {
+        input = &input[2..];
+        radix = 16;
+    } else if input.len() >= 2 && input.starts_with('0') {
+        input = &input[1..];
+        radix = 8;
+    }
+    if input.is_empty() {
+        return Some((0, true));
+    }
+    if !input.chars().all(|c: char| c.is_digit(radix)) {
+        return None;
+    }
+    // Anything that does not fit is out of range anyway.
+    let number: u64 = u64::from_str_radix(input, radix).unwrap_or(u64::MAX);
+    Some((number, radix != 10))
+}
+
+
+// https://url.spec.whatwg.org/#concept-ipv4-parser
+// `span` is reported for every error, as `input` is the already mapped domain.
+fn parse_ipv4(input: &str, span: Range<usize>, violations: &mut Vec<Violation>) -> Result<Ipv4Addr, ParseError> {
+    let mut parts: Vec<&str> = input.split('.').collect();
+    if parts.last() == Some(&"") {
+        violations.push(Violation::new(ViolationKind::Ipv4EmptyPart, span.clone()));
+        if parts.len() > 1 {
+            parts.pop();
+        }
+    }
+    if parts.len() > 4 {
+        return Err(ParseError::Ipv4TooManyParts(span));
+    }
+    let mut numbers: Vec<u64> = Vec::with_capacity(parts.len());
+    for part in parts {
+        match parse_ipv4_number(part) {
+            Some((number, is_non_decimal)) => {
+                if is_non_decimal {
+                    violations.push(Violation::new(
+                        ViolationKind::Ipv4NonDecimalPart,
+                        span.clone(),
+                    ));
+                }
+                numbers.push(number);
+            }
+            None => return Err(ParseError::Ipv4NonNumericPart(span)),
+        }
+    }
+    if numbers.iter().any(|&n: &u64| n > 255) {
+        violations.push(Violation::new(
+            ViolationKind::Ipv4OutOfRangePart,
+            span.clone(),
+        ));
+    }
+    let last: u64 = numbers.pop().unwrap();
+    if numbers.iter().any(|&n: &u64| n > 255) || last >= 256u64.pow(4 - numbers.len() as u32) {
+        return Err(ParseError::Ipv4OutOfRangePart(span));
+    }
+    let mut ipv4: u64 = last;
+    for (i, n) in numbers.iter().enumerate() {
+        ipv4 += n * 256u64.pow(3 - i as u32);
+    }
+    Ok(Ipv4Addr::from(ipv4 as u32))
+}
+
+
+// https://url.spec.whatwg.org/#concept-ipv6-parser
+fn parse_ipv6(address: &str) -> Result<Ipv6Addr, ParseError> {
+    let input: Vec<(usize, char)> = address.char_indices().collect();
+    let c = |pointer: usize| -> Option<char> { input.get(pointer).map(|&(_, c): &(usize, char)| c) };
+    // The byte range of the code point at `pointer`, or an empty range at the end.
+    let at = |pointer: usize| -> Range<usize> {
+        match input.get(pointer) {
+            Some(&(i, c)) => i..i + c.len_utf8(),
+            None => address.len()..address.len(),
+        }
+    };
+    let mut address: [u16; 8] = [0; 8];
+    let mut piece_index: usize = 0;
+    let mut compress: Option<usize> = None;
+    let mut pointer: usize = 0;
+    if c(pointer) == Some(':') {
+        if c(pointer + 1) != Some(':') {
+            return Err(ParseError::Ipv6InvalidCompression(at(pointer)));
+        }
+        pointer += 2;
+        piece_index += 1;
+        compress = Some(piece_index);
+    }
+    while c(pointer).is_some() {
+        if piece_index == 8 {
+            return Err(ParseError::Ipv6TooManyPieces(at(pointer)));
+        }
+        if c(pointer) == Some(':') {
+            if compress.is_some() {
+                return Err(ParseError::Ipv6MultipleCompression(at(pointer)));
+            }
+            pointer += 1;
+            piece_index += 1;
+            compress = Some(piece_index);
+            continue;
+        }
+        let mut value: u16 = 0;
+        let mut length: usize = 0;
+        while length < 4 && c(pointer).is_some_and(|c: char| c.is_ascii_hexdigit()) {
+            value = value * 0x10 + c(pointer).unwrap().to_digit(16).unwrap() as u16;
+            pointer += 1;
+            length += 1;
+        }
+        if c(pointer) == Some('.') {
+            if length == 0 {
+                return Err(ParseError::Ipv4InIpv6InvalidCodePoint(at(pointer)));
+            }
+            pointer -= length;
+            if piece_index > 6 {
+                return Err(ParseError::Ipv4InIpv6TooManyPieces(at(pointer)));
+            }
+            let mut numbers_seen: usize = 0;
+            while c(pointer).is_some() {
+                let mut ipv4_piece: Option<u16> = None;
+                if numbers_seen > 0 {
+                    if c(pointer) == Some('.') && numbers_seen < 4 {
+                        pointer += 1;
+                    } else {
+                        return Err(ParseError::Ipv4InIpv6InvalidCodePoint(at(pointer)));
+                    }
+                }
+                if !c(pointer).is_some_and(|c: char| c.is_ascii_digit()) {
+                    return Err(ParseError::Ipv4InIpv6InvalidCodePoint(at(pointer)));
+                }
+                while let Some(number) = c(pointer).and_then(|c: char| c.to_digit(10)) {
+                    ipv4_piece = match ipv4_piece {
+                        None => Some(number as u16),
+                        Some(0) => return Err(ParseError::Ipv4InIpv6InvalidCodePoint(at(pointer))),
+                        Some(piece) => Some(piece * 10 + number as u16),
+                    };
+                    if ipv4_piece.unwrap() > 255 {
+                        return Err(ParseError::Ipv4InIpv6OutOfRangePart(at(pointer)));
+                    }
+                    pointer += 1;
+                }
+                address[piece_index] = address[piece_index] * 0x100 + ipv4_piece.unwrap();
+                numbers_seen += 1;
+                if numbers_seen == 2 || numbers_seen == 4 {
+                    piece_index += 1;
+                }
+            }
+            if numbers_seen != 4 {
+                return Err(ParseError::Ipv4InIpv6TooFewParts(at(pointer)));
+            }
+            break;
+        } else if c(pointer) == Some(':') {
+            pointer += 1;
+            if c(pointer).is_none() {
+                return Err(ParseError::Ipv6InvalidCodePoint(at(pointer)));
+            }
+        } else if c(pointer).is_some() {
+            return Err(ParseError::Ipv6InvalidCodePoint(at(pointer)));
+        }
+        address[piece_index] = value;
+        piece_index += 1;
+    }
+    if let Some(compress) = compress {
+        let mut swaps: usize = piece_index - compress;
+        piece_index = 7;
+        while piece_index != 0 && swaps > 0 {
+            address.swap(piece_index, compress + swaps - 1);
+            piece_index -= 1;
+            swaps -= 1;
+        }
+    } else if piece_index != 8 {
+        return Err(ParseError::Ipv6TooFewPieces(0..address.len()));
+    }
+    Ok(Ipv6Addr::from(address))
+}
+
+
+// https://url.spec.whatwg.org/#concept-ipv6-serializer
+fn serialize_ipv6(address: &Ipv6Addr) -> String {
+    let pieces: [u16; 8] = address.segments();
+    let mut compress: Option<(usize, usize)> = None;
+    let mut i: usize = 0;
+    while i < 8 {
+        if pieces[i] == 0 {
+            let start: usize = i;
+            while i < 8 && pieces[i] == 0 {
+                i += 1;
+            }
+            let length: usize = i - start;
+            if length > 1 && compress.map_or(true, |(_, longest): (usize, usize)| length > longest) {
+                compress = Some((start, length));
+            }
+        } else {
+            i += 1;
+        }
+    }
+    let mut output: String = String::new();
+    let mut ignore0: bool = false;
+    for (piece_index, piece) in pieces.iter().enumerate() {
+        if ignore0 && *piece == 0 {
+            continue;
+        }
+        ignore0 = false;
+        if let Some((start, _)) = compress {
+            if start == piece_index {
+                output += if piece_index == 0 { "::" } else { ":" };
+                ignore0 = true;
+                continue;
+            }
+        }
+        output += &format!("{:x}", piece);
+        if piece_index != 7 {
+            output.push(':');
+        }
+    }
+    output
+}
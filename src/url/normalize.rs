@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Rewrites a URL into a canonical form so that equivalent URLs compare equal, e.g. for
+// deduplication. Parsing already does much of this for special schemes; the steps still apply to
+// other schemes and make the guarantee explicit. Only the steps marked safe preserve the meaning of
+// a URL in every scheme.
+
+use super::{default_port, Host, Path, URL};
+use crate::uri::{normalize_percent_encoding, remove_dot_segments};
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    // Safe. Lowercases the scheme and the host.
+    pub lowercase: bool,
+    // Safe. Drops a port equal to the scheme's default port.
+    pub remove_default_port: bool,
+    // Safe. Decodes percent-encoded unreserved characters and uppercases the remaining percent-encodings.
+    // https://www.rfc-editor.org/rfc/rfc3986#section-6.2.2.2
+    pub normalize_percent_encoding: bool,
+    // Safe. https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
+    pub remove_dot_segments: bool,
+    // Orders the query pairs by key, keeping the order of pairs with the same key. Drops empty pairs.
+    pub sort_query: bool,
+    // Drops a `?` with nothing after it.
+    pub remove_empty_query: bool,
+    // Drops a `#` with nothing after it.
+    pub remove_empty_fragment: bool,
+    // Drops a leading `www.` label from a domain, unless it is part of the registrable domain as in `www.com`.
+    pub strip_www: bool,
+    // Drops an empty last path segment, e.g. `/a/` becomes `/a`. The path `/` is kept.
+    pub remove_trailing_slash: bool,
+}
+
+impl NormalizeOptions {
+    // Everything, for crawlers that would rather merge a few distinct pages than fetch duplicates.
+    pub const AGGRESSIVE: Self = Self {
+        sort_query: true,
+        strip_www: true,
+        remove_trailing_slash: true,
+        ..Self::HTTP_EQUIVALENT
+    };
+    // Also treats an empty query or fragment as absent, which HTTP servers do.
+    pub const HTTP_EQUIVALENT: Self = Self {
+        remove_empty_query: true,
+        remove_empty_fragment: true,
+        ..Self::RFC3986_SAFE
+    };
+    // https://www.rfc-editor.org/rfc/rfc3986#section-6.2.2
+    pub const RFC3986_SAFE: Self = Self {
+        lowercase: true,
+        remove_default_port: true,
+        normalize_percent_encoding: true,
+        remove_dot_segments: true,
+        sort_query: false,
+        remove_empty_query: false,
+        remove_empty_fragment: false,
+        strip_www: false,
+        remove_trailing_slash: false,
+    };
+}
+
+
+impl URL {
+    pub fn normalize(&self, options: &NormalizeOptions) -> Self {
+        let mut url: Self = self.clone();
+        if options.lowercase {
+            url.scheme.make_ascii_lowercase();
+            if let Some(Host::Opaque(host)) = &mut url.host {
+                host.make_ascii_lowercase();
+            }
+        }
+        if options.strip_www {
+            let stripped: Option<String> = match &url.host {
+                Some(host @ Host::Domain(domain)) if host.registrable_domain() != Some(domain.as_str()) => {
+                    domain.strip_prefix("www.").map(str::to_string)
+                }
+                _ => None,
+            };
+            if let Some(domain) = stripped {
+                url.host = Some(Host::Domain(domain));
+            }
+        }
+        if options.remove_default_port && url.port.is_some() && url.port == default_port(&url.scheme) {
+            url.port = None;
+        }
+        if options.normalize_percent_encoding {
+            url.username = normalize_percent_encoding(&url.username, false);
+            url.password = normalize_percent_encoding(&url.password, false);
+            if let Some(Host::Opaque(host)) = &mut url.host {
+                *host = normalize_percent_encoding(host, false);
+            }
+            match &mut url.path {
+                Path::Opaque(path) => *path = normalize_percent_encoding(path, false),
+                Path::Segments(segments) => {
+                    for segment in segments.iter_mut() {
+                        *segment = normalize_percent_encoding(segment, false);
+                    }
+                }
+            }
+            url.query = url
+                .query
+                .as_deref()
+                .map(|query: &str| normalize_percent_encoding(query, false));
+            url.fragment = url
+                .fragment
+                .as_deref()
+                .map(|fragment: &str| normalize_percent_encoding(fragment, false));
+        }
+        if options.remove_dot_segments {
+            if let Path::Segments(segments) = &mut url.path {
+                if !segments.is_empty() {
+                    let path: String = remove_dot_segments(&format!("/{}", segments.join("/")));
+                    *segments = path
+                        .strip_prefix('/')
+                        .unwrap_or(&path)
+                        .split('/')
+                        .map(str::to_string)
+                        .collect();
+                }
+            }
+        }
+        if options.remove_trailing_slash {
+            if let Path::Segments(segments) = &mut url.path {
+                if segments.len() > 1 && segments.last().is_some_and(String::is_empty) {
+                    segments.pop();
+                }
+            }
+        }
+        if options.sort_query {
+            if let Some(query) = &mut url.query {
+                let mut pairs: Vec<&str> = query
+                    .split('&')
+                    .filter(|pair: &&str| !pair.is_empty())
+                    .collect();
+                pairs.sort_by_key(|pair: &&str| pair.split('=').next().unwrap());
+                *query = pairs.join("&");
+            }
+        }
+        if options.remove_empty_query && url.query.as_deref() == Some("") {
+            url.query = None;
+        }
+        if options.remove_empty_fragment && url.fragment.as_deref() == Some("") {
+            url.fragment = None;
+        }
+        url.update_serialization();
+        url
+    }
+}
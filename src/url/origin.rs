@@ -0,0 +1,115 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://html.spec.whatwg.org/multipage/browsers.html#origin
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{Host, URL};
+use crate::idna;
+
+
+static NEXT_OPAQUE_ORIGIN: AtomicUsize = AtomicUsize::new(0);
+
+
+// An opaque origin is only the same origin as itself and its copies.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OpaqueOrigin(usize);
+
+impl OpaqueOrigin {
+    fn new() -> Self {
+        Self(NEXT_OPAQUE_ORIGIN.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Origin {
+    // The port is `None` when the URL uses the scheme's default port.
+    Tuple(String, Host, Option<u16>),
+    Opaque(OpaqueOrigin),
+}
+
+impl Origin {
+    pub fn new_opaque() -> Self {
+        Self::Opaque(OpaqueOrigin::new())
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        matches!(self, Self::Tuple(..))
+    }
+
+    // https://html.spec.whatwg.org/multipage/browsers.html#ascii-serialisation-of-an-origin
+    pub fn ascii_serialization(&self) -> String {
+        match self {
+            Self::Tuple(scheme, host, port) => serialize_tuple(scheme, &host.to_string(), *port),
+            Self::Opaque(_) => "null".to_string(),
+        }
+    }
+
+    // Like `ascii_serialization`, but domains are converted to Unicode.
+    pub fn unicode_serialization(&self) -> String {
+        match self {
+            Self::Tuple(scheme, Host::Domain(domain), port) => {
+                serialize_tuple(scheme, &idna::domain_to_unicode(domain), *port)
+            }
+            _ => self.ascii_serialization(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/browsers.html#same-origin
+    pub fn is_same_origin(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    // https://html.spec.whatwg.org/multipage/browsers.html#same-site
+    // Schemeful: `http://example.com` and `https://example.com` are not the same site.
+    pub fn is_same_site(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Tuple(scheme, host, _), Self::Tuple(other_scheme, other_host, _)) => {
+                scheme == other_scheme && is_schemelessly_same_site(host, other_host)
+            }
+            _ => self.is_same_origin(other),
+        }
+    }
+}
+
+
+fn serialize_tuple(scheme: &str, host: &str, port: Option<u16>) -> String {
+    match port {
+        Some(port) => format!("{}://{}:{}", scheme, host, port),
+        None => format!("{}://{}", scheme, host),
+    }
+}
+
+
+// https://html.spec.whatwg.org/multipage/browsers.html#schemelessly-same-site
+fn is_schemelessly_same_site(host: &Host, other: &Host) -> bool {
+    match host.registrable_domain() {
+        Some(domain) => other.registrable_domain() == Some(domain),
+        None => host == other,
+    }
+}
+
+
+impl URL {
+    // https://url.spec.whatwg.org/#concept-url-origin
+    // `file:` URLs get a new opaque origin each time.
+    pub fn origin(&self) -> Origin {
+        match self.scheme.as_str() {
+            "blob" => {
+                match URL::parse(&self.path()) {
+                    Ok(url) if matches!(url.scheme(), "http" | "https") => url.origin(),
+                    _ => Origin::new_opaque(),
+                }
+            }
+            "ftp" | "http" | "https" | "ws" | "wss" => {
+                match &self.host {
+                    Some(host) => Origin::Tuple(self.scheme.clone(), host.clone(), self.port),
+                    None => Origin::new_opaque(),
+                }
+            }
+            _ => Origin::new_opaque(),
+        }
+    }
+}
@@ -0,0 +1,784 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// https://url.spec.whatwg.org/#concept-basic-url-parser
+
+use std::ops::Range;
+
+use super::{default_port, is_special_scheme, Host, ParseError, Path, Violation, ViolationKind, URL};
+use crate::percent_encoding::{utf8_percent_encode, C0_CONTROL, FRAGMENT, PATH, QUERY, SPECIAL_QUERY, USERINFO};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum State {
+    SchemeStart,
+    Scheme,
+    NoScheme,
+    SpecialRelativeOrAuthority,
+    PathOrAuthority,
+    Relative,
+    RelativeSlash,
+    SpecialAuthoritySlashes,
+    SpecialAuthorityIgnoreSlashes,
+    Authority,
+    Host,
+    Hostname,
+    Port,
+    File,
+    FileSlash,
+    FileHost,
+    PathStart,
+    Path,
+    OpaquePath,
+    Query,
+    Fragment,
+}
+
+
+pub(crate) fn parse(input: &str, base: Option<&URL>) -> Result<URL, ParseError> {
+    parse_with_violations(input, base, &mut Vec::new())
+}
+
+
+pub(crate) fn parse_with_violations(
+    input: &str,
+    base: Option<&URL>,
+    violations: &mut Vec<Violation>,
+) -> Result<URL, ParseError> {
+    let url: URL = URL {
+        serialization: String::new(),
+        scheme: String::new(),
+        username: String::new(),
+        password: String::new(),
+        host: None,
+        port: None,
+        path: Path::Segments(Vec::new()),
+        query: None,
+        fragment: None,
+    };
+    let mut parser: Parser = Parser::new(input, base, url, None, violations);
+    parser.run()?;
+    parser.url.update_serialization();
+    Ok(parser.url)
+}
+
+
+// Used by the URL setters, which run the parser on an existing URL.
+// As in the standard, changes made before a failure are kept.
+pub(crate) fn parse_with_override(input: &str, url: &mut URL, state_override: State) -> Result<(), ParseError> {
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut parser: Parser = Parser::new(
+        input,
+        None,
+        url.clone(),
+        Some(state_override),
+        &mut violations,
+    );
+    let result: Result<(), ParseError> = parser.run();
+    parser.url.update_serialization();
+    *url = parser.url;
+    result
+}
+
+
+// https://url.spec.whatwg.org/#url-code-points
+pub(crate) fn is_url_code_point(c: char) -> bool {
+    let is_noncharacter: bool = matches!(c, '\u{fdd0}'..='\u{fdef}') || c as u32 & 0xfffe == 0xfffe;
+    c.is_ascii_alphanumeric()
+        || matches!(
+            c,
+            '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | '-' | '.' | '/' | ':' | ';' | '=' | '?' | '@' | '_' | '~'
+        )
+        || (c >= '\u{a0}' && !is_noncharacter)
+}
+
+
+fn is_windows_drive_letter(input: &str) -> bool {
+    let chars: Vec<char> = input.chars().collect();
+    chars.len() == 2 && chars[0].is_ascii_alphabetic() && (chars[1] == ':' || chars[1] == '|')
+}
+
+
+fn is_normalized_windows_drive_letter(input: &str) -> bool {
+    is_windows_drive_letter(input) && input.ends_with(':')
+}
+
+
+fn starts_with_windows_drive_letter(input: &[char]) -> bool {
+    input.len() >= 2
+        && input[0].is_ascii_alphabetic()
+        && (input[1] == ':' || input[1] == '|')
+        && (input.len() == 2 || matches!(input[2], '/' | '\\' | '?' | '#'))
+}
+
+
+fn is_single_dot_segment(input: &str) -> bool {
+    input == "." || input.eq_ignore_ascii_case("%2e")
+}
+
+
+fn is_double_dot_segment(input: &str) -> bool {
+    matches!(
+        input.to_ascii_lowercase().as_str(),
+        ".." | ".%2e" | "%2e." | "%2e%2e"
+    )
+}
+
+
+struct Parser<'a> {
+    input: Vec<char>,
+    // The byte offset in the original input of each code point in `input`.
+    offsets: Vec<usize>,
+    // The byte offset just past the last code point in `input`.
+    end: usize,
+    pointer: usize,
+    base: Option<&'a URL>,
+    state_override: Option<State>,
+    url: URL,
+    buffer: String,
+    at_sign_seen: bool,
+    inside_brackets: bool,
+    password_token_seen: bool,
+    violations: &'a mut Vec<Violation>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(
+        input: &str,
+        base: Option<&'a URL>,
+        url: URL,
+        state_override: Option<State>,
+        violations: &'a mut Vec<Violation>,
+    ) -> Self {
+        let mut start: usize = 0;
+        let mut end: usize = input.len();
+        if state_override.is_none() {
+            let trimmed: &str = input.trim_start_matches(|c: char| c <= ' ');
+            start = input.len() - trimmed.len();
+            end = trimmed.trim_end_matches(|c: char| c <= ' ').len() + start;
+            if start > 0 {
+                violations.push(Violation::new(ViolationKind::InvalidUrlUnit, 0..start));
+            }
+            if end < input.len() {
+                violations.push(Violation::new(
+                    ViolationKind::InvalidUrlUnit,
+                    end..input.len(),
+                ));
+            }
+        }
+        let mut chars: Vec<char> = Vec::with_capacity(end - start);
+        let mut offsets: Vec<usize> = Vec::with_capacity(end - start);
+        for (i, c) in input[start..end].char_indices() {
+            if matches!(c, '\t' | '\n' | '\r') {
+                violations.push(Violation::new(
+                    ViolationKind::InvalidUrlUnit,
+                    start + i..start + i + 1,
+                ));
+            } else {
+                chars.push(c);
+                offsets.push(start + i);
+            }
+        }
+        Self {
+            input: chars,
+            offsets,
+            end,
+            pointer: 0,
+            base,
+            state_override,
+            url,
+            buffer: String::new(),
+            at_sign_seen: false,
+            inside_brackets: false,
+            password_token_seen: false,
+            violations,
+        }
+    }
+
+    fn offset_of(&self, pointer: usize) -> usize {
+        self.offsets.get(pointer).copied().unwrap_or(self.end)
+    }
+
+    // The byte range in the original input covered by the code points from `start` to `end`.
+    fn span(&self, start: usize, end: usize) -> Range<usize> {
+        match end
+            .checked_sub(1)
+            .filter(|&last: &usize| last >= start && last < self.input.len())
+        {
+            Some(last) => self.offset_of(start)..self.offset_of(last) + self.input[last].len_utf8(),
+            None => self.offset_of(start)..self.offset_of(start),
+        }
+    }
+
+    fn span_at(&self, pointer: usize) -> Range<usize> {
+        self.span(pointer, pointer + 1)
+    }
+
+    // The span of the code points collected into the buffer, which end right before the pointer.
+    fn buffer_span(&self) -> Range<usize> {
+        self.span(self.pointer - self.buffer.chars().count(), self.pointer)
+    }
+
+    fn violation(&mut self, kind: ViolationKind, span: Range<usize>) {
+        self.violations.push(Violation::new(kind, span));
+    }
+
+    fn check_url_unit(&mut self, c: char) {
+        let is_valid: bool = if c == '%' {
+            self.input
+                .get(self.pointer + 1..self.pointer + 3)
+                .is_some_and(|hex: &[char]| hex.iter().all(char::is_ascii_hexdigit))
+        } else {
+            is_url_code_point(c)
+        };
+        if !is_valid {
+            self.violation(ViolationKind::InvalidUrlUnit, self.span_at(self.pointer));
+        }
+    }
+
+    fn parse_host(&mut self, is_opaque: bool) -> Result<Host, ParseError> {
+        let start: usize = self.buffer_span().start;
+        let mut violations: Vec<Violation> = Vec::new();
+        let host: Host =
+            Host::parse_with(&self.buffer, is_opaque, &mut violations).map_err(|err: ParseError| err.offset(start))?;
+        for violation in violations {
+            self.violation(
+                violation.kind,
+                violation.span.start + start..violation.span.end + start,
+            );
+        }
+        Ok(host)
+    }
+
+    fn remaining_starts_with(&self, prefix: &str) -> bool {
+        let remaining: &[char] = self.input.get(self.pointer + 1..).unwrap_or(&[]);
+        remaining.len() >= prefix.chars().count() && prefix.chars().zip(remaining).all(|(a, &b)| a == b)
+    }
+
+    fn segments(&mut self) -> &mut Vec<String> {
+        if let Path::Opaque(_) = self.url.path {
+            self.url.path = Path::Segments(Vec::new());
+        }
+        match &mut self.url.path {
+            Path::Segments(segments) => segments,
+            Path::Opaque(_) => unreachable!(),
+        }
+    }
+
+    // https://url.spec.whatwg.org/#shorten-a-urls-path
+    fn shorten_path(&mut self) {
+        let is_file: bool = self.url.scheme == "file";
+        let segments: &mut Vec<String> = self.segments();
+        if is_file && segments.len() == 1 && is_normalized_windows_drive_letter(&segments[0]) {
+            return;
+        }
+        segments.pop();
+    }
+
+    fn run(&mut self) -> Result<(), ParseError> {
+        let mut state: State = self.state_override.unwrap_or(State::SchemeStart);
+        loop {
+            let c: Option<char> = self.input.get(self.pointer).copied();
+            let is_special: bool = self.url.is_special();
+            match state {
+                State::SchemeStart => {
+                    if let Some(c) = c.filter(|c: &char| c.is_ascii_alphabetic()) {
+                        self.buffer.push(c.to_ascii_lowercase());
+                        state = State::Scheme;
+                    } else if self.state_override.is_none() {
+                        state = State::NoScheme;
+                        continue;
+                    } else {
+                        return Err(ParseError::InvalidScheme(self.span_at(self.pointer)));
+                    }
+                }
+                State::Scheme => {
+                    if let Some(c) = c.filter(|c: &char| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+                        self.buffer.push(c.to_ascii_lowercase());
+                    } else if c == Some(':') {
+                        if self.state_override.is_some()
+                            && (is_special != is_special_scheme(&self.buffer)
+                                || ((self.url.includes_credentials() || self.url.port.is_some()) && self.buffer == "file")
+                                || (self.url.scheme == "file" && self.url.host == Some(Host::Empty)))
+                        {
+                            return Ok(());
+                        }
+                        self.url.scheme = std::mem::take(&mut self.buffer);
+                        if self.state_override.is_some() {
+                            if self.url.port.is_some() && self.url.port == default_port(&self.url.scheme) {
+                                self.url.port = None;
+                            }
+                            return Ok(());
+                        }
+                        if self.url.scheme == "file" {
+                            if !self.remaining_starts_with("//") {
+                                self.violation(
+                                    ViolationKind::SpecialSchemeMissingFollowingSolidus,
+                                    self.span_at(self.pointer + 1),
+                                );
+                            }
+                            state = State::File;
+                        } else if self.url.is_special()
+                            && self
+                                .base
+                                .is_some_and(|base: &URL| base.scheme == self.url.scheme)
+                        {
+                            state = State::SpecialRelativeOrAuthority;
+                        } else if self.url.is_special() {
+                            state = State::SpecialAuthoritySlashes;
+                        } else if self.remaining_starts_with("/") {
+                            state = State::PathOrAuthority;
+                            self.pointer += 1;
+                        } else {
+                            self.url.path = Path::Opaque(String::new());
+                            state = State::OpaquePath;
+                        }
+                    } else if self.state_override.is_none() {
+                        self.buffer.clear();
+                        state = State::NoScheme;
+                        self.pointer = 0;
+                        continue;
+                    } else {
+                        return Err(ParseError::InvalidScheme(self.span_at(self.pointer)));
+                    }
+                }
+                State::NoScheme => {
+                    let base: &URL = match self.base {
+                        Some(base) if !matches!(base.path, Path::Opaque(_)) || c == Some('#') => base,
+                        _ => {
+                            return Err(ParseError::MissingSchemeNonRelativeUrl(
+                                self.span(0, self.input.len()),
+                            ))
+                        }
+                    };
+                    if let Path::Opaque(_) = base.path {
+                        self.url.scheme = base.scheme.clone();
+                        self.url.path = base.path.clone();
+                        self.url.query = base.query.clone();
+                        self.url.fragment = Some(String::new());
+                        state = State::Fragment;
+                    } else if base.scheme != "file" {
+                        state = State::Relative;
+                        continue;
+                    } else {
+                        state = State::File;
+                        continue;
+                    }
+                }
+                State::SpecialRelativeOrAuthority => {
+                    if c == Some('/') && self.remaining_starts_with("/") {
+                        state = State::SpecialAuthorityIgnoreSlashes;
+                        self.pointer += 1;
+                    } else {
+                        self.violation(
+                            ViolationKind::SpecialSchemeMissingFollowingSolidus,
+                            self.span_at(self.pointer),
+                        );
+                        state = State::Relative;
+                        continue;
+                    }
+                }
+                State::PathOrAuthority => {
+                    if c == Some('/') {
+                        state = State::Authority;
+                    } else {
+                        state = State::Path;
+                        continue;
+                    }
+                }
+                State::Relative => {
+                    let base: &URL = self.base.unwrap();
+                    self.url.scheme = base.scheme.clone();
+                    if c == Some('/') || (self.url.is_special() && c == Some('\\')) {
+                        if c == Some('\\') {
+                            self.violation(
+                                ViolationKind::InvalidReverseSolidus,
+                                self.span_at(self.pointer),
+                            );
+                        }
+                        state = State::RelativeSlash;
+                    } else {
+                        self.url.username = base.username.clone();
+                        self.url.password = base.password.clone();
+                        self.url.host = base.host.clone();
+                        self.url.port = base.port;
+                        self.url.path = base.path.clone();
+                        self.url.query = base.query.clone();
+                        if c == Some('?') {
+                            self.url.query = Some(String::new());
+                            state = State::Query;
+                        } else if c == Some('#') {
+                            self.url.fragment = Some(String::new());
+                            state = State::Fragment;
+                        } else if c.is_some() {
+                            self.url.query = None;
+                            self.shorten_path();
+                            state = State::Path;
+                            continue;
+                        }
+                    }
+                }
+                State::RelativeSlash => {
+                    if is_special && matches!(c, Some('/' | '\\')) {
+                        if c == Some('\\') {
+                            self.violation(
+                                ViolationKind::InvalidReverseSolidus,
+                                self.span_at(self.pointer),
+                            );
+                        }
+                        state = State::SpecialAuthorityIgnoreSlashes;
+                    } else if c == Some('/') {
+                        state = State::Authority;
+                    } else {
+                        let base: &URL = self.base.unwrap();
+                        self.url.username = base.username.clone();
+                        self.url.password = base.password.clone();
+                        self.url.host = base.host.clone();
+                        self.url.port = base.port;
+                        state = State::Path;
+                        continue;
+                    }
+                }
+                State::SpecialAuthoritySlashes => {
+                    if c == Some('/') && self.remaining_starts_with("/") {
+                        state = State::SpecialAuthorityIgnoreSlashes;
+                        self.pointer += 1;
+                    } else {
+                        self.violation(
+                            ViolationKind::SpecialSchemeMissingFollowingSolidus,
+                            self.span_at(self.pointer),
+                        );
+                        state = State::SpecialAuthorityIgnoreSlashes;
+                        continue;
+                    }
+                }
+                State::SpecialAuthorityIgnoreSlashes => {
+                    if !matches!(c, Some('/' | '\\')) {
+                        state = State::Authority;
+                        continue;
+                    }
+                    self.violation(
+                        ViolationKind::SpecialSchemeMissingFollowingSolidus,
+                        self.span_at(self.pointer),
+                    );
+                }
+                State::Authority => {
+                    if c == Some('@') {
+                        let span: Range<usize> = self.span(self.pointer - self.buffer.chars().count(), self.pointer + 1);
+                        self.violation(ViolationKind::InvalidCredentials, span);
+                        if self.at_sign_seen {
+                            self.buffer.insert_str(0, "%40");
+                        }
+                        self.at_sign_seen = true;
+                        for code_point in std::mem::take(&mut self.buffer).chars() {
+                            if code_point == ':' && !self.password_token_seen {
+                                self.password_token_seen = true;
+                                continue;
+                            }
+                            let encoded: String = utf8_percent_encode(code_point.encode_utf8(&mut [0; 4]), &USERINFO);
+                            if self.password_token_seen {
+                                self.url.password += &encoded;
+                            } else {
+                                self.url.username += &encoded;
+                            }
+                        }
+                    } else if matches!(c, None | Some('/' | '?' | '#')) || (is_special && c == Some('\\')) {
+                        if self.at_sign_seen && self.buffer.is_empty() {
+                            return Err(ParseError::HostMissing(self.span_at(self.pointer)));
+                        }
+                        self.pointer -= self.buffer.chars().count();
+                        self.buffer.clear();
+                        state = State::Host;
+                        continue;
+                    } else {
+                        self.buffer.push(c.unwrap());
+                    }
+                }
+                State::Host | State::Hostname => {
+                    if self.state_override.is_some() && self.url.scheme == "file" {
+                        state = State::FileHost;
+                        continue;
+                    } else if c == Some(':') && !self.inside_brackets {
+                        if self.buffer.is_empty() {
+                            return Err(ParseError::HostMissing(self.span_at(self.pointer)));
+                        }
+                        if self.state_override == Some(State::Hostname) {
+                            return Ok(());
+                        }
+                        self.url.host = Some(self.parse_host(!is_special)?);
+                        self.buffer.clear();
+                        state = State::Port;
+                    } else if matches!(c, None | Some('/' | '?' | '#')) || (is_special && c == Some('\\')) {
+                        if is_special && self.buffer.is_empty() {
+                            return Err(ParseError::HostMissing(self.span_at(self.pointer)));
+                        } else if self.state_override.is_some()
+                            && self.buffer.is_empty()
+                            && (self.url.includes_credentials() || self.url.port.is_some())
+                        {
+                            return Ok(());
+                        }
+                        self.url.host = Some(self.parse_host(!is_special)?);
+                        self.buffer.clear();
+                        state = State::PathStart;
+                        if self.state_override.is_some() {
+                            return Ok(());
+                        }
+                        continue;
+                    } else {
+                        let c: char = c.unwrap();
+                        if c == '[' {
+                            self.inside_brackets = true;
+                        } else if c == ']' {
+                            self.inside_brackets = false;
+                        }
+                        self.buffer.push(c);
+                    }
+                }
+                State::Port => {
+                    if let Some(c) = c.filter(|c: &char| c.is_ascii_digit()) {
+                        self.buffer.push(c);
+                    } else if matches!(c, None | Some('/' | '?' | '#'))
+                        || (is_special && c == Some('\\'))
+                        || self.state_override.is_some()
+                    {
+                        if !self.buffer.is_empty() {
+                            let port: u16 = match self.buffer.trim_start_matches('0') {
+                                "" => 0,
+                                digits => {
+                                    match digits.parse() {
+                                        Ok(port) => port,
+                                        Err(_) => return Err(ParseError::PortOutOfRange(self.buffer_span())),
+                                    }
+                                }
+                            };
+                            self.url.port = if default_port(&self.url.scheme) == Some(port) {
+                                None
+                            } else {
+                                Some(port)
+                            };
+                            self.buffer.clear();
+                            if self.state_override.is_some() {
+                                return Ok(());
+                            }
+                        }
+                        if self.state_override.is_some() {
+                            return Err(ParseError::PortInvalid(self.span_at(self.pointer)));
+                        }
+                        state = State::PathStart;
+                        continue;
+                    } else {
+                        return Err(ParseError::PortInvalid(self.span_at(self.pointer)));
+                    }
+                }
+                State::File => {
+                    self.url.scheme = "file".to_string();
+                    self.url.host = Some(Host::Empty);
+                    if matches!(c, Some('/' | '\\')) {
+                        if c == Some('\\') {
+                            self.violation(
+                                ViolationKind::InvalidReverseSolidus,
+                                self.span_at(self.pointer),
+                            );
+                        }
+                        state = State::FileSlash;
+                    } else if let Some(base) = self.base.filter(|base: &&URL| base.scheme == "file") {
+                        self.url.host = base.host.clone();
+                        self.url.path = base.path.clone();
+                        self.url.query = base.query.clone();
+                        if c == Some('?') {
+                            self.url.query = Some(String::new());
+                            state = State::Query;
+                        } else if c == Some('#') {
+                            self.url.fragment = Some(String::new());
+                            state = State::Fragment;
+                        } else if c.is_some() {
+                            self.url.query = None;
+                            if starts_with_windows_drive_letter(&self.input[self.pointer..]) {
+                                let span: Range<usize> = self.span(self.pointer, self.pointer + 2);
+                                self.violation(ViolationKind::FileInvalidWindowsDriveLetter, span);
+                                self.url.path = Path::Segments(Vec::new());
+                            } else {
+                                self.shorten_path();
+                            }
+                            state = State::Path;
+                            continue;
+                        }
+                    } else {
+                        state = State::Path;
+                        continue;
+                    }
+                }
+                State::FileSlash => {
+                    if matches!(c, Some('/' | '\\')) {
+                        if c == Some('\\') {
+                            self.violation(
+                                ViolationKind::InvalidReverseSolidus,
+                                self.span_at(self.pointer),
+                            );
+                        }
+                        state = State::FileHost;
+                    } else {
+                        if let Some(base) = self.base.filter(|base: &&URL| base.scheme == "file") {
+                            self.url.host = base.host.clone();
+                            if !starts_with_windows_drive_letter(&self.input[self.pointer..]) {
+                                if let Path::Segments(segments) = &base.path {
+                                    if segments
+                                        .first()
+                                        .is_some_and(|s: &String| is_normalized_windows_drive_letter(s))
+                                    {
+                                        let drive: String = segments[0].clone();
+                                        self.segments().push(drive);
+                                    }
+                                }
+                            }
+                        }
+                        state = State::Path;
+                        continue;
+                    }
+                }
+                State::FileHost => {
+                    if matches!(c, None | Some('/' | '\\' | '?' | '#')) {
+                        if self.state_override.is_none() && is_windows_drive_letter(&self.buffer) {
+                            self.violation(
+                                ViolationKind::FileInvalidWindowsDriveLetterHost,
+                                self.buffer_span(),
+                            );
+                            state = State::Path;
+                        } else if self.buffer.is_empty() {
+                            self.url.host = Some(Host::Empty);
+                            if self.state_override.is_some() {
+                                return Ok(());
+                            }
+                            state = State::PathStart;
+                        } else {
+                            let mut host: Host = self.parse_host(!is_special)?;
+                            if host == Host::Domain("localhost".to_string()) {
+                                host = Host::Empty;
+                            }
+                            self.url.host = Some(host);
+                            if self.state_override.is_some() {
+                                return Ok(());
+                            }
+                            self.buffer.clear();
+                            state = State::PathStart;
+                        }
+                        continue;
+                    } else {
+                        self.buffer.push(c.unwrap());
+                    }
+                }
+                State::PathStart => {
+                    if is_special {
+                        if c == Some('\\') {
+                            self.violation(
+                                ViolationKind::InvalidReverseSolidus,
+                                self.span_at(self.pointer),
+                            );
+                        }
+                        state = State::Path;
+                        if !matches!(c, Some('/' | '\\')) {
+                            continue;
+                        }
+                    } else if self.state_override.is_none() && c == Some('?') {
+                        self.url.query = Some(String::new());
+                        state = State::Query;
+                    } else if self.state_override.is_none() && c == Some('#') {
+                        self.url.fragment = Some(String::new());
+                        state = State::Fragment;
+                    } else if c.is_some() {
+                        state = State::Path;
+                        if c != Some('/') {
+                            continue;
+                        }
+                    } else if self.state_override.is_some() && self.url.host.is_none() {
+                        self.segments().push(String::new());
+                    }
+                }
+                State::Path => {
+                    let is_slash: bool = c == Some('/') || (is_special && c == Some('\\'));
+                    if is_slash && c == Some('\\') {
+                        self.violation(
+                            ViolationKind::InvalidReverseSolidus,
+                            self.span_at(self.pointer),
+                        );
+                    }
+                    if c.is_none() || is_slash || (self.state_override.is_none() && matches!(c, Some('?' | '#'))) {
+                        let buffer: String = std::mem::take(&mut self.buffer);
+                        if is_double_dot_segment(&buffer) {
+                            self.shorten_path();
+                            if !is_slash {
+                                self.segments().push(String::new());
+                            }
+                        } else if is_single_dot_segment(&buffer) && !is_slash {
+                            self.segments().push(String::new());
+                        } else if !is_single_dot_segment(&buffer) {
+                            let is_file: bool = self.url.scheme == "file";
+                            let segments: &mut Vec<String> = self.segments();
+                            if is_file && segments.is_empty() && is_windows_drive_letter(&buffer) {
+                                segments.push(format!("{}:", &buffer[..1]));
+                            } else {
+                                segments.push(buffer);
+                            }
+                        }
+                        if c == Some('?') {
+                            self.url.query = Some(String::new());
+                            state = State::Query;
+                        } else if c == Some('#') {
+                            self.url.fragment = Some(String::new());
+                            state = State::Fragment;
+                        }
+                    } else if let Some(c) = c {
+                        self.check_url_unit(c);
+                        self.buffer += &utf8_percent_encode(c.encode_utf8(&mut [0; 4]), &PATH);
+                    }
+                }
+                State::OpaquePath => {
+                    if c == Some('?') {
+                        self.url.query = Some(String::new());
+                        state = State::Query;
+                    } else if c == Some('#') {
+                        self.url.fragment = Some(String::new());
+                        state = State::Fragment;
+                    } else if let Some(c) = c {
+                        self.check_url_unit(c);
+                        if let Path::Opaque(path) = &mut self.url.path {
+                            *path += &utf8_percent_encode(c.encode_utf8(&mut [0; 4]), &C0_CONTROL);
+                        }
+                    }
+                }
+                State::Query => {
+                    if c.is_none() || (self.state_override.is_none() && c == Some('#')) {
+                        let set = if is_special { &SPECIAL_QUERY } else { &QUERY };
+                        let encoded: String = utf8_percent_encode(&std::mem::take(&mut self.buffer), set);
+                        self.url
+                            .query
+                            .get_or_insert_with(String::new)
+                            .push_str(&encoded);
+                        if c == Some('#') {
+                            self.url.fragment = Some(String::new());
+                            state = State::Fragment;
+                        }
+                    } else if let Some(c) = c {
+                        self.check_url_unit(c);
+                        self.buffer.push(c);
+                    }
+                }
+                State::Fragment => {
+                    if let Some(c) = c {
+                        self.check_url_unit(c);
+                        let encoded: String = utf8_percent_encode(c.encode_utf8(&mut [0; 4]), &FRAGMENT);
+                        self.url
+                            .fragment
+                            .get_or_insert_with(String::new)
+                            .push_str(&encoded);
+                    }
+                }
+            }
+            if self.pointer >= self.input.len() {
+                break;
+            }
+            self.pointer += 1;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Segment-wise access to a URL's path. Segments are stored percent-encoded; `PathSegments` yields
+// them as stored and `decoded` decodes them. `PathSegmentsMut` encodes what it is given, `/` and
+// `%` included, so a segment never splits or changes meaning.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::iter::FusedIterator;
+use std::slice::Iter;
+
+use super::{Path, URL};
+use crate::percent_encoding::{percent_decode_str, utf8_percent_encode, PATH_SEGMENT};
+
+
+// The URL has an opaque path, e.g. `mailto:a@example.com`, which has no segments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpaquePathError;
+
+impl Display for OpaquePathError {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "URL has an opaque path")
+    }
+}
+
+impl Error for OpaquePathError {}
+
+
+// The raw segments of a path, e.g. `a`, `b%20c` and `` for `/a/b%20c/`.
+#[derive(Clone, Debug)]
+pub struct PathSegments<'a> {
+    segments: Iter<'a, String>,
+}
+
+impl<'a> PathSegments<'a> {
+    pub(crate) fn new(segments: &'a [String]) -> Self {
+        Self {
+            segments: segments.iter(),
+        }
+    }
+
+    // The remaining segments, percent-decoded. Invalid UTF-8 is replaced with U+FFFD.
+    pub fn decoded(self) -> impl DoubleEndedIterator<Item = String> + 'a {
+        self.map(percent_decode_str)
+    }
+}
+
+impl<'a> Iterator for PathSegments<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.segments.next().map(String::as_str)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.segments.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for PathSegments<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.segments.next_back().map(String::as_str)
+    }
+}
+
+impl ExactSizeIterator for PathSegments<'_> {}
+
+impl FusedIterator for PathSegments<'_> {}
+
+
+// Edits the path of a URL, keeping its serialization up to date.
+// `.` and `..` are skipped when pushed since the parser would resolve them.
+#[derive(Debug)]
+pub struct PathSegmentsMut<'a> {
+    url: &'a mut URL,
+}
+
+impl<'a> PathSegmentsMut<'a> {
+    pub(crate) fn new(url: &'a mut URL) -> Self {
+        Self { url }
+    }
+
+    pub fn push(&mut self, segment: &str) -> &mut Self {
+        self.extend([segment])
+    }
+
+    // Appends each segment. A path of just `/` is replaced rather than becoming `//segment`.
+    pub fn extend<I: IntoIterator<Item = S>, S: AsRef<str>>(&mut self, segments: I) -> &mut Self {
+        let path: &mut Vec<String> = self.segments();
+        let mut is_root: bool = path.len() == 1 && path[0].is_empty();
+        for segment in segments {
+            let segment: &str = segment.as_ref();
+            if matches!(segment, "." | "..") {
+                continue;
+            }
+            if is_root {
+                path.clear();
+                is_root = false;
+            }
+            path.push(utf8_percent_encode(segment, &PATH_SEGMENT));
+        }
+        self.url.update_serialization();
+        self
+    }
+
+    // Removes the last segment. Removing the only segment leaves the path `/`.
+    pub fn pop(&mut self) -> &mut Self {
+        let path: &mut Vec<String> = self.segments();
+        if path.pop().is_some() && path.is_empty() {
+            path.push(String::new());
+        }
+        self.url.update_serialization();
+        self
+    }
+
+    // Removes a trailing empty segment, e.g. `/a/` becomes `/a`. The path `/` is left as is.
+    pub fn pop_if_empty(&mut self) -> &mut Self {
+        let path: &mut Vec<String> = self.segments();
+        if path.len() > 1 && path.last().is_some_and(String::is_empty) {
+            path.pop();
+        }
+        self.url.update_serialization();
+        self
+    }
+
+    // Removes every segment, leaving the path `/`.
+    pub fn clear(&mut self) -> &mut Self {
+        *self.segments() = vec![String::new()];
+        self.url.update_serialization();
+        self
+    }
+
+    fn segments(&mut self) -> &mut Vec<String> {
+        match &mut self.url.path {
+            Path::Segments(segments) => segments,
+            Path::Opaque(_) => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Display adapters that hide credentials and secret query and fragment values, for logging URLs
+// such as presigned S3 URLs.
+// Hidden values are replaced with `REDACTED`, as in `to_redacted_query_params` of
+// `#[derive(QueryParams)]`.
+
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Display, Formatter};
+
+use super::URL;
+use crate::percent_encoding::percent_decode_str;
+
+
+const REDACTED: &str = "REDACTED";
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedactOptions {
+    // The password is always hidden.
+    pub username: bool,
+    // Keys of query and fragment pairs whose values are hidden, compared case-insensitively after percent-decoding.
+    pub query_keys: Vec<String>,
+}
+
+impl Default for RedactOptions {
+    fn default() -> Self {
+        Self {
+            username: false,
+            query_keys: [
+                "token",
+                "access_token",
+                "refresh_token",
+                "sig",
+                "signature",
+                "api_key",
+                "apikey",
+                "password",
+                "secret",
+                "X-Amz-Credential",
+                "X-Amz-Security-Token",
+                "X-Amz-Signature",
+            ]
+            .iter()
+            .map(|key: &&str| key.to_string())
+            .collect(),
+        }
+    }
+}
+
+
+// Writes the URL with the values chosen by its options hidden.
+pub struct Redacted<'a> {
+    url: &'a URL,
+    options: Cow<'a, RedactOptions>,
+}
+
+impl Redacted<'_> {
+    fn redact(&self) -> URL {
+        let mut url: URL = self.url.clone();
+        if !url.password.is_empty() {
+            url.password = REDACTED.to_string();
+        }
+        if self.options.username && !url.username.is_empty() {
+            url.username = REDACTED.to_string();
+        }
+        // Fragments can carry parameters too, e.g. `#access_token=...` of the OAuth implicit flow.
+        url.query = url
+            .query
+            .as_deref()
+            .map(|query: &str| self.redact_pairs(query));
+        url.fragment = url
+            .fragment
+            .as_deref()
+            .map(|fragment: &str| self.redact_pairs(fragment));
+        url.update_serialization();
+        url
+    }
+
+    // Hides the values of the `key=value` pairs with a secret key. Pairs without `=` are kept.
+    fn redact_pairs(&self, pairs: &str) -> String {
+        let pairs: Vec<String> = pairs
+            .split('&')
+            .map(|pair: &str| -> String {
+                let Some((key, _)) = pair.split_once('=') else {
+                    return pair.to_string();
+                };
+                let name: String = percent_decode_str(&key.replace('+', " "));
+                if self
+                    .options
+                    .query_keys
+                    .iter()
+                    .any(|secret: &String| secret.eq_ignore_ascii_case(&name))
+                {
+                    format!("{}={}", key, REDACTED)
+                } else {
+                    pair.to_string()
+                }
+            })
+            .collect();
+        pairs.join("&")
+    }
+}
+
+impl Debug for Redacted<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self.redact(), fmt)
+    }
+}
+
+impl Display for Redacted<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.redact().href())
+    }
+}
+
+
+impl URL {
+    // Hides the password and the values of the query and fragment keys in `RedactOptions::default()`.
+    pub fn redacted(&self) -> Redacted<'_> {
+        Redacted {
+            url: self,
+            options: Cow::Owned(Default::default()),
+        }
+    }
+
+    pub fn redacted_with<'a>(&'a self, options: &'a RedactOptions) -> Redacted<'a> {
+        Redacted {
+            url: self,
+            options: Cow::Borrowed(options),
+        }
+    }
+}
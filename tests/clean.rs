@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "clean")]
+
+use url_parser::clean::{self, Rules, RulesError};
+use url_parser::url::URL;
+
+
+fn clean(input: &str) -> Option<String> {
+    clean::clean(&URL::parse(input).unwrap()).map(|url: URL| url.href().to_string())
+}
+
+
+#[test]
+fn tracking_params() {
+    assert_eq!(
+        clean("https://example.com/a?utm_source=x&id=1&UTM_Medium=y&fbclid=z#top"),
+        Some("https://example.com/a?id=1#top".to_string()),
+    );
+    assert_eq!(
+        clean("https://example.com/?gclid=1&utm_campaign=2"),
+        Some("https://example.com/".to_string())
+    );
+    assert_eq!(
+        clean("https://example.com/?a=%20&b&&utm_id=1"),
+        Some("https://example.com/?a=%20&b&".to_string())
+    );
+    assert_eq!(
+        clean("https://example.com/#utm_source=x&section=2"),
+        Some("https://example.com/#section=2".to_string())
+    );
+    assert_eq!(
+        clean("https://example.com/?utmost=1"),
+        Some("https://example.com/?utmost=1".to_string())
+    );
+    assert_eq!(
+        clean("https://www.youtube.com/watch?v=abc&si=xyz&feature=share"),
+        Some("https://www.youtube.com/watch?v=abc".to_string())
+    );
+    assert_eq!(
+        clean("https://www.amazon.com/dp/B000/ref=sr_1_1?qid=1&tag=aff-20&th=1"),
+        Some("https://www.amazon.com/dp/B000".to_string()),
+    );
+}
+
+
+#[test]
+fn redirections() {
+    assert_eq!(
+        clean("https://www.google.com/url?sa=t&url=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1%26utm_source%3Dg&usg=x"),
+        Some("https://example.com/a?b=1".to_string()),
+    );
+    assert_eq!(
+        clean(concat!(
+            "https://l.facebook.com/l.php",
+            "?u=https%3A%2F%2Fwww.google.com%2Furl%3Fq%3Dhttps%253A%252F%252Fexample.com%252F&h=x",
+        )),
+        Some("https://example.com/".to_string()),
+    );
+    assert_eq!(
+        clean("https://www.google.com/search?q=rust&ei=x"),
+        Some("https://www.google.com/search?q=rust".to_string())
+    );
+    assert_eq!(
+        clean("https://www.google.com/maps/place?ved=1"),
+        Some("https://www.google.com/maps/place?ved=1".to_string()),
+    );
+}
+
+
+#[test]
+fn custom_rules() {
+    let rules: Rules = Rules::parse(
+        r#"{
+            "providers": {
+                "tracker": {
+                    "urlPattern": "^https?://tracker\\.example/",
+                    "completeProvider": true
+                },
+                "shop": {
+                    "urlPattern": "^https?://shop\\.example/",
+                    "rules": ["session"],
+                    "referralMarketing": ["aff"],
+                    "rawRules": ["/track/[0-9]+"],
+                    "exceptions": ["/checkout"]
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let url: URL = URL::parse("https://shop.example/track/12/item?session=1&aff=2&id=3").unwrap();
+    assert_eq!(
+        rules.clean(&url).unwrap().href(),
+        "https://shop.example/item?id=3"
+    );
+    let url: URL = URL::parse("https://shop.example/checkout?session=1").unwrap();
+    assert_eq!(rules.clean(&url).unwrap(), url);
+    assert_eq!(
+        rules.clean(&URL::parse("https://tracker.example/pixel").unwrap()),
+        None
+    );
+
+    assert!(matches!(Rules::parse("{}"), Err(RulesError::Json(_))));
+    assert!(matches!(
+        Rules::parse(r#"{"providers": {"bad": {"urlPattern": "("}}}"#),
+        Err(RulesError::InvalidRegex { provider, pattern }) if provider == "bad" && pattern == "(",
+    ));
+    assert!(matches!(
+        Rules::from_file("tests/missing.json"),
+        Err(RulesError::Io(_))
+    ));
+}
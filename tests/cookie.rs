@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use url_parser::cookie::{default_path, domain_attribute, domain_match, path_match, CookieDomain};
+use url_parser::url::URL;
+
+
+fn url(input: &str) -> URL {
+    URL::parse(input).unwrap()
+}
+
+
+#[test]
+fn domains() {
+    assert!(domain_match(
+        &url("https://www.example.com/"),
+        "example.com"
+    ));
+    assert!(domain_match(
+        &url("https://www.example.com/"),
+        "www.example.com"
+    ));
+    assert!(domain_match(
+        &url("https://WWW.Example.com/"),
+        "Example.COM"
+    ));
+    assert!(!domain_match(
+        &url("https://wwwexample.com/"),
+        "example.com"
+    ));
+    assert!(!domain_match(
+        &url("https://example.com/"),
+        "www.example.com"
+    ));
+    assert!(domain_match(&url("http://127.0.0.1/"), "127.0.0.1"));
+    assert!(!domain_match(&url("http://127.0.0.1/"), "0.0.1"));
+    assert!(!domain_match(&url("file:///tmp/"), ""));
+}
+
+
+#[test]
+fn domain_attributes() {
+    let request: URL = url("https://www.example.co.uk/");
+    assert_eq!(
+        domain_attribute(&request, ".Example.co.uk"),
+        Some(CookieDomain::Domain("example.co.uk".to_string()))
+    );
+    assert_eq!(
+        domain_attribute(&request, ""),
+        Some(CookieDomain::HostOnly("www.example.co.uk".to_string()))
+    );
+    assert_eq!(domain_attribute(&request, "co.uk"), None);
+    assert_eq!(domain_attribute(&request, "other.co.uk"), None);
+    assert_eq!(domain_attribute(&request, "sub.www.example.co.uk"), None);
+    assert_eq!(
+        domain_attribute(&url("https://github.io/"), "github.io"),
+        Some(CookieDomain::HostOnly("github.io".to_string()))
+    );
+    assert_eq!(
+        domain_attribute(&url("https://alice.github.io/"), "github.io"),
+        None
+    );
+    assert_eq!(
+        domain_attribute(
+            &url("https://www.b\u{fc}cher.example/"),
+            "B\u{fc}cher.example"
+        ),
+        Some(CookieDomain::Domain("xn--bcher-kva.example".to_string())),
+    );
+}
+
+
+#[test]
+fn paths() {
+    assert_eq!(default_path(&url("https://example.com")), "/".to_string());
+    assert_eq!(default_path(&url("https://example.com/a")), "/".to_string());
+    assert_eq!(
+        default_path(&url("https://example.com/a/b?c=/d")),
+        "/a".to_string()
+    );
+    assert_eq!(
+        default_path(&url("https://example.com/a/b/")),
+        "/a/b".to_string()
+    );
+    assert_eq!(
+        default_path(&url("mailto:user@example.com")),
+        "/".to_string()
+    );
+
+    assert!(path_match(&url("https://example.com/docs"), "/docs"));
+    assert!(path_match(&url("https://example.com/docs/web"), "/docs"));
+    assert!(path_match(&url("https://example.com/docs/web"), "/docs/"));
+    assert!(path_match(&url("https://example.com/docs/web"), "/"));
+    assert!(!path_match(&url("https://example.com/docsets"), "/docs"));
+    assert!(!path_match(&url("https://example.com/"), "/docs"));
+}
@@ -0,0 +1,51 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use url_parser::data_url::{DataURL, MIMEType};
+use url_parser::url::URL;
+
+
+#[test]
+fn process() {
+    let url: URL = URL::parse("data:Text/HTML;Charset=UTF-8;base64,PGI+aGk8L2I+#top").unwrap();
+    let data_url: DataURL = DataURL::process(&url).unwrap();
+    assert_eq!(data_url.mime_type().essence(), "text/html");
+    assert_eq!(data_url.mime_type().parameter("charset"), Some("UTF-8"));
+    assert_eq!(data_url.body(), b"<b>hi</b>");
+
+    let url: URL = URL::parse("data:;charset=utf-8,a%20b").unwrap();
+    let data_url: DataURL = DataURL::process(&url).unwrap();
+    assert_eq!(data_url.mime_type().to_string(), "text/plain;charset=utf-8");
+    assert_eq!(data_url.into_body(), b"a b");
+
+    assert_eq!(
+        DataURL::process(&URL::parse("data:text/plain").unwrap()),
+        None
+    );
+    assert_eq!(
+        DataURL::process(&URL::parse("data:;base64,a").unwrap()),
+        None
+    );
+    assert_eq!(
+        DataURL::process(&URL::parse("http://example.com/,x").unwrap()),
+        None
+    );
+}
+
+
+#[test]
+fn mime_types() {
+    let mime_type: MIMEType = MIMEType::parse(" multipart/form-data; boundary=\"a b\"; BOUNDARY=c; x").unwrap();
+    assert_eq!(mime_type.type_(), "multipart");
+    assert_eq!(mime_type.subtype(), "form-data");
+    assert_eq!(
+        mime_type.parameters(),
+        [("boundary".to_string(), "a b".to_string())]
+    );
+    assert_eq!(
+        mime_type.to_string(),
+        "multipart/form-data;boundary=\"a b\""
+    );
+    assert_eq!(MIMEType::parse("text/"), None);
+    assert_eq!(MIMEType::parse("text plain"), None);
+}
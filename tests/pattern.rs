@@ -0,0 +1,386 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Conformance tests driven by `tests/wpt/urlpatterntestdata.json` from web-platform-tests
+// (urlpattern). Cases marked `skip` need ECMAScript regular expressions and are not run; every
+// other case must pass.
+
+#![cfg(feature = "pattern")]
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+use url_parser::pattern::{
+    PatternError,
+    URLPattern,
+    URLPatternComponentResult,
+    URLPatternInit,
+    URLPatternOptions,
+    URLPatternResult,
+};
+use url_parser::url::URL;
+
+
+const COMPONENTS: [&str; 8] = [
+    "protocol", "username", "password", "hostname", "port", "pathname", "search", "hash",
+];
+
+
+fn component<'a>(init: &'a URLPatternInit, name: &str) -> &'a Option<String> {
+    match name {
+        "protocol" => &init.protocol,
+        "username" => &init.username,
+        "password" => &init.password,
+        "hostname" => &init.hostname,
+        "port" => &init.port,
+        "pathname" => &init.pathname,
+        "search" => &init.search,
+        _ => &init.hash,
+    }
+}
+
+
+fn pattern_component<'a>(pattern: &'a URLPattern, name: &str) -> &'a str {
+    match name {
+        "protocol" => pattern.protocol(),
+        "username" => pattern.username(),
+        "password" => pattern.password(),
+        "hostname" => pattern.hostname(),
+        "port" => pattern.port(),
+        "pathname" => pattern.pathname(),
+        "search" => pattern.search(),
+        _ => pattern.hash(),
+    }
+}
+
+
+fn result_component<'a>(result: &'a URLPatternResult, name: &str) -> &'a URLPatternComponentResult {
+    match name {
+        "protocol" => &result.protocol,
+        "username" => &result.username,
+        "password" => &result.password,
+        "hostname" => &result.hostname,
+        "port" => &result.port,
+        "pathname" => &result.pathname,
+        "search" => &result.search,
+        _ => &result.hash,
+    }
+}
+
+
+// The URL components as in the pattern data: no `:` after the protocol, no `?` or `#` before the
+// search or hash.
+fn url_component(url: &URL, name: &str) -> String {
+    match name {
+        "protocol" => url.scheme().to_string(),
+        "username" => url.username().to_string(),
+        "password" => url.password().to_string(),
+        "hostname" => url.host_str().unwrap_or_default(),
+        "port" => {
+            url.port()
+                .map(|port: u16| port.to_string())
+                .unwrap_or_default()
+        }
+        "pathname" => url.path(),
+        "search" => url.query().unwrap_or_default().to_string(),
+        _ => url.fragment().unwrap_or_default().to_string(),
+    }
+}
+
+
+// An init dictionary, or `Err` if its base URL is invalid.
+fn init(object: &Map<String, Value>) -> Result<URLPatternInit, String> {
+    let string = |name: &str| -> Option<String> { object.get(name).and_then(Value::as_str).map(str::to_string) };
+    let base_url: Option<URL> = match string("baseURL") {
+        Some(base) => Some(URL::parse(&base).map_err(|err| format!("invalid base URL {:?}: {}", base, err))?),
+        None => None,
+    };
+    Ok(URLPatternInit {
+        protocol: string("protocol"),
+        username: string("username"),
+        password: string("password"),
+        hostname: string("hostname"),
+        port: string("port"),
+        pathname: string("pathname"),
+        search: string("search"),
+        hash: string("hash"),
+        base_url,
+    })
+}
+
+
+// The constructor arguments: a string or init, then an optional base URL string, then optional
+// options. A base URL after the options is a type error.
+fn construct(arguments: &[Value]) -> Result<(URLPattern, URLPatternInit, Option<URL>), String> {
+    let mut options: URLPatternOptions = URLPatternOptions::default();
+    let mut has_options: bool = false;
+    let mut base: Option<&str> = None;
+    let empty: Value = Value::Object(Map::new());
+    let input: &Value = arguments.first().unwrap_or(&empty);
+    for argument in arguments.iter().skip(1) {
+        match argument {
+            Value::String(value) if base.is_none() && !has_options => base = Some(value),
+            Value::Object(object) => {
+                has_options = true;
+                options.ignore_case = object
+                    .get("ignoreCase")
+                    .and_then(Value::as_bool)
+                    .unwrap_or_default();
+            }
+            _ => return Err(format!("unexpected argument {}", argument)),
+        }
+    }
+    let base: Option<URL> = match base {
+        Some(base) => Some(URL::parse(base).map_err(|err| format!("invalid base URL {:?}: {}", base, err))?),
+        None => None,
+    };
+    let error = |err: PatternError| -> String { err.to_string() };
+    match input {
+        Value::String(input) => {
+            let pattern: URLPattern = URLPattern::parse_with_options(input, base.as_ref(), options).map_err(error)?;
+            Ok((pattern, URLPatternInit::default(), base))
+        }
+        Value::Object(object) if base.is_none() => {
+            let init: URLPatternInit = init(object)?;
+            let pattern: URLPattern = URLPattern::from_init_with_options(&init, options).map_err(error)?;
+            let base: Option<URL> = init.base_url.clone();
+            Ok((pattern, init, base))
+        }
+        _ => Err("an init with a base URL argument".to_string()),
+    }
+}
+
+
+// The pattern string of a component missing from `expected_obj`: the init's own, `*` if an earlier
+// component was given, or else the base URL's.
+fn default_pattern(name: &str, init: &URLPatternInit, base: Option<&URL>, is_init: bool) -> String {
+    if let Some(value) = component(init, name) {
+        return value.clone();
+    }
+    let index: usize = COMPONENTS
+        .iter()
+        .position(|component: &&str| *component == name)
+        .unwrap();
+    let has_earlier: bool = is_init
+        && ["protocol", "hostname", "port", "pathname", "search"]
+            .iter()
+            .filter(|earlier: &&&str| {
+                COMPONENTS
+                    .iter()
+                    .position(|component: &&str| component == *earlier)
+                    .unwrap()
+                    < index
+            })
+            .any(|earlier: &&str| component(init, earlier).is_some());
+    match base {
+        Some(base) if !has_earlier && !matches!(name, "username" | "password") => url_component(base, name),
+        _ => "*".to_string(),
+    }
+}
+
+
+// The result of matching the inputs, or `Err` if they are invalid arguments.
+fn exec(pattern: &URLPattern, inputs: &[Value]) -> Result<Option<URLPatternResult>, String> {
+    match (inputs.first(), inputs.get(1)) {
+        (None, _) => Ok(pattern.exec_init(&URLPatternInit::default())),
+        (Some(Value::String(input)), base) => {
+            let url: Result<URL, _> = match base.and_then(Value::as_str) {
+                Some(base) => {
+                    match URL::parse(base) {
+                        Ok(base) => URL::parse_with_base(input, &base),
+                        Err(_) => return Ok(None),
+                    }
+                }
+                None => URL::parse(input),
+            };
+            Ok(url.ok().and_then(|url: URL| pattern.exec(&url)))
+        }
+        (Some(Value::Object(object)), None) => {
+            match init(object) {
+                Ok(init) => Ok(pattern.exec_init(&init)),
+                Err(_) => Ok(None),
+            }
+        }
+        _ => Err("an init with a base URL argument".to_string()),
+    }
+}
+
+
+fn check_match(case: &Map<String, Value>, actual: Option<URLPatternResult>) -> Result<(), String> {
+    let expected: &Map<String, Value> = match (&case["expected_match"], &actual) {
+        (Value::Null, None) => return Ok(()),
+        (Value::Null, Some(actual)) => return Err(format!("expected no match, got {:?}", actual)),
+        (Value::Object(expected), Some(_)) => expected,
+        (expected, None) => return Err(format!("expected {}, got no match", expected)),
+        (expected, Some(actual)) => return Err(format!("expected {}, got {:?}", expected, actual)),
+    };
+    let actual: URLPatternResult = actual.unwrap();
+    let exactly_empty: Vec<&str> = case
+        .get("exactly_empty_components")
+        .and_then(Value::as_array)
+        .map(|names: &Vec<Value>| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for name in COMPONENTS {
+        let (input, groups): (String, BTreeMap<String, Option<String>>) = match expected.get(name) {
+            Some(expected) => {
+                (
+                    expected["input"].as_str().unwrap().to_string(),
+                    expected["groups"]
+                        .as_object()
+                        .unwrap()
+                        .iter()
+                        .map(|(key, value): (&String, &Value)| (key.clone(), value.as_str().map(str::to_string)))
+                        .collect(),
+                )
+            }
+            None if exactly_empty.contains(&name) => (String::new(), BTreeMap::new()),
+            None => {
+                (
+                    String::new(),
+                    BTreeMap::from([("0".to_string(), Some(String::new()))]),
+                )
+            }
+        };
+        let actual: &URLPatternComponentResult = result_component(&actual, name);
+        let actual_groups: BTreeMap<String, Option<String>> = actual.groups.iter().cloned().collect();
+        if actual.input != input || actual_groups != groups {
+            return Err(format!(
+                "{}: expected {:?} {:?}, got {:?} {:?}",
+                name, input, groups, actual.input, actual_groups
+            ));
+        }
+    }
+    Ok(())
+}
+
+
+fn run(case: &Map<String, Value>) -> Result<(), String> {
+    let arguments: &Vec<Value> = case["pattern"].as_array().unwrap();
+    let expects_error: bool = case.get("expected_obj").and_then(Value::as_str) == Some("error");
+    let (pattern, init, base) = match construct(arguments) {
+        Ok(_) if expects_error => return Err("expected a constructor error".to_string()),
+        Ok(constructed) => constructed,
+        Err(_) if expects_error => return Ok(()),
+        Err(message) => return Err(message),
+    };
+    let expected_obj: Option<&Map<String, Value>> = case.get("expected_obj").and_then(Value::as_object);
+    for name in COMPONENTS {
+        let expected: String = match expected_obj.and_then(|expected: &Map<String, Value>| expected.get(name)) {
+            Some(expected) => expected.as_str().unwrap().to_string(),
+            None if case
+                .get("exactly_empty_components")
+                .and_then(Value::as_array)
+                .is_some_and(|names: &Vec<Value>| names.iter().any(|value: &Value| value == name)) =>
+            {
+                String::new()
+            }
+            None => {
+                default_pattern(
+                    name,
+                    &init,
+                    base.as_ref(),
+                    !arguments.first().is_some_and(Value::is_string),
+                )
+            }
+        };
+        let actual: &str = pattern_component(&pattern, name);
+        if actual != expected {
+            return Err(format!(
+                "{} pattern: expected {:?}, got {:?}",
+                name, expected, actual
+            ));
+        }
+    }
+    let inputs: &[Value] = case
+        .get("inputs")
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice);
+    if case.get("inputs").is_none() {
+        return Ok(());
+    }
+    let expects_error: bool = case["expected_match"].as_str() == Some("error");
+    match exec(&pattern, inputs) {
+        Ok(_) if expects_error => Err("expected a match error".to_string()),
+        Ok(actual) => check_match(case, actual),
+        Err(_) if expects_error => Ok(()),
+        Err(message) => Err(message),
+    }
+}
+
+
+#[test]
+fn urlpatterntestdata() {
+    let cases: Vec<Value> = serde_json::from_str(include_str!("wpt/urlpatterntestdata.json")).unwrap();
+    let mut message: String = String::new();
+    for (i, case) in cases.iter().enumerate() {
+        let case: &Map<String, Value> = case.as_object().unwrap();
+        if case.contains_key("skip") {
+            continue;
+        }
+        if let Err(failure) = run(case) {
+            message += &format!(
+                "FAIL case {}\n    {}\n    {}\n",
+                i,
+                Value::from(case.clone()),
+                failure
+            );
+        }
+    }
+    assert!(message.is_empty(), "\n{}", message);
+}
+
+
+#[test]
+fn match_url() {
+    let pattern: URLPattern = URLPattern::parse("https://*.example.com/users/:id{/}?").unwrap();
+    let url: URL = URL::parse("https://api.example.com/users/42?tab=posts").unwrap();
+    assert!(pattern.test(&url));
+    let result: URLPatternResult = pattern.exec(&url).unwrap();
+    assert_eq!(result.hostname.group("0"), Some("api"));
+    assert_eq!(result.pathname.group("id"), Some("42"));
+    assert_eq!(result.search.input, "tab=posts");
+    assert!(!pattern.test(&URL::parse("https://example.org/users/42").unwrap()));
+    assert!(!pattern.test(&URL::parse("http://api.example.com/users/42").unwrap()));
+}
+
+
+#[test]
+fn base_url() {
+    assert_eq!(
+        URLPattern::parse("/users/:id").unwrap_err(),
+        PatternError::BaseURLRequired
+    );
+    let base: URL = URL::parse("https://example.com/app/").unwrap();
+    let pattern: URLPattern = URLPattern::parse_with_base("users/:id", &base).unwrap();
+    assert_eq!(pattern.protocol(), "https");
+    assert_eq!(pattern.hostname(), "example.com");
+    assert_eq!(pattern.pathname(), "/app/users/:id");
+    assert!(pattern.test(&URL::parse("https://example.com/app/users/42").unwrap()));
+    assert!(!pattern.test(&URL::parse("https://example.com/users/42").unwrap()));
+}
+
+
+#[test]
+fn regexp_groups() {
+    let pattern: URLPattern = URLPattern::parse("https://example.com/posts/:year(\\d+)/:slug").unwrap();
+    assert!(pattern.has_regexp_groups());
+    let result: URLPatternResult = pattern
+        .exec(&URL::parse("https://example.com/posts/2023/hello").unwrap())
+        .unwrap();
+    assert_eq!(
+        result.pathname.groups,
+        vec![
+            ("year".to_string(), Some("2023".to_string())),
+            ("slug".to_string(), Some("hello".to_string()))
+        ]
+    );
+    assert!(!pattern.test(&URL::parse("https://example.com/posts/latest/hello").unwrap()));
+    assert!(matches!(
+        URLPattern::parse("https://example.com/(?=a)"),
+        Err(PatternError::Tokenize(_))
+    ));
+    assert!(matches!(
+        URLPattern::parse("https://example.com/(a(?=b))"),
+        Err(PatternError::InvalidRegex(_))
+    ));
+}
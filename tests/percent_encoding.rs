@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use url_parser::percent_encoding::{
+    percent_decode,
+    percent_decode_str,
+    utf8_percent_encode,
+    COMPONENT,
+    FRAGMENT,
+    PATH,
+    PATH_SEGMENT,
+    QUERY,
+    USERINFO,
+};
+
+
+#[test]
+fn encode_sets() {
+    assert_eq!(
+        utf8_percent_encode("a b\"#<>`", &FRAGMENT),
+        "a%20b%22#%3C%3E%60".to_string()
+    );
+    assert_eq!(
+        utf8_percent_encode("a b\"#<>`", &QUERY),
+        "a%20b%22%23%3C%3E`".to_string()
+    );
+    assert_eq!(
+        utf8_percent_encode("/a?b^{c}", &PATH),
+        "/a%3Fb^%7Bc%7D".to_string()
+    );
+    assert_eq!(
+        utf8_percent_encode("a/b\\c%d?", &PATH_SEGMENT),
+        "a%2Fb%5Cc%25d%3F".to_string()
+    );
+    assert_eq!(
+        utf8_percent_encode("user:pass@", &USERINFO),
+        "user%3Apass%40".to_string()
+    );
+    assert_eq!(
+        utf8_percent_encode("a+b&c,d", &COMPONENT),
+        "a%2Bb%26c%2Cd".to_string()
+    );
+    assert_eq!(
+        utf8_percent_encode("\u{7f}é", &QUERY),
+        "%7F%C3%A9".to_string()
+    );
+}
+
+
+#[test]
+fn decode() {
+    assert_eq!(percent_decode(b"%41%4a%4A"), b"AJJ".to_vec());
+    assert_eq!(percent_decode(b"%%4%zz%4"), b"%%4%zz%4".to_vec());
+    assert_eq!(percent_decode_str("%E6%97%A5%FF"), "日\u{fffd}".to_string());
+}
@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// `tests/psl/test_psl.txt` is the test file published with the Public Suffix List.
+
+use std::path::PathBuf;
+use std::{env, fs};
+
+use url_parser::idna;
+use url_parser::psl::{self, List, Mode};
+use url_parser::url::Host;
+
+
+// Reads a `'quoted'` or `null` argument.
+fn argument(input: &str) -> Option<&str> {
+    input
+        .trim()
+        .strip_prefix('\'')
+        .and_then(|input: &str| input.strip_suffix('\''))
+}
+
+
+#[test]
+fn check_public_suffix() {
+    let list: &List = List::builtin();
+    let mut failures: Vec<String> = Vec::new();
+    for line in include_str!("psl/test_psl.txt").lines() {
+        let Some(arguments) = line
+            .strip_prefix("checkPublicSuffix(")
+            .and_then(|line: &str| line.strip_suffix(");"))
+        else {
+            continue;
+        };
+        let (input, expected) = arguments.split_once(',').unwrap();
+        let Some(input) = argument(input) else {
+            continue;
+        };
+        let domain: String = idna::domain_to_ascii(input).unwrap();
+        let expected: Option<String> = argument(expected).map(|expected: &str| idna::domain_to_ascii(expected).unwrap());
+        let actual: Option<&str> = list.registrable_domain(&domain, Mode::All);
+        if actual != expected.as_deref() {
+            failures.push(format!(
+                "{}: expected {:?}, got {:?}",
+                input, expected, actual
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+
+#[test]
+fn modes() {
+    let list: &List = List::builtin();
+    assert_eq!(
+        list.public_suffix("www.example.co.uk", Mode::All),
+        Some("co.uk")
+    );
+    assert_eq!(
+        list.public_suffix("www.example.co.uk.", Mode::All),
+        Some("co.uk.")
+    );
+    assert_eq!(
+        list.registrable_domain("www.example.co.uk.", Mode::All),
+        Some("example.co.uk.")
+    );
+    assert_eq!(
+        list.public_suffix("user.github.io", Mode::All),
+        Some("github.io")
+    );
+    assert_eq!(
+        list.public_suffix("user.github.io", Mode::Icann),
+        Some("io")
+    );
+    assert_eq!(
+        list.registrable_domain("user.github.io", Mode::Icann),
+        Some("github.io")
+    );
+    assert!(list.is_public_suffix("github.io", Mode::All));
+    assert!(!list.is_public_suffix("github.io", Mode::Icann));
+    assert_eq!(list.public_suffix("", Mode::All), None);
+}
+
+
+#[test]
+fn runtime_list() {
+    let source: &str =
+        "// ===BEGIN ICANN DOMAINS===\nexample\n*.wild.example\n!www.wild.example\n// ===END ICANN DOMAINS===\n";
+    let path: PathBuf = env::temp_dir().join(format!("url_parser_psl_{}.dat", std::process::id()));
+    fs::write(&path, source).unwrap();
+    let list: List = List::from_file(&path).unwrap();
+    assert_eq!(
+        list.registrable_domain("a.b.example", Mode::All),
+        Some("b.example")
+    );
+    assert_eq!(
+        list.registrable_domain("a.b.wild.example", Mode::All),
+        Some("a.b.wild.example")
+    );
+    assert_eq!(
+        list.registrable_domain("www.wild.example", Mode::All),
+        Some("www.wild.example")
+    );
+    assert_eq!(list.public_suffix("example.com", Mode::All), Some("com"));
+
+    let host: Host = Host::parse("a.b.wild.example").unwrap();
+    assert_eq!(host.public_suffix(), Some("example"));
+    psl::load_from_file(&path).unwrap();
+    assert_eq!(host.public_suffix(), Some("b.wild.example"));
+    psl::reset_list();
+    assert_eq!(host.public_suffix(), Some("example"));
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        Host::parse("www.example.com").unwrap().registrable_domain(),
+        Some("example.com")
+    );
+    assert!(Host::parse("co.uk").unwrap().is_public_suffix());
+    assert_eq!(Host::parse("127.0.0.1").unwrap().public_suffix(), None);
+}
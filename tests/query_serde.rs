@@ -0,0 +1,217 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use url_parser::query::{from_query_params, to_query_params};
+use url_parser::template::Template;
+use url_parser::url::URL;
+use url_parser::QueryParams;
+
+
+#[derive(Debug, PartialEq, Deserialize, QueryParams, Serialize)]
+struct Search {
+    q: String,
+    page: u32,
+    exact: bool,
+    lang: Option<String>,
+    tags: Vec<String>,
+    range: (u8, u8),
+}
+
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Order {
+    Asc,
+    Desc,
+}
+
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Paging {
+    limit: u8,
+    order: Order,
+}
+
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Flattened {
+    q: String,
+    #[serde(flatten)]
+    paging: Paging,
+}
+
+
+#[derive(Debug, Serialize)]
+struct Nested {
+    values: Vec<Vec<u8>>,
+}
+
+
+#[test]
+fn same_as_derive() {
+    let search: Search = Search {
+        q: "rust".to_string(),
+        page: 2,
+        exact: false,
+        lang: None,
+        tags: vec!["url".to_string(), "parser".to_string()],
+        range: (1, 9),
+    };
+    assert_eq!(to_query_params(&search).unwrap(), search.to_query_params());
+    assert_eq!(
+        to_query_params(&search).unwrap(),
+        "?q=rust&page=2&exact=false&tags=url,parser&range=1,9".to_string(),
+    );
+    let search: Search = Search {
+        q: "a&b c,d".to_string(),
+        page: 1,
+        exact: true,
+        lang: Some("x y".to_string()),
+        tags: vec!["e,f".to_string(), "g&h".to_string()],
+        range: (0, 0),
+    };
+    assert_eq!(to_query_params(&search).unwrap(), search.to_query_params());
+    assert_eq!(
+        search.to_query_params(),
+        "?q=a%26b%20c%2Cd&page=1&exact=true&lang=x%20y&tags=e%2Cf,g%26h&range=0,0".to_string(),
+    );
+    assert_eq!(
+        from_query_params::<Search>(&search.to_query_params()).unwrap(),
+        search
+    );
+}
+
+
+#[test]
+fn percent_encoding() {
+    let search: Search = Search {
+        q: "a&b=c d,e".to_string(),
+        page: 1,
+        exact: true,
+        lang: Some("日本語".to_string()),
+        tags: vec!["x,y".to_string(), "%".to_string()],
+        range: (0, 0),
+    };
+    let query: String = to_query_params(&search).unwrap();
+    assert_eq!(
+        query,
+        concat!(
+            "?q=a%26b%3Dc%20d%2Ce&page=1&exact=true&lang=%E6%97%A5%E6%9C%AC%E8%AA%9E",
+            "&tags=x%2Cy,%25&range=0,0",
+        )
+        .to_string(),
+    );
+    assert_eq!(from_query_params::<Search>(&query).unwrap(), search);
+}
+
+
+#[test]
+fn round_trip() {
+    let query: &str = "?q=rust&page=2&exact=false&tags=url,parser&range=1,9";
+    let search: Search = from_query_params(query).unwrap();
+    assert_eq!(
+        search,
+        Search {
+            q: "rust".to_string(),
+            page: 2,
+            exact: false,
+            lang: None,
+            tags: vec!["url".to_string(), "parser".to_string()],
+            range: (1, 9),
+        },
+    );
+    assert_eq!(to_query_params(&search).unwrap(), query.to_string());
+
+    let search: Search = Search {
+        q: String::new(),
+        page: 0,
+        exact: false,
+        lang: None,
+        tags: Vec::new(),
+        range: (0, 0),
+    };
+    let query: String = to_query_params(&search).unwrap();
+    assert_eq!(query, "?page=0&exact=false&range=0,0".to_string());
+    assert_eq!(from_query_params::<Search>(&query).unwrap(), search);
+}
+
+
+#[test]
+fn flatten_and_enums() {
+    let flattened: Flattened = Flattened {
+        q: "rust".to_string(),
+        paging: Paging {
+            limit: 10,
+            order: Order::Desc,
+        },
+    };
+    let query: String = to_query_params(&flattened).unwrap();
+    assert_eq!(query, "?q=rust&limit=10&order=desc".to_string());
+    assert_eq!(
+        from_query_params::<Paging>(&query).unwrap(),
+        flattened.paging
+    );
+}
+
+
+#[test]
+fn maps() {
+    let mut map: BTreeMap<String, Option<u8>> = BTreeMap::new();
+    map.insert("a b".to_string(), Some(1));
+    map.insert("c".to_string(), None);
+    assert_eq!(to_query_params(&map).unwrap(), "?a%20b=1".to_string());
+    assert_eq!(
+        from_query_params::<BTreeMap<String, String>>("a%20b=1&c=").unwrap(),
+        BTreeMap::from([
+            ("a b".to_string(), "1".to_string()),
+            ("c".to_string(), "".to_string())
+        ]),
+    );
+}
+
+
+#[test]
+fn errors() {
+    assert!(to_query_params(&1).is_err());
+    assert!(to_query_params(&Nested {
+        values: vec![vec![1]]
+    })
+    .is_err());
+    assert!(from_query_params::<Search>("?q=rust&page=two").is_err());
+    assert_eq!(
+        from_query_params::<Search>("?q=rust")
+            .unwrap_err()
+            .to_string(),
+        "missing field `page`".to_string(),
+    );
+}
+
+
+#[test]
+fn template_bindings() {
+    let template: Template = Template::parse("/search/{q}{?page,exact,tags,range}").unwrap();
+    let url: URL = URL::parse("https://example.com/search/url%20parser?tags=a,b&page=3&exact=true&range=1,5").unwrap();
+    let search: Search = template.match_url(&url).unwrap().deserialize().unwrap();
+    assert_eq!(
+        search,
+        Search {
+            q: "url parser".to_string(),
+            page: 3,
+            exact: true,
+            lang: None,
+            tags: vec!["a".to_string(), "b".to_string()],
+            range: (1, 5),
+        }
+    );
+    let url: URL = URL::parse("https://example.com/search/x?page=next").unwrap();
+    assert!(template
+        .match_url(&url)
+        .unwrap()
+        .deserialize::<Search>()
+        .is_err());
+}
@@ -0,0 +1,335 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// `tests/uritemplate/` holds spec-examples.json, spec-examples-by-section.json, extended-tests.json
+// and negative-tests.json from the uritemplate-test suite
+// (https://github.com/uri-templates/uritemplate-test).
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value as Json};
+use url_parser::template::{Bindings, NoMatch, Template, TemplateErrorKind, Value, Variables};
+use url_parser::url::URL;
+use url_parser::QueryParams;
+
+
+// JSON numbers are written as they appear in the file, and `null` is undefined.
+fn variables(json: &Map<String, Json>) -> Variables {
+    let string = |json: &Json| -> String {
+        match json {
+            Json::String(string) => string.clone(),
+            json => json.to_string(),
+        }
+    };
+    let mut variables: Variables = Variables::new();
+    for (name, value) in json {
+        match value {
+            Json::Null => continue,
+            Json::Array(items) => variables.insert(name, items.iter().map(string).collect::<Vec<String>>()),
+            Json::Object(pairs) => {
+                variables.insert(
+                    name,
+                    Value::Assoc(
+                        pairs
+                            .iter()
+                            .map(|(key, value): (&String, &Json)| (key.clone(), string(value)))
+                            .collect(),
+                    ),
+                )
+            }
+            value => variables.insert(name, string(value)),
+        };
+    }
+    variables
+}
+
+
+fn run(source: &str) {
+    let groups: Map<String, Json> = serde_json::from_str(source).unwrap();
+    let mut failures: Vec<String> = Vec::new();
+    for (group, test) in &groups {
+        let variables: Variables = variables(test["variables"].as_object().unwrap());
+        for case in test["testcases"].as_array().unwrap() {
+            let input: &str = case[0].as_str().unwrap();
+            // Associative arrays can be expanded in any order, so some cases list every accepted result.
+            let expected: Vec<&str> = match &case[1] {
+                Json::String(expected) => vec![expected.as_str()],
+                Json::Array(alternatives) => {
+                    alternatives
+                        .iter()
+                        .map(|json: &Json| json.as_str().unwrap())
+                        .collect()
+                }
+                // `false`: the template fails to parse or to expand.
+                _ => {
+                    if let Ok(actual) = Template::parse(input).and_then(|template: Template| template.expand(&variables)) {
+                        failures.push(format!(
+                            "{}: {}: expected an error, got {:?}",
+                            group, input, actual
+                        ));
+                    }
+                    continue;
+                }
+            };
+            match Template::parse(input).and_then(|template: Template| template.expand(&variables)) {
+                Ok(actual) => {
+                    if !expected.contains(&actual.as_str()) {
+                        failures.push(format!(
+                            "{}: {}: expected {:?}, got {:?}",
+                            group, input, expected, actual
+                        ));
+                    }
+                }
+                Err(err) => failures.push(format!("{}: {}: {}", group, input, err)),
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+
+#[test]
+fn spec_examples() {
+    run(include_str!("uritemplate/spec-examples.json"));
+}
+
+
+#[test]
+fn spec_examples_by_section() {
+    run(include_str!("uritemplate/spec-examples-by-section.json"));
+}
+
+
+#[test]
+fn extended_tests() {
+    run(include_str!("uritemplate/extended-tests.json"));
+}
+
+
+#[test]
+fn negative_tests() {
+    run(include_str!("uritemplate/negative-tests.json"));
+}
+
+
+#[test]
+fn parse_errors() {
+    let error = |input: &str| -> (TemplateErrorKind, std::ops::Range<usize>) {
+        let err = Template::parse(input).unwrap_err();
+        (err.kind, err.span)
+    };
+    assert_eq!(
+        error("/users/{id"),
+        (TemplateErrorKind::UnclosedExpression, 7..10)
+    );
+    assert_eq!(
+        error("/users/id}"),
+        (TemplateErrorKind::UnopenedExpression, 9..10)
+    );
+    assert_eq!(error("/a b"), (TemplateErrorKind::InvalidLiteral, 2..3));
+    assert_eq!(error("{=var}"), (TemplateErrorKind::InvalidOperator, 1..2));
+    assert_eq!(error("{?a,b-c}"), (TemplateErrorKind::InvalidVarname, 5..6));
+    assert_eq!(error("{a..b}"), (TemplateErrorKind::InvalidVarname, 3..4));
+    assert_eq!(error("{}"), (TemplateErrorKind::InvalidVarname, 1..1));
+    assert_eq!(
+        error("{var:10000}"),
+        (TemplateErrorKind::InvalidPrefix, 5..10)
+    );
+    assert_eq!(error("{var:0}"), (TemplateErrorKind::InvalidPrefix, 5..6));
+    assert_eq!(
+        Template::parse("/%7Eusers/{+path}")
+            .unwrap()
+            .variable_names(),
+        vec!["path"]
+    );
+}
+
+
+#[test]
+fn expand_errors() {
+    let mut variables: Variables = Variables::new();
+    variables.insert("list", vec!["a", "b"]);
+    variables.insert(
+        "keys",
+        Value::Assoc(vec![("a".to_string(), "1".to_string())]),
+    );
+    variables.insert("var", "value");
+    let error = |input: &str| -> (TemplateErrorKind, std::ops::Range<usize>) {
+        let err = Template::parse(input)
+            .unwrap()
+            .expand(&variables)
+            .unwrap_err();
+        (err.kind, err.span)
+    };
+    assert_eq!(
+        error("/x{list:1}"),
+        (TemplateErrorKind::PrefixOnComposite, 3..9)
+    );
+    assert_eq!(
+        error("/x{?var,keys:2}"),
+        (TemplateErrorKind::PrefixOnComposite, 8..14)
+    );
+    assert_eq!(
+        Template::parse("/x{var:2}{undefined:1}")
+            .unwrap()
+            .expand(&variables),
+        Ok("/xva".to_string())
+    );
+}
+
+
+#[derive(QueryParams)]
+struct Search {
+    q: String,
+    tags: Vec<String>,
+    page: Option<u32>,
+}
+
+
+#[test]
+fn sources() {
+    let template: Template = Template::parse("/search{?q,tags,page}").unwrap();
+    let search: Search = Search {
+        q: "rust".to_string(),
+        tags: vec!["url".to_string(), "web".to_string()],
+        page: None,
+    };
+    assert_eq!(
+        template
+            .expand(&Variables::from_query_params(&search))
+            .unwrap(),
+        "/search?q=rust&tags=url,web"
+    );
+
+    let template: Template = Template::parse("/users/{id}{/path*}{#section}").unwrap();
+    let map: HashMap<&str, &str> = HashMap::from([("id", "42"), ("section", "a b")]);
+    assert_eq!(
+        template.expand(&Variables::from(map)).unwrap(),
+        "/users/42#a%20b"
+    );
+    let map: BTreeMap<&str, Vec<&str>> = BTreeMap::from([("path", vec!["x", "y"])]);
+    assert_eq!(
+        template.expand(&Variables::from(map)).unwrap(),
+        "/users//x/y"
+    );
+}
+
+
+#[test]
+fn exploded_keys_are_encoded() {
+    let mut variables: Variables = Variables::new();
+    variables.insert(
+        "keys",
+        Value::Assoc(vec![("a&b".to_string(), "1".to_string())]),
+    );
+    assert_eq!(
+        Template::parse("/x{?keys*}")
+            .unwrap()
+            .expand(&variables)
+            .unwrap(),
+        "/x?a%26b=1"
+    );
+    assert_eq!(
+        Template::parse("/x{;keys*}")
+            .unwrap()
+            .expand(&variables)
+            .unwrap(),
+        "/x;a%26b=1"
+    );
+    assert_eq!(
+        Template::parse("/x{/keys*}")
+            .unwrap()
+            .expand(&variables)
+            .unwrap(),
+        "/x/a%26b=1"
+    );
+}
+
+
+#[test]
+fn match_url() {
+    let url: URL = URL::parse("https://example.com/users/42/posts/hello%20world?page=2&q=a,b#top").unwrap();
+    let template: Template = Template::parse("/users/{id}/posts/{slug}{?q,page}{#section}").unwrap();
+    let bindings: Bindings = template.match_url(&url).unwrap();
+    assert_eq!(bindings.get("id").as_deref(), Some("42"));
+    assert_eq!(bindings.get("slug").as_deref(), Some("hello world"));
+    assert_eq!(bindings.get_raw("slug"), Some("hello%20world"));
+    assert_eq!(bindings.get("q").as_deref(), Some("a,b"));
+    assert_eq!(bindings.get("page").as_deref(), Some("2"));
+    assert_eq!(bindings.get("section").as_deref(), Some("top"));
+
+    let template: Template = Template::parse("https://{host}/users/{id}{?page}").unwrap();
+    let url: URL = URL::parse("https://example.com/users/7").unwrap();
+    let bindings: Bindings = template.match_url(&url).unwrap();
+    assert_eq!(
+        bindings.iter().collect::<Vec<(&str, &str)>>(),
+        vec![("host", "example.com"), ("id", "7")]
+    );
+
+    let template: Template = Template::parse("/map{?x,y}{/path}{.ext}").unwrap();
+    let url: URL = URL::parse("https://example.com/map?x=1024&y=768").unwrap();
+    assert_eq!(template.match_url(&url).unwrap().len(), 2);
+    let url: URL = URL::parse("https://example.com/map/a.json").unwrap();
+    let bindings: Bindings = template.match_url(&url).unwrap();
+    assert_eq!(
+        (
+            bindings.get("path").as_deref(),
+            bindings.get("ext").as_deref()
+        ),
+        (Some("a"), Some("json"))
+    );
+
+    let template: Template = Template::parse("{x,y}").unwrap();
+    let url: URL = URL::parse("urn:1024,768").unwrap();
+    let bindings: Bindings = template.match_url(&url).unwrap();
+    assert_eq!(
+        (bindings.get("x").as_deref(), bindings.get("y").as_deref()),
+        (Some("1024"), Some("768"))
+    );
+}
+
+
+#[test]
+fn no_match() {
+    let url: URL = URL::parse("https://example.com/users/42/comments").unwrap();
+    let template: Template = Template::parse("/users/{id}/posts").unwrap();
+    assert_eq!(template.match_url(&url), None);
+    assert_eq!(
+        template.try_match_url(&url),
+        Err(NoMatch::Literal {
+            position: 10,
+            expected: "/posts".to_string()
+        })
+    );
+    let template: Template = Template::parse("/users/{id}").unwrap();
+    assert_eq!(template.try_match_url(&url), Err(NoMatch::TrailingInput(9)));
+    let template: Template = Template::parse("/users{/path*}").unwrap();
+    assert_eq!(
+        template.try_match_url(&url),
+        Err(NoMatch::UnsupportedModifier("path".to_string()))
+    );
+    let url: URL = URL::parse("https://example.com/search?q=a&other=1").unwrap();
+    let template: Template = Template::parse("/search{?q}").unwrap();
+    assert_eq!(
+        template.try_match_url(&url),
+        Err(NoMatch::TrailingInput(11))
+    );
+}
+
+
+// Backtracking over every split of the input took 47s on a 60-character path with six variables,
+// so a matcher that is exponential again would not finish on these.
+#[test]
+fn match_url_is_polynomial() {
+    let url: URL = URL::parse(&format!("https://example.com/{}", "a".repeat(200))).unwrap();
+    let template: Template = Template::parse("/{a}{b}{c}{d}{e}{f}{g}{h}").unwrap();
+    assert_eq!(
+        template.match_url(&url).unwrap().get("a"),
+        Some("a".repeat(193))
+    );
+    let url: URL = URL::parse(&format!("https://example.com/{}", "a,".repeat(60))).unwrap();
+    let template: Template = Template::parse("/{a,b,c,d,e,f,g,h,i,j}").unwrap();
+    assert!(template.match_url(&url).is_some());
+    let template: Template = Template::parse("/{a,b,c,d,e,f,g,h,i,j}/x").unwrap();
+    assert_eq!(template.match_url(&url), None);
+}
@@ -0,0 +1,427 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+// Conformance tests driven by the vendored data files in `tests/wpt/`:
+// - urltestdata.json, setters_tests.json and toascii.json from web-platform-tests
+// - IdnaTestV2.txt from Unicode 13.0.0, matching `data/IdnaMappingTable.txt`
+// - data-urls.json, base64.json, mime-types.json and generated-mime-types.json from
+//   web-platform-tests (fetch, mimesniff)
+// Cases listed in `tests/wpt/expected_failures.txt` must fail; every other case must pass.
+
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+use url_parser::data_url::{forgiving_base64_decode, DataURL, MIMEType};
+use url_parser::idna::Config;
+use url_parser::url::URL;
+
+
+struct Report {
+    expected_failures: BTreeSet<&'static str>,
+    failures: Vec<(String, String)>,
+    unexpected_successes: Vec<String>,
+}
+
+impl Report {
+    fn new() -> Self {
+        Self {
+            expected_failures: include_str!("wpt/expected_failures.txt")
+                .lines()
+                .filter(|line: &&str| !line.is_empty() && !line.starts_with('#'))
+                .collect(),
+            failures: Vec::new(),
+            unexpected_successes: Vec::new(),
+        }
+    }
+
+    fn check(&mut self, name: String, result: Result<(), String>) {
+        match (result, self.expected_failures.contains(name.as_str())) {
+            (Ok(()), true) => self.unexpected_successes.push(name),
+            (Err(message), false) => self.failures.push((name, message)),
+            _ => (),
+        }
+    }
+
+    fn assert(self) {
+        let mut message: String = String::new();
+        for (name, failure) in &self.failures {
+            message += &format!("FAIL {}\n    {}\n", name, failure);
+        }
+        for name in &self.unexpected_successes {
+            message += &format!("PASS {} (listed in expected_failures.txt)\n", name);
+        }
+        assert!(message.is_empty(), "\n{}", message);
+    }
+}
+
+
+// The getters of the URL Standard's `URL` interface.
+fn attribute(url: &URL, name: &str) -> Option<String> {
+    let prefixed = |prefix: &str, value: Option<&str>| -> String {
+        value
+            .filter(|value: &&str| !value.is_empty())
+            .map(|value: &str| prefix.to_string() + value)
+            .unwrap_or_default()
+    };
+    let port: String = url
+        .port()
+        .map(|port: u16| port.to_string())
+        .unwrap_or_default();
+    let value: String = match name {
+        "href" => url.href().to_string(),
+        "protocol" => format!("{}:", url.scheme()),
+        "username" => url.username().to_string(),
+        "password" => url.password().to_string(),
+        "host" => url.host_str().unwrap_or_default() + &prefixed(":", Some(&port)),
+        "hostname" => url.host_str().unwrap_or_default(),
+        "port" => port,
+        "pathname" => url.path(),
+        "search" => prefixed("?", url.query()),
+        "hash" => prefixed("#", url.fragment()),
+        "origin" => url.origin().ascii_serialization(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+
+fn check_attributes(url: &URL, expected: &Map<String, Value>) -> Result<(), String> {
+    for (name, expected) in expected {
+        let (Some(actual), Some(expected)) = (attribute(url, name), expected.as_str()) else {
+            continue;
+        };
+        if actual != expected {
+            return Err(format!(
+                "expected {} {:?}, got {:?}",
+                name, expected, actual
+            ));
+        }
+    }
+    Ok(())
+}
+
+
+fn run_url_test(test: &Map<String, Value>) -> Result<(), String> {
+    let input: &str = test["input"].as_str().unwrap();
+    let base: Option<URL> = match test.get("base").and_then(Value::as_str) {
+        Some(base) => {
+            match URL::parse(base) {
+                Ok(base) => Some(base),
+                Err(_) if test.contains_key("failure") => return Ok(()),
+                Err(err) => return Err(format!("base failed to parse: {}", err)),
+            }
+        }
+        None => None,
+    };
+    let result = match &base {
+        Some(base) => URL::parse_with_base(input, base),
+        None => URL::parse(input),
+    };
+    match result {
+        Ok(url) if test.contains_key("failure") => Err(format!("expected failure, got {}", url.href())),
+        Ok(url) => check_attributes(&url, test),
+        Err(_) if test.contains_key("failure") => Ok(()),
+        Err(err) => Err(format!("unexpected failure: {}", err)),
+    }
+}
+
+
+#[test]
+fn urltestdata() {
+    let tests: Vec<Value> = serde_json::from_str(include_str!("wpt/urltestdata.json")).unwrap();
+    let mut report: Report = Report::new();
+    for test in tests.iter().filter_map(Value::as_object) {
+        let mut name: String = format!("<{}>", test["input"].as_str().unwrap().escape_default());
+        if let Some(base) = test.get("base").and_then(Value::as_str) {
+            name += &format!(" against <{}>", base.escape_default());
+        }
+        report.check(name, run_url_test(test));
+    }
+    report.assert();
+}
+
+
+fn run_setter_test(setter: &str, test: &Map<String, Value>) -> Result<(), String> {
+    let mut url: URL = URL::parse(test["href"].as_str().unwrap()).map_err(|err| err.to_string())?;
+    let value: &str = test["new_value"].as_str().unwrap();
+    match setter {
+        "href" => url.set_href(value).map_err(|err| err.to_string())?,
+        "protocol" => url.set_protocol(value),
+        "username" => url.set_username(value),
+        "password" => url.set_password(value),
+        "host" => url.set_host(value),
+        "hostname" => url.set_hostname(value),
+        "port" => url.set_port(value),
+        "pathname" => url.set_pathname(value),
+        "search" => url.set_search(value),
+        "hash" => url.set_hash(value),
+        _ => return Err(format!("unknown setter {}", setter)),
+    }
+    check_attributes(&url, test["expected"].as_object().unwrap())
+}
+
+
+#[test]
+fn setters() {
+    let tests: Map<String, Value> = serde_json::from_str(include_str!("wpt/setters_tests.json")).unwrap();
+    let mut report: Report = Report::new();
+    for (setter, tests) in tests.iter().filter(|(setter, _)| *setter != "comment") {
+        for test in tests
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_object)
+        {
+            let name: String = format!(
+                "<{}> set {} to <{}>",
+                test["href"].as_str().unwrap().escape_default(),
+                setter,
+                test["new_value"].as_str().unwrap().escape_default(),
+            );
+            report.check(name, run_setter_test(setter, test));
+        }
+    }
+    report.assert();
+}
+
+
+// As in url/toascii.window.js: each input is used as the host of a URL, then set through the host
+// and hostname setters. A `null` output means the host is rejected, so parsing fails and the
+// setters keep the old host.
+fn run_toascii_test(test: &Map<String, Value>) -> Vec<(String, Result<(), String>)> {
+    let input: &str = test["input"].as_str().unwrap();
+    let expected: Option<&str> = test["output"].as_str();
+    let check = |actual: Option<String>, expected: Option<String>| -> Result<(), String> {
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}, got {:?}", expected, actual))
+        }
+    };
+    let name = |via: &str| -> String { format!("toascii <{}> {}", input.escape_default(), via) };
+    let parsed: Option<String> = URL::parse(&format!("https://{}/x", input))
+        .ok()
+        .map(|url: URL| url.href().to_string());
+    let mut results: Vec<(String, Result<(), String>)> = vec![(
+        name("(using URL)"),
+        check(
+            parsed,
+            expected.map(|output: &str| format!("https://{}/x", output)),
+        ),
+    )];
+    for (setter, set) in [
+        ("host", URL::set_host as fn(&mut URL, &str)),
+        ("hostname", URL::set_hostname),
+    ] {
+        let mut url: URL = URL::parse("https://x/x").unwrap();
+        set(&mut url, input);
+        let result: Result<(), String> = check(url.host_str(), Some(expected.unwrap_or("x").to_string()));
+        results.push((name(&format!("(using URL.{})", setter)), result));
+    }
+    results
+}
+
+
+#[test]
+fn toascii() {
+    let tests: Vec<Value> = serde_json::from_str(include_str!("wpt/toascii.json")).unwrap();
+    let mut report: Report = Report::new();
+    for test in tests.iter().filter_map(Value::as_object) {
+        for (name, result) in run_toascii_test(test) {
+            report.check(name, result);
+        }
+    }
+    report.assert();
+}
+
+
+// Resolves the `\uXXXX` and `\x{XXXX}` escapes used in IdnaTestV2.txt.
+fn unescape(input: &str) -> String {
+    let mut output: String = String::with_capacity(input.len());
+    let mut rest: &str = input;
+    while let Some(index) = rest.find('\\') {
+        output += &rest[..index];
+        rest = &rest[index..];
+        let (hex, len) = if let Some(escaped) = rest.strip_prefix("\\u") {
+            (&escaped[..4], 6)
+        } else if let Some(escaped) = rest.strip_prefix("\\x{") {
+            let end: usize = escaped.find('}').unwrap();
+            (&escaped[..end], end + 4)
+        } else {
+            output.push('\\');
+            rest = &rest[1..];
+            continue;
+        };
+        output.push(char::from_u32(u32::from_str_radix(hex, 16).unwrap()).unwrap());
+        rest = &rest[len..];
+    }
+    output + rest
+}
+
+
+// A case with status codes must fail, whichever check the codes name; a case without must convert
+// to `expected`.
+fn check_idna(expected: &str, status: &[String], actual: Option<String>) -> Result<(), String> {
+    match actual {
+        None if status.is_empty() => Err(format!("expected {:?}, got an error", expected)),
+        Some(actual) if status.is_empty() && actual != expected => Err(format!("expected {:?}, got {:?}", expected, actual)),
+        Some(actual) if !status.is_empty() => Err(format!("expected {:?}, got {:?}", status, actual)),
+        _ => Ok(()),
+    }
+}
+
+
+#[test]
+fn idna_test_v2() {
+    let config: Config = Config {
+        use_std3_ascii_rules: true,
+        transitional_processing: false,
+        check_hyphens: true,
+        check_bidi: true,
+        check_joiners: true,
+        verify_dns_length: true,
+    };
+    let transitional: Config = Config {
+        transitional_processing: true,
+        ..config
+    };
+    let parse_status = |status: &str| -> Vec<String> {
+        status
+            .trim_matches(|c: char| c == '[' || c == ']')
+            .split(", ")
+            .filter(|s: &&str| !s.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    let mut report: Report = Report::new();
+    for (i, line) in include_str!("wpt/IdnaTestV2.txt").lines().enumerate() {
+        let line: &str = line.split('#').next().unwrap();
+        if line.trim().is_empty() {
+            continue;
+        }
+        let columns: Vec<String> = line
+            .split(';')
+            .map(|column: &str| unescape(column.trim()))
+            .collect();
+        let source: &str = &columns[0];
+        let to_unicode: &str = if columns[1].is_empty() {
+            source
+        } else {
+            &columns[1]
+        };
+        let to_unicode_status: Vec<String> = parse_status(&columns[2]);
+        let to_ascii_n: &str = if columns[3].is_empty() {
+            to_unicode
+        } else {
+            &columns[3]
+        };
+        let to_ascii_n_status: Vec<String> = if columns[4].is_empty() {
+            to_unicode_status.clone()
+        } else {
+            parse_status(&columns[4])
+        };
+        let to_ascii_t: &str = if columns[5].is_empty() {
+            to_ascii_n
+        } else {
+            &columns[5]
+        };
+        let to_ascii_t_status: Vec<String> = if columns[6].is_empty() {
+            to_ascii_n_status.clone()
+        } else {
+            parse_status(&columns[6])
+        };
+
+        let (unicode, errors) = config.to_unicode(source);
+        let unicode: Option<String> = if errors { None } else { Some(unicode) };
+        report.check(
+            format!("IdnaTestV2 line {} toUnicode", i + 1),
+            check_idna(to_unicode, &to_unicode_status, unicode),
+        );
+        report.check(
+            format!("IdnaTestV2 line {} toAsciiN", i + 1),
+            check_idna(to_ascii_n, &to_ascii_n_status, config.to_ascii(source)),
+        );
+        report.check(
+            format!("IdnaTestV2 line {} toAsciiT", i + 1),
+            check_idna(
+                to_ascii_t,
+                &to_ascii_t_status,
+                transitional.to_ascii(source),
+            ),
+        );
+    }
+    report.assert();
+}
+
+
+fn bytes(value: &Value) -> Option<Vec<u8>> {
+    value.as_array().map(|bytes: &Vec<Value>| {
+        bytes
+            .iter()
+            .map(|byte: &Value| byte.as_u64().unwrap() as u8)
+            .collect()
+    })
+}
+
+
+#[test]
+fn data_urls() {
+    let tests: Vec<Value> = serde_json::from_str(include_str!("wpt/data-urls.json")).unwrap();
+    let mut report: Report = Report::new();
+    for test in tests.iter().filter_map(Value::as_array) {
+        let input: &str = test[0].as_str().unwrap();
+        let expected: Option<(&str, Vec<u8>)> = test[1]
+            .as_str()
+            .map(|mime_type: &str| (mime_type, bytes(&test[2]).unwrap()));
+        let actual: Option<(String, Vec<u8>)> = URL::parse(input)
+            .ok()
+            .and_then(|url: URL| DataURL::process(&url))
+            .map(|data_url: DataURL| (data_url.mime_type().to_string(), data_url.into_body()));
+        let result: Result<(), String> = match (expected, actual) {
+            (None, None) => Ok(()),
+            (Some((mime_type, body)), Some(actual)) if mime_type == actual.0 && body == actual.1 => Ok(()),
+            (expected, actual) => Err(format!("expected {:?}, got {:?}", expected, actual)),
+        };
+        report.check(format!("data URL <{}>", input.escape_default()), result);
+    }
+    report.assert();
+}
+
+
+#[test]
+fn base64() {
+    let tests: Vec<Value> = serde_json::from_str(include_str!("wpt/base64.json")).unwrap();
+    let mut report: Report = Report::new();
+    for test in tests.iter().filter_map(Value::as_array) {
+        let input: &str = test[0].as_str().unwrap();
+        let expected: Option<Vec<u8>> = bytes(&test[1]);
+        let actual: Option<Vec<u8>> = forgiving_base64_decode(input);
+        let result: Result<(), String> = if actual == expected {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}, got {:?}", expected, actual))
+        };
+        report.check(format!("base64 <{}>", input.escape_default()), result);
+    }
+    report.assert();
+}
+
+
+#[test]
+fn mime_types() {
+    let mut tests: Vec<Value> = serde_json::from_str(include_str!("wpt/mime-types.json")).unwrap();
+    tests.extend(serde_json::from_str::<Vec<Value>>(include_str!("wpt/generated-mime-types.json")).unwrap());
+    let mut report: Report = Report::new();
+    for test in tests.iter().filter_map(Value::as_object) {
+        let input: &str = test["input"].as_str().unwrap();
+        let expected: Option<&str> = test["output"].as_str();
+        let actual: Option<String> = MIMEType::parse(input).map(|mime_type: MIMEType| mime_type.to_string());
+        let result: Result<(), String> = if actual.as_deref() == expected {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}, got {:?}", expected, actual))
+        };
+        report.check(format!("MIME type <{}>", input.escape_default()), result);
+    }
+    report.assert();
+}
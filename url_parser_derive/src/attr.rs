@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Ident, LitStr, Path, Result, Token, WherePredicate};
+
+
+// #[query(...)] on the struct
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub bound: Option<Vec<WherePredicate>>,
+    pub krate: Option<Path>,
+}
+
+impl ContainerAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut container_attrs: Self = Default::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr: &&Attribute| attr.path().is_ident("query"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    let predicates: Punctuated<WherePredicate, Token![,]> = lit.parse_with(Punctuated::parse_terminated)?;
+                    container_attrs.bound = Some(predicates.into_iter().collect());
+                    Ok(())
+                } else if meta.path.is_ident("crate") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    container_attrs.krate = Some(lit.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported query attribute"))
+                }
+            })?;
+        }
+        Ok(container_attrs)
+    }
+}
+
+
+// #[query(...)] on a field
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub flatten: bool,
+    // The value is replaced in `to_redacted_query_params`.
+    pub secret: bool,
+}
+
+impl FieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut field_attrs: Self = Default::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr: &&Attribute| attr.path().is_ident("query"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("flatten") {
+                    field_attrs.flatten = true;
+                    Ok(())
+                } else if meta.path.is_ident("secret") {
+                    field_attrs.secret = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported query attribute"))
+                }
+            })?;
+        }
+        Ok(field_attrs)
+    }
+}
+
+
+// #[path_params("/users/{id}", crate = "...")] on the struct
+pub struct PathParamsAttrs {
+    pub template: LitStr,
+    pub krate: Option<Path>,
+}
+
+impl PathParamsAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut template: Option<LitStr> = None;
+        let mut krate: Option<Path> = None;
+        for attr in attrs
+            .iter()
+            .filter(|attr: &&Attribute| attr.path().is_ident("path_params"))
+        {
+            attr.parse_args_with(|input: ParseStream| {
+                template = Some(input.parse()?);
+                while !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                    if input.is_empty() {
+                        break;
+                    }
+                    let key: Ident = input.call(Ident::parse_any)?;
+                    if key != "crate" {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "unsupported path_params attribute",
+                        ));
+                    }
+                    input.parse::<Token![=]>()?;
+                    let lit: LitStr = input.parse()?;
+                    krate = Some(lit.parse()?);
+                }
+                Ok(())
+            })?;
+        }
+        match template {
+            Some(template) => Ok(Self { template, krate }),
+            None => {
+                Err(syn::Error::new(
+                    Span::call_site(),
+                    "missing #[path_params(\"...\")] attribute",
+                ))
+            }
+        }
+    }
+}
+
+
+// #[url(...)] on the struct
+#[derive(Default)]
+pub struct UrlRequestAttrs {
+    pub base: Option<LitStr>,
+    pub path: Option<LitStr>,
+    pub krate: Option<Path>,
+}
+
+impl UrlRequestAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut container_attrs: Self = Default::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr: &&Attribute| attr.path().is_ident("url"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("base") {
+                    container_attrs.base = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("path") {
+                    container_attrs.path = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("crate") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    container_attrs.krate = Some(lit.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported url attribute"))
+                }
+            })?;
+        }
+        Ok(container_attrs)
+    }
+}
+
+
+// The part of the URL a field fills.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UrlRole {
+    Path,
+    Query,
+    Fragment,
+}
+
+
+// #[url(...)] on a field
+#[derive(Default)]
+pub struct UrlFieldAttrs {
+    pub role: Option<UrlRole>,
+}
+
+impl UrlFieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut field_attrs: Self = Default::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr: &&Attribute| attr.path().is_ident("url"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let role: UrlRole = if meta.path.is_ident("path") {
+                    UrlRole::Path
+                } else if meta.path.is_ident("query") {
+                    UrlRole::Query
+                } else if meta.path.is_ident("fragment") {
+                    UrlRole::Fragment
+                } else {
+                    return Err(meta.error("unsupported url attribute"));
+                };
+                if field_attrs.role.is_some() {
+                    return Err(meta.error("a field can only fill one part of the URL"));
+                }
+                field_attrs.role = Some(role);
+                Ok(())
+            })?;
+        }
+        Ok(field_attrs)
+    }
+}
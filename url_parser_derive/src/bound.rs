@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::visit::{self, Visit};
+use syn::{parse_quote, Generics, Ident, Type, TypePath, WherePredicate};
+
+
+struct TypeParamVisitor<'a> {
+    param: &'a Ident,
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for TypeParamVisitor<'_> {
+    fn visit_type_path(&mut self, tpath: &'ast TypePath) {
+        if tpath.qself.is_none() && tpath.path.leading_colon.is_none() && tpath.path.segments[0].ident == *self.param {
+            self.found = true;
+        }
+        visit::visit_type_path(self, tpath);
+    }
+}
+
+
+fn uses_type_param(ty: &Type, param: &Ident) -> bool {
+    let mut visitor: TypeParamVisitor = TypeParamVisitor {
+        param,
+        found: false,
+    };
+    visitor.visit_type(ty);
+    visitor.found
+}
+
+
+// Collects `T: #bound` for each type parameter of `generics` that appears in `ty`.
+pub fn infer_bounds(generics: &Generics, ty: &Type, bound: &TokenStream2, bounds: &mut Vec<WherePredicate>) {
+    for param in generics.type_params() {
+        let ident: &Ident = &param.ident;
+        if uses_type_param(ty, ident) {
+            let predicate: WherePredicate = parse_quote!(#ident: #bound);
+            let predicate_str: String = quote!(#predicate).to_string();
+            if !bounds
+                .iter()
+                .any(|p: &WherePredicate| quote!(#p).to_string() == predicate_str)
+            {
+                bounds.push(predicate);
+            }
+        }
+    }
+}
+
+
+pub fn with_bounds(generics: &Generics, bounds: Vec<WherePredicate>) -> Generics {
+    let mut generics: Generics = generics.clone();
+    if !bounds.is_empty() {
+        generics.make_where_clause().predicates.extend(bounds);
+    }
+    generics
+}
@@ -1,13 +1,24 @@
 // SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
 // SPDX-License-Identifier: Apache-2.0
 
+mod attr;
+mod bound;
+mod path_params;
+
+use attr::{ContainerAttrs, FieldAttrs, PathParamsAttrs, UrlFieldAttrs, UrlRequestAttrs, UrlRole};
+use bound::{infer_bounds, with_bounds};
+use path_params::Segment;
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{
     parse_macro_input,
     parse_quote,
+    Field,
+    Fields,
     GenericArgument,
+    Generics,
     Ident,
     Item,
     Path,
@@ -19,32 +30,61 @@ use syn::{
     TypeReference,
     TypeSlice,
     TypeTuple,
+    WherePredicate,
 };
 
 
-#[proc_macro_derive(QueryParams)]
+#[proc_macro_derive(QueryParams, attributes(query))]
 pub fn derive_query_params(input: TokenStream) -> TokenStream {
     if let Item::Struct(ast) = parse_macro_input!(input) {
-        let ident: Ident = ast.ident;
-        let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-        let mut query_generator: TokenStream2 = TokenStream2::new();
-        for field in ast.fields {
-            let field_ident: Ident = field.ident.unwrap();
-            match field.ty {
-                Type::Array(tarray) => query_generator = parse_type_array(&field_ident, tarray, query_generator),
-                Type::Path(tpath) => query_generator = parse_type_path(&field_ident, tpath, query_generator),
-                Type::Ptr(tptr) => query_generator = parse_type_ptr(&field_ident, tptr, query_generator),
-                Type::Reference(tref) => query_generator = parse_type_reference(&field_ident, tref, query_generator),
-                Type::Tuple(ttuple) => query_generator = parse_type_tuple(&field_ident, ttuple, query_generator),
-                _ => query_generator = unsupported_field_type_error(&field_ident, query_generator),
-            }
+        let container_attrs: ContainerAttrs = match ContainerAttrs::from_attrs(&ast.attrs) {
+            Ok(container_attrs) => container_attrs,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        if let Fields::Unnamed(fields) = &ast.fields {
+            let expanded: TokenStream2 = quote_spanned! {fields.span()=>
+                ::core::compile_error!("This derive macro can only be used for struct with named fields.");
+            };
+            return expanded.into();
         }
+        let krate: Path = container_attrs
+            .krate
+            .unwrap_or_else(|| parse_quote!(::url_parser));
+        let ident: Ident = ast.ident;
+        let mut bounds: Vec<WherePredicate> = Vec::new();
+        let redacted_query_generator: TokenStream2 = match query_generator(
+            &krate,
+            &ast.generics,
+            ast.fields.clone(),
+            true,
+            &mut Vec::new(),
+        ) {
+            Ok(query_generator) => query_generator,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let query_generator: TokenStream2 = match query_generator(&krate, &ast.generics, ast.fields, false, &mut bounds) {
+            Ok(query_generator) => query_generator,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let generics: Generics = with_bounds(&ast.generics, container_attrs.bound.unwrap_or(bounds));
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         let expanded: TokenStream2 = quote! {
-            impl #impl_generics QueryParams for #ident #ty_generics #where_clause {
-                fn to_query_params(&self) -> String {
-                    let mut query: String = "?".to_string();
+            impl #impl_generics #krate::QueryParams for #ident #ty_generics #where_clause {
+                fn to_query_pairs(
+                    &self,
+                ) -> ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> {
+                    let mut query: ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> =
+                        ::std::vec::Vec::new();
                     #query_generator
-                    query.pop();
+                    query
+                }
+
+                fn to_redacted_query_pairs(
+                    &self,
+                ) -> ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> {
+                    let mut query: ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> =
+                        ::std::vec::Vec::new();
+                    #redacted_query_generator
                     query
                 }
             }
@@ -52,7 +92,353 @@ pub fn derive_query_params(input: TokenStream) -> TokenStream {
         expanded.into()
     } else {
         let expanded: TokenStream2 = quote! {
-            compile_error!("This derive macro can only be used for struct.");
+            ::core::compile_error!("This derive macro can only be used for struct.");
+        };
+        expanded.into()
+    }
+}
+
+
+#[proc_macro_derive(UrlRequest, attributes(url, query))]
+pub fn derive_url_request(input: TokenStream) -> TokenStream {
+    if let Item::Struct(ast) = parse_macro_input!(input) {
+        match url_request(ast) {
+            Ok(expanded) => expanded.into(),
+            Err(err) => err.to_compile_error().into(),
+        }
+    } else {
+        let expanded: TokenStream2 = quote! {
+            ::core::compile_error!("This derive macro can only be used for struct.");
+        };
+        expanded.into()
+    }
+}
+
+
+// `to_url()` parses the base followed by the path, then sets the query and fragment. Parsing fails
+// only for a base that `check_base` lets through, e.g. with an invalid IPv4 or IDNA host. The query
+// is encoded like `QueryParams::to_query_params`: keys and list elements one by one, so a value
+// cannot add a pair or an element of its own.
+fn url_request(ast: syn::ItemStruct) -> syn::Result<TokenStream2> {
+    let attrs: UrlRequestAttrs = UrlRequestAttrs::from_attrs(&ast.attrs)?;
+    let Fields::Named(fields) = ast.fields else {
+        return Ok(quote! {
+            ::core::compile_error!("This derive macro can only be used for struct with named fields.");
+        });
+    };
+    let krate: Path = attrs.krate.unwrap_or_else(|| parse_quote!(::url_parser));
+    let base: syn::LitStr = attrs.base.ok_or_else(|| {
+        syn::Error::new(
+            Span::call_site(),
+            "missing #[url(base = \"...\")] attribute",
+        )
+    })?;
+    if base.value().contains(['?', '#']) {
+        return Err(syn::Error::new(
+            base.span(),
+            "the base URL cannot have a query or fragment",
+        ));
+    }
+    check_base(&base)?;
+    let template: syn::LitStr = attrs
+        .path
+        .unwrap_or_else(|| syn::LitStr::new("", Span::call_site()));
+    if template.value().contains(['?', '#']) {
+        return Err(syn::Error::new(
+            template.span(),
+            "the path cannot have a query or fragment",
+        ));
+    }
+    let segments: Vec<Segment> = path_params::parse_template(&template)?;
+    let mut bounds: Vec<WherePredicate> = Vec::new();
+    let mut path_fields: Vec<&Ident> = Vec::new();
+    let mut query_fields: Vec<Field> = Vec::new();
+    let mut fragment_generator: TokenStream2 = TokenStream2::new();
+    for field in &fields.named {
+        let field_ident: &Ident = field.ident.as_ref().unwrap();
+        let Some(role) = UrlFieldAttrs::from_attrs(&field.attrs)?.role else {
+            return Err(syn::Error::new(
+                field_ident.span(),
+                format!(
+                    "field `{}` needs #[url(path)], #[url(query)] or #[url(fragment)]",
+                    field_ident
+                ),
+            ));
+        };
+        match role {
+            UrlRole::Path => {
+                infer_bounds(
+                    &ast.generics,
+                    &field.ty,
+                    &quote!(::core::fmt::Display),
+                    &mut bounds,
+                );
+                path_fields.push(field_ident);
+            }
+            UrlRole::Query => query_fields.push(field.clone()),
+            UrlRole::Fragment => {
+                if !fragment_generator.is_empty() {
+                    return Err(syn::Error::new(
+                        field_ident.span(),
+                        "only one field can be the fragment",
+                    ));
+                }
+                infer_bounds(
+                    &ast.generics,
+                    &field.ty,
+                    &quote!(::core::fmt::Display),
+                    &mut bounds,
+                );
+                fragment_generator = match &field.ty {
+                    Type::Path(tpath) if is_option(tpath) => {
+                        quote! {
+                            // url: URL
+                            if let ::core::option::Option::Some(val) = &self.#field_ident {
+                                url.set_hash(&val.to_string());
+                            }
+                        }
+                    }
+                    _ => {
+                        quote! {
+                            // url: URL
+                            let val: ::std::string::String = self.#field_ident.to_string();
+                            if !val.is_empty() {
+                                url.set_hash(&val);
+                            }
+                        }
+                    }
+                };
+            }
+        }
+    }
+    for segment in &segments {
+        if let Segment::Placeholder(name) = segment {
+            if !path_fields
+                .iter()
+                .any(|field_ident: &&Ident| *field_ident == name)
+                && fields.named.iter().any(|field: &Field| {
+                    field
+                        .ident
+                        .as_ref()
+                        .is_some_and(|ident: &Ident| ident == name)
+                })
+            {
+                return Err(syn::Error::new(
+                    template.span(),
+                    format!("field `{}` is not marked #[url(path)]", name),
+                ));
+            }
+        }
+    }
+    path_params::check_fields(&template, &segments, &path_fields)?;
+    let base: String = if template.value().starts_with('/') {
+        base.value().trim_end_matches('/').to_string()
+    } else {
+        base.value()
+    };
+    let path_generator: TokenStream2 = path_params::to_path_generator(&krate, &segments);
+    let query_generator: TokenStream2 = query_generator(&krate, &ast.generics, query_fields, false, &mut bounds)?;
+    let ident: &Ident = &ast.ident;
+    let generics: Generics = with_bounds(&ast.generics, bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            pub fn to_url(&self) -> ::core::result::Result<#krate::url::URL, #krate::url::ParseError> {
+                let mut path: ::std::string::String = ::std::string::String::from(#base);
+                #path_generator
+                let mut url: #krate::url::URL = #krate::url::URL::parse(&path)?;
+                let mut query: ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> =
+                    ::std::vec::Vec::new();
+                #query_generator
+                if !query.is_empty() {
+                    let encode = |input: &::std::string::String| -> ::std::string::String {
+                        #krate::percent_encoding::utf8_percent_encode(input, &#krate::percent_encoding::COMPONENT)
+                    };
+                    let query: ::std::vec::Vec<::std::string::String> = query
+                        .iter()
+                        .map(|(key, values)| {
+                            let values: ::std::vec::Vec<::std::string::String> = values.iter().map(encode).collect();
+                            ::std::format!("{}={}", encode(key), values.join(","))
+                        })
+                        .collect();
+                    url.set_search(&query.join("&"));
+                }
+                #fragment_generator
+                ::core::result::Result::Ok(url)
+            }
+        }
+    })
+}
+
+
+// Rejects most bases that `URL::parse` would, so that mistakes show up when the derive expands. The
+// derive cannot depend on `url_parser`, so this checks the scheme and the authority by hand, and
+// leaves the full host parsing (IPv4 numbers, IDNA) to `to_url()`.
+fn check_base(base: &syn::LitStr) -> syn::Result<()> {
+    let value: String = base.value();
+    let err = |message: &str| -> syn::Result<()> {
+        Err(syn::Error::new(
+            base.span(),
+            format!("invalid #[url(base)]: {}", message),
+        ))
+    };
+    let Some((scheme, rest)) = value.split_once(':') else {
+        return err("missing scheme");
+    };
+    let is_valid_scheme: bool = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !is_valid_scheme {
+        return err("invalid scheme");
+    }
+    if value.chars().any(|c: char| c.is_control() || c == ' ') {
+        return err("whitespace or control character");
+    }
+    let scheme: String = scheme.to_ascii_lowercase();
+    let is_special: bool = matches!(
+        scheme.as_str(),
+        "ftp" | "file" | "http" | "https" | "ws" | "wss"
+    );
+    let authority: &str = if is_special {
+        rest.trim_start_matches(['/', '\\'])
+    } else if let Some(authority) = rest.strip_prefix("//") {
+        authority
+    } else {
+        return Ok(());
+    };
+    let authority: &str = &authority[..authority.find(['/', '\\']).unwrap_or(authority.len())];
+    let host_port: &str = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host_port): (&str, &str)| host_port);
+    let (host, port): (&str, &str) = if host_port.starts_with('[') {
+        match host_port.find(']') {
+            Some(end) => host_port.split_at(end + 1),
+            None => return err("unclosed IPv6 address"),
+        }
+    } else {
+        host_port.split_at(host_port.find(':').unwrap_or(host_port.len()))
+    };
+    if let Some(port) = port.strip_prefix(':') {
+        if !port.is_empty() && (!port.bytes().all(|byte: u8| byte.is_ascii_digit()) || port.parse::<u16>().is_err()) {
+            return err("invalid port");
+        }
+    } else if !port.is_empty() {
+        return err("invalid host");
+    }
+    if host.is_empty() && is_special && scheme != "file" {
+        return err("missing host");
+    }
+    if !host.starts_with('[') && host.contains(['<', '>', '[', ']', '^', '|']) {
+        return err("invalid host");
+    }
+    Ok(())
+}
+
+
+// Pushes `(key, values)` to `query: Vec<(String, Vec<String>)>` for each field, collecting the
+// bounds its type needs. With `redacted`, the values of `#[query(secret)]` fields are replaced.
+fn query_generator(
+    krate: &Path,
+    generics: &Generics,
+    fields: impl IntoIterator<Item = Field>,
+    redacted: bool,
+    bounds: &mut Vec<WherePredicate>,
+) -> syn::Result<TokenStream2> {
+    let mut query_generator: TokenStream2 = TokenStream2::new();
+    for field in fields {
+        let field_attrs: FieldAttrs = FieldAttrs::from_attrs(&field.attrs)?;
+        let field_ident: Ident = field.ident.unwrap();
+        if field_attrs.flatten {
+            if field_attrs.secret {
+                return Err(syn::Error::new(
+                    field_ident.span(),
+                    "a flattened field cannot be secret",
+                ));
+            }
+            infer_bounds(generics, &field.ty, &quote!(#krate::QueryParams), bounds);
+            query_generator = parse_flatten(krate, &field_ident, redacted, query_generator);
+            continue;
+        }
+        infer_bounds(generics, &field.ty, &quote!(::core::fmt::Display), bounds);
+        let field_generator: TokenStream2 = match field.ty {
+            Type::Array(tarray) => parse_type_array(&field_ident, tarray, TokenStream2::new()),
+            Type::Path(tpath) => parse_type_path(&field_ident, tpath, TokenStream2::new()),
+            Type::Ptr(tptr) => parse_type_ptr(&field_ident, tptr, TokenStream2::new()),
+            Type::Reference(tref) => parse_type_reference(&field_ident, tref, TokenStream2::new()),
+            Type::Tuple(ttuple) => parse_type_tuple(&field_ident, ttuple, TokenStream2::new()),
+            _ => unsupported_field_type_error(&field_ident, TokenStream2::new()),
+        };
+        query_generator = if redacted && field_attrs.secret {
+            parse_secret(&field_ident, field_generator, query_generator)
+        } else {
+            quote!(#query_generator #field_generator)
+        };
+    }
+    Ok(query_generator)
+}
+
+
+// The template goes in `#[path_params("/users/{id}")]` rather than `#[path("...")]`: `path` is a
+// built-in attribute (`#[path = "file"]` on modules), so a derive helper with that name is
+// ambiguous wherever it is written.
+#[proc_macro_derive(PathParams, attributes(path_params))]
+pub fn derive_path_params(input: TokenStream) -> TokenStream {
+    if let Item::Struct(ast) = parse_macro_input!(input) {
+        let attrs: PathParamsAttrs = match PathParamsAttrs::from_attrs(&ast.attrs) {
+            Ok(attrs) => attrs,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let Fields::Named(fields) = &ast.fields else {
+            let expanded: TokenStream2 = quote! {
+                ::core::compile_error!("This derive macro can only be used for struct with named fields.");
+            };
+            return expanded.into();
+        };
+        let krate: Path = attrs.krate.unwrap_or_else(|| parse_quote!(::url_parser));
+        let ident: &Ident = &ast.ident;
+        let fields: Vec<(&Ident, &Type)> = fields
+            .named
+            .iter()
+            .map(|field: &syn::Field| (field.ident.as_ref().unwrap(), &field.ty))
+            .collect();
+        let field_idents: Vec<&Ident> = fields.iter().map(|(field_ident, _)| *field_ident).collect();
+        let segments: Vec<Segment> = match path_params::parse_template(&attrs.template).and_then(|segments: Vec<Segment>| {
+            path_params::check_fields(&attrs.template, &segments, &field_idents).map(|_| segments)
+        }) {
+            Ok(segments) => segments,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let mut bounds: Vec<WherePredicate> = Vec::new();
+        for (_, ty) in &fields {
+            infer_bounds(
+                &ast.generics,
+                ty,
+                &quote!(::core::fmt::Display + ::core::str::FromStr),
+                &mut bounds,
+            );
+        }
+        let path_generator: TokenStream2 = path_params::to_path_generator(&krate, &segments);
+        let path_parser: TokenStream2 = path_params::from_path_generator(&krate, &segments, &fields);
+        let generics: Generics = with_bounds(&ast.generics, bounds);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let expanded: TokenStream2 = quote! {
+            impl #impl_generics #krate::PathParams for #ident #ty_generics #where_clause {
+                fn to_path(&self) -> ::std::string::String {
+                    let mut path: ::std::string::String = ::std::string::String::new();
+                    #path_generator
+                    path
+                }
+
+                fn from_path(path: &::core::primitive::str) -> ::core::option::Option<Self> {
+                    #path_parser
+                }
+            }
+        };
+        expanded.into()
+    } else {
+        let expanded: TokenStream2 = quote! {
+            ::core::compile_error!("This derive macro can only be used for struct.");
         };
         expanded.into()
     }
@@ -156,13 +542,16 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                         } else {
                             quote! {
                                 #query_generator
-                                // query: String
+                                // query: Vec<(String, Vec<String>)>
                                 unsafe {
                                     if !self.#field_ident.is_null() {
-                                        if let Some(val) = *self.#field_ident {
-                                            let val: String = val.to_string();
+                                        if let ::core::option::Option::Some(val) = *self.#field_ident {
+                                            let val: ::std::string::String = val.to_string();
                                             if !val.is_empty() {
-                                                query += &format!("{}={}&", stringify!(#field_ident), val);
+                                                query.push((
+                                                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                                                    ::std::vec![val],
+                                                ));
                                             }
                                         }
                                     }
@@ -177,14 +566,17 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                             } else {
                                 quote! {
                                     #query_generator
-                                    // query: String
+                                    // query: Vec<(String, Vec<String>)>
                                     if !self.#field_ident.is_null() {
                                         unsafe {
-                                            if let Some(val) = *self.#field_ident {
+                                            if let ::core::option::Option::Some(val) = *self.#field_ident {
                                                 if !val.is_null() {
-                                                    let val: String = (*val).to_string();
+                                                    let val: ::std::string::String = (*val).to_string();
                                                     if !val.is_empty() {
-                                                        query += &format!("{}={}&", stringify!(#field_ident), val);
+                                                        query.push((
+                                                            ::std::string::String::from(::core::stringify!(#field_ident)),
+                                                            ::std::vec![val],
+                                                        ));
                                                     }
                                                 }
                                             }
@@ -203,12 +595,15 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                             } else {
                                 quote! {
                                     #query_generator
-                                    // query: String
+                                    // query: Vec<(String, Vec<String>)>
                                     if !self.#field_ident.is_null() {
-                                        if let Some(val) = *self.#field_ident {
-                                            let val: String = val.to_string();
+                                        if let ::core::option::Option::Some(val) = *self.#field_ident {
+                                            let val: ::std::string::String = val.to_string();
                                             if !val.is_empty() {
-                                                query += &format!("{}={}&", stringify!(#field_ident), val);
+                                                query.push((
+                                                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                                                    ::std::vec![val],
+                                                ));
                                             }
                                         }
                                     }
@@ -225,16 +620,16 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                     Type::Path(tpath) => parse_ptr_slice(field_ident, tpath.clone(), query_generator),
                     Type::Ptr(tptr) => {
                         if let Type::Path(tpath) = *tptr.elem {
-                            parse_ptr_slice_ptr(&field_ident, tpath, query_generator)
+                            parse_ptr_slice_ptr(field_ident, tpath, query_generator)
                         } else {
-                            unsupported_field_type_error(&field_ident, query_generator)
+                            unsupported_field_type_error(field_ident, query_generator)
                         }
                     }
                     Type::Reference(tref) => {
                         if let Type::Path(tpath) = *tref.elem {
-                            parse_ptr_slice(&field_ident, tpath, query_generator)
+                            parse_ptr_slice(field_ident, tpath, query_generator)
                         } else {
-                            unsupported_field_type_error(&field_ident, query_generator)
+                            unsupported_field_type_error(field_ident, query_generator)
                         }
                     }
                     _ => unsupported_field_type_error(field_ident, query_generator),
@@ -242,12 +637,15 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
             } else {
                 quote! {
                     #query_generator
-                    // query: String
+                    // query: Vec<(String, Vec<String>)>
                     if !self.#field_ident.is_null() {
                         unsafe {
-                            let val: String = (*self.#field_ident).to_string();
+                            let val: ::std::string::String = (*self.#field_ident).to_string();
                             if !val.is_empty() {
-                                query += &format!("{}={}&", stringify!(#field_ident), *self.#field_ident);
+                                query.push((
+                                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                                    ::std::vec![val],
+                                ));
                             }
                         }
                     }
@@ -268,7 +666,7 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
         Type::Tuple(ttuple) => {
             if ttuple.elems.iter().all(|ty: &Type| -> bool {
                 match ty {
-                    Type::Path(tpath) => !(is_option(&tpath) || is_vec(&tpath)),
+                    Type::Path(tpath) => !(is_option(tpath) || is_vec(tpath)),
                     Type::Ptr(tptr) => {
                         if let Type::Path(tpath) = *tptr.elem.clone() {
                             !(is_option(&tpath) || is_vec(&tpath))
@@ -287,7 +685,7 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                 }
             }) {
                 let mut tuple_apd_query_generator: TokenStream2 = quote! {
-                    let mut values: String = Default::default();
+                    let mut values: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
                 };
                 for (i, elm) in ttuple.elems.iter().enumerate() {
                     let idx: TokenStream2 = i.to_string().parse().unwrap();
@@ -297,7 +695,7 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                             unsafe {
                                 let value = (*self.#field_ident).#idx;
                                 if !value.is_null() {
-                                    values += &format!("{}," *value);
+                                    values.push((*value).to_string());
                                 }
                             }
                         };
@@ -305,19 +703,21 @@ fn parse_type_ptr(field_ident: &Ident, tptr: TypePtr, query_generator: TokenStre
                         tuple_apd_query_generator = quote! {
                             #tuple_apd_query_generator
                             unsafe {
-                                values += &format!("{},", (*self.#field_ident).#idx);
+                                values.push((*self.#field_ident).#idx.to_string());
                             }
                         };
                     }
                 }
                 quote! {
                     #query_generator
-                    // query: String
+                    // query: Vec<(String, Vec<String>)>
                     if !self.#field_ident.is_null() {
                         #tuple_apd_query_generator
-                        values.pop();
-                        if values.len() != 0 {
-                            query += &format!("{}={}&", stringify!(#field_ident), values);
+                        if !values.is_empty() {
+                            query.push((
+                                ::std::string::String::from(::core::stringify!(#field_ident)),
+                                values,
+                            ));
                         }
                     }
                 }
@@ -336,16 +736,17 @@ fn parse_ptr_slice(field_ident: &Ident, tpath: TypePath, query_generator: TokenS
     } else {
         quote! {
             #query_generator
-            // query: String
+            // query: Vec<(String, Vec<String>)>
             if !self.#field_ident.is_null() {
-                let mut val: String;
+                let values: ::std::vec::Vec<::std::string::String>;
                 unsafe {
-                    val = (*self.#field_ident).iter()
-                        .fold(String::new(), |acc, v| format!("{}{},", acc, v));
+                    values = (*self.#field_ident).iter().map(|v| v.to_string()).collect();
                 }
-                val.pop();
-                if !val.is_empty() {
-                    query += &format!("{}={}&", stringify!(#field_ident), val);
+                if !values.is_empty() {
+                    query.push((
+                        ::std::string::String::from(::core::stringify!(#field_ident)),
+                        values,
+                    ));
                 }
             }
         }
@@ -359,21 +760,23 @@ fn parse_slice_ptr(field_ident: &Ident, tpath: TypePath, query_generator: TokenS
     } else {
         quote! {
             #query_generator
-            // query: String
-            let mut values: String = Default::default();
+            // query: Vec<(String, Vec<String>)>
+            let mut values: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
             for v in self.#field_ident.clone() {
                 if !v.is_null() {
                     unsafe {
-                        let val: String = v.as_ref().unwrap().to_string();
+                        let val: ::std::string::String = v.as_ref().unwrap().to_string();
                         if !val.is_empty() {
-                            values += &format!("{},", val);
+                            values.push(val);
                         }
                     }
                 }
             }
-            values.pop();
             if !values.is_empty() {
-                query += &format!("{}={}&", stringify!(#field_ident), values);
+                query.push((
+                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                    values,
+                ));
             }
         }
     }
@@ -386,22 +789,24 @@ fn parse_ptr_slice_ptr(field_ident: &Ident, tpath: TypePath, query_generator: To
     } else {
         quote! {
             #query_generator
-            // query: String
+            // query: Vec<(String, Vec<String>)>
             if !self.#field_ident.is_null() {
-                let mut values: String = Default::default();
+                let mut values: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
                 unsafe {
                     for v in *self.#field_ident {
                         if !v.is_null() {
-                            let val: String = v.as_ref().unwrap().to_string();
+                            let val: ::std::string::String = v.as_ref().unwrap().to_string();
                             if !val.is_empty() {
-                                values += &format!("{},", val);
+                                values.push(val);
                             }
                         }
                     }
                 }
-                values.pop();
                 if !values.is_empty() {
-                    query += &format!("{}={}&", stringify!(#field_ident), values);
+                    query.push((
+                        ::std::string::String::from(::core::stringify!(#field_ident)),
+                        values,
+                    ));
                 }
             }
         }
@@ -447,7 +852,7 @@ fn parse_type_slice(field_ident: &Ident, tslice: TypeSlice, query_generator: Tok
 fn parse_type_tuple(field_ident: &Ident, ttuple: TypeTuple, query_generator: TokenStream2) -> TokenStream2 {
     if ttuple.elems.iter().all(|ty: &Type| -> bool {
         match ty {
-            Type::Path(tpath) => !(is_option(&tpath) || is_vec(&tpath)),
+            Type::Path(tpath) => !(is_option(tpath) || is_vec(tpath)),
             Type::Ptr(tptr) => {
                 if let Type::Path(tpath) = *tptr.elem.clone() {
                     !(is_option(&tpath) || is_vec(&tpath))
@@ -466,7 +871,7 @@ fn parse_type_tuple(field_ident: &Ident, ttuple: TypeTuple, query_generator: Tok
         }
     }) {
         let mut tuple_apd_query_generator: TokenStream2 = quote! {
-            let mut values: String = Default::default();
+            let mut values: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
         };
         for (i, val) in ttuple.elems.iter().enumerate() {
             let idx: TokenStream2 = i.to_string().parse().unwrap();
@@ -475,25 +880,26 @@ fn parse_type_tuple(field_ident: &Ident, ttuple: TypeTuple, query_generator: Tok
                     #tuple_apd_query_generator
                     if !self.#field_ident.#idx.is_null() {
                         unsafe {
-                            values += &format!("{},", *self.#field_ident.#idx);
+                            values.push((*self.#field_ident.#idx).to_string());
                         }
                     }
                 };
             } else {
                 tuple_apd_query_generator = quote! {
                     #tuple_apd_query_generator
-                    // query: String
-                    values += &format!("{},", self.#field_ident.#idx);
+                    values.push(self.#field_ident.#idx.to_string());
                 };
             }
         }
         quote! {
             #query_generator
-            // query: String
+            // query: Vec<(String, Vec<String>)>
             #tuple_apd_query_generator
-            if values.len() != 0 {
-                values.pop();
-                query += &format!("{}={}&", stringify!(#field_ident), values);
+            if !values.is_empty() {
+                query.push((
+                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                    values,
+                ));
             }
         }
     } else {
@@ -508,12 +914,13 @@ fn parse_slice(field_ident: &Ident, tpath: TypePath, query_generator: TokenStrea
     } else {
         quote! {
             #query_generator
-            // query: String
-            let mut val: String = self.#field_ident.iter()
-                .fold(String::new(), |acc, v| format!("{}{},", acc, v));
-            val.pop();
-            if !val.is_empty() {
-                query += &format!("{}={}&", stringify!(#field_ident), val);
+            // query: Vec<(String, Vec<String>)>
+            let values: ::std::vec::Vec<::std::string::String> = self.#field_ident.iter().map(|v| v.to_string()).collect();
+            if !values.is_empty() {
+                query.push((
+                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                    values,
+                ));
             }
         }
     }
@@ -528,11 +935,14 @@ fn parse_option(field_ident: &Ident, tpath: TypePath, query_generator: TokenStre
             } else {
                 quote! {
                     #query_generator
-                    // query: String
-                    if let Some(val) = &self.#field_ident {
-                        let val: String = val.to_string();
+                    // query: Vec<(String, Vec<String>)>
+                    if let ::core::option::Option::Some(val) = &self.#field_ident {
+                        let val: ::std::string::String = val.to_string();
                         if !val.is_empty() {
-                            query += &format!("{}={}&", stringify!(#field_ident), val);
+                            query.push((
+                                ::std::string::String::from(::core::stringify!(#field_ident)),
+                                ::std::vec![val],
+                            ));
                         }
                     }
                 }
@@ -545,13 +955,16 @@ fn parse_option(field_ident: &Ident, tpath: TypePath, query_generator: TokenStre
                 } else {
                     quote! {
                         #query_generator
-                        // query: String
-                        if let Some(val) = &self.#field_ident {
+                        // query: Vec<(String, Vec<String>)>
+                        if let ::core::option::Option::Some(val) = &self.#field_ident {
                             if !val.is_null() {
                                 unsafe {
-                                    let val: String = (val.as_ref().unwrap()).to_string();
+                                    let val: ::std::string::String = (val.as_ref().unwrap()).to_string();
                                     if !val.is_empty() {
-                                        query += &format!("{}={}&", stringify!(#field_ident), val);
+                                        query.push((
+                                            ::std::string::String::from(::core::stringify!(#field_ident)),
+                                            ::std::vec![val],
+                                        ));
                                     }
                                 }
                             }
@@ -569,11 +982,14 @@ fn parse_option(field_ident: &Ident, tpath: TypePath, query_generator: TokenStre
                 } else {
                     quote! {
                         #query_generator
-                        // query: String
-                        if let Some(val) = &self.#field_ident {
-                            let val: String = val.to_string();
+                        // query: Vec<(String, Vec<String>)>
+                        if let ::core::option::Option::Some(val) = &self.#field_ident {
+                            let val: ::std::string::String = val.to_string();
                             if !val.is_empty() {
-                                query += &format!("{}={}&", stringify!(#field_ident), val);
+                                query.push((
+                                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                                    ::std::vec![val],
+                                ));
                             }
                         }
                     }
@@ -592,16 +1008,16 @@ fn parse_vector(field_ident: &Ident, tpath: TypePath, query_generator: TokenStre
         Type::Path(tpath) => parse_slice(field_ident, tpath.clone(), query_generator),
         Type::Ptr(tptr) => {
             if let Type::Path(tpath) = *tptr.elem.clone() {
-                parse_slice_ptr(&field_ident, tpath, query_generator)
+                parse_slice_ptr(field_ident, tpath, query_generator)
             } else {
-                unsupported_field_type_error(&field_ident, query_generator)
+                unsupported_field_type_error(field_ident, query_generator)
             }
         }
         Type::Reference(tref) => {
             if let Type::Path(tpath) = *tref.elem.clone() {
-                parse_slice(&field_ident, tpath, query_generator)
+                parse_slice(field_ident, tpath, query_generator)
             } else {
-                unsupported_field_type_error(&field_ident, query_generator)
+                unsupported_field_type_error(field_ident, query_generator)
             }
         }
         _ => unsupported_field_type_error(field_ident, query_generator),
@@ -609,13 +1025,53 @@ fn parse_vector(field_ident: &Ident, tpath: TypePath, query_generator: TokenStre
 }
 
 
+fn parse_flatten(krate: &Path, field_ident: &Ident, redacted: bool, query_generator: TokenStream2) -> TokenStream2 {
+    let to_query_pairs: TokenStream2 = if redacted {
+        quote!(#krate::QueryParams::to_redacted_query_pairs)
+    } else {
+        quote!(#krate::QueryParams::to_query_pairs)
+    };
+    quote! {
+        #query_generator
+        // query: Vec<(String, Vec<String>)>
+        query.extend(#to_query_pairs(&self.#field_ident));
+    }
+}
+
+
 fn parse_impl_display(field_ident: &Ident, query_generator: TokenStream2) -> TokenStream2 {
     quote! {
         #query_generator
-        // query: String
-        let val: String = self.#field_ident.to_string();
+        // query: Vec<(String, Vec<String>)>
+        let val: ::std::string::String = self.#field_ident.to_string();
         if !val.is_empty() {
-            query += &format!("{}={}&", stringify!(#field_ident), val);
+            query.push((
+                ::std::string::String::from(::core::stringify!(#field_ident)),
+                ::std::vec![val],
+            ));
+        }
+    }
+}
+
+
+// Runs the field's generator on a scratch `query` and appends the key with a placeholder value if
+// it wrote anything.
+fn parse_secret(field_ident: &Ident, field_generator: TokenStream2, query_generator: TokenStream2) -> TokenStream2 {
+    quote! {
+        #query_generator
+        // query: Vec<(String, Vec<String>)>
+        {
+            let redacted_query: &mut ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> =
+                &mut query;
+            let mut query: ::std::vec::Vec<(::std::string::String, ::std::vec::Vec<::std::string::String>)> =
+                ::std::vec::Vec::new();
+            #field_generator
+            if !query.is_empty() {
+                redacted_query.push((
+                    ::std::string::String::from(::core::stringify!(#field_ident)),
+                    ::std::vec![::std::string::String::from("REDACTED")],
+                ));
+            }
         }
     }
 }
@@ -625,6 +1081,6 @@ fn unsupported_field_type_error(field_ident: &Ident, query_generator: TokenStrea
     println!("The type of the field {} does not supported.", field_ident);
     quote! {
         #query_generator
-        compile_error!("Unsupported field type detected.");
+        ::core::compile_error!("Unsupported field type detected.");
     }
 }
@@ -0,0 +1,185 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Error, Ident, LitStr, Path, Result};
+
+
+// A piece of a path template: fixed text, or a `{name}` placeholder filled by the field `name`.
+pub enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic()
+}
+
+
+fn is_ident_continue(c: char) -> bool {
+    c == '_' || c.is_ascii_alphanumeric()
+}
+
+
+// Placeholders must be identifiers, used once, and separated by fixed text.
+pub fn parse_template(template: &LitStr) -> Result<Vec<Segment>> {
+    let value: String = template.value();
+    let error = |message: String| -> Error { Error::new(template.span(), message) };
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut rest: &str = &value;
+    while !rest.is_empty() {
+        match rest.find(['{', '}']) {
+            Some(start) if rest[start..].starts_with('}') => return Err(error("unopened `}` in path template".into())),
+            Some(start) => {
+                if start > 0 {
+                    segments.push(Segment::Literal(rest[..start].to_string()));
+                } else if matches!(segments.last(), Some(Segment::Placeholder(_))) {
+                    return Err(error("adjacent placeholders in path template".into()));
+                }
+                let end: usize = start
+                    + rest[start..]
+                        .find('}')
+                        .ok_or_else(|| error("unclosed `{` in path template".into()))?;
+                let name: &str = &rest[start + 1..end];
+                if !name.starts_with(is_ident_start) || !name.chars().all(is_ident_continue) {
+                    return Err(error(format!(
+                        "invalid placeholder `{{{}}}` in path template",
+                        name
+                    )));
+                }
+                if segments
+                    .iter()
+                    .any(|segment: &Segment| matches!(segment, Segment::Placeholder(other) if other == name))
+                {
+                    return Err(error(format!(
+                        "duplicate placeholder `{{{}}}` in path template",
+                        name
+                    )));
+                }
+                segments.push(Segment::Placeholder(name.to_string()));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                segments.push(Segment::Literal(rest.to_string()));
+                rest = "";
+            }
+        }
+    }
+    Ok(segments)
+}
+
+
+// Checks that the placeholders and `fields` name each other.
+pub fn check_fields(template: &LitStr, segments: &[Segment], fields: &[&Ident]) -> Result<()> {
+    for segment in segments {
+        if let Segment::Placeholder(name) = segment {
+            if !fields.iter().any(|field: &&Ident| *field == name) {
+                return Err(Error::new(
+                    template.span(),
+                    format!("no field `{}` for placeholder `{{{}}}`", name, name),
+                ));
+            }
+        }
+    }
+    for field in fields {
+        if !segments
+            .iter()
+            .any(|segment: &Segment| matches!(segment, Segment::Placeholder(name) if *field == name))
+        {
+            return Err(Error::new(
+                field.span(),
+                format!("field `{}` has no placeholder in the path template", field),
+            ));
+        }
+    }
+    Ok(())
+}
+
+
+// The fixed text that ends the placeholder at `index` within its segment, or `""` if the
+// placeholder runs to the end of the segment.
+fn delimiter(segments: &[Segment], index: usize) -> &str {
+    match segments.get(index + 1) {
+        Some(Segment::Literal(literal)) => literal.split('/').next().unwrap(),
+        _ => "",
+    }
+}
+
+
+// Appends the path to `path: String`, percent-encoding each field as a single segment. The
+// characters of the text that follows a placeholder are encoded too, so that `from_path` can end
+// the value at the first occurrence of that text.
+pub fn to_path_generator(krate: &Path, segments: &[Segment]) -> TokenStream2 {
+    let mut path_generator: TokenStream2 = TokenStream2::new();
+    for (i, segment) in segments.iter().enumerate() {
+        path_generator = match segment {
+            Segment::Literal(literal) => {
+                quote! {
+                    #path_generator
+                    path += #literal;
+                }
+            }
+            Segment::Placeholder(name) => {
+                let field_ident: Ident = format_ident!("{}", name);
+                let bytes = delimiter(segments, i).bytes().filter(u8::is_ascii);
+                quote! {
+                    #path_generator
+                    path += &#krate::percent_encoding::utf8_percent_encode(
+                        &self.#field_ident.to_string(),
+                        &#krate::percent_encoding::PATH_SEGMENT #(.add(#bytes))*,
+                    );
+                }
+            }
+        };
+    }
+    path_generator
+}
+
+
+// Reads `path: &str` into `Self`. A placeholder ends at the first occurrence of the following
+// fixed text within its segment, which `to_path` never writes inside a value.
+pub fn from_path_generator(krate: &Path, segments: &[Segment], fields: &[(&Ident, &syn::Type)]) -> TokenStream2 {
+    let mut path_parser: TokenStream2 = quote! {
+        let rest: &::core::primitive::str = path;
+    };
+    for (i, segment) in segments.iter().enumerate() {
+        path_parser = match segment {
+            Segment::Literal(literal) => {
+                quote! {
+                    #path_parser
+                    let rest: &::core::primitive::str = rest.strip_prefix(#literal)?;
+                }
+            }
+            Segment::Placeholder(name) => {
+                let (field_ident, ty) = fields.iter().find(|(field, _)| *field == name).unwrap();
+                let value_ident: Ident = format_ident!("value_{}", field_ident);
+                let delimiter: &str = delimiter(segments, i);
+                let end: TokenStream2 = if delimiter.is_empty() {
+                    quote!(segment.len())
+                } else {
+                    quote!(segment.find(#delimiter)?)
+                };
+                quote! {
+                    #path_parser
+                    let segment: &::core::primitive::str = &rest[..rest.find('/').unwrap_or(rest.len())];
+                    let end: ::core::primitive::usize = #end;
+                    let #value_ident: #ty = #krate::percent_encoding::percent_decode_str(&rest[..end]).parse().ok()?;
+                    let rest: &::core::primitive::str = &rest[end..];
+                }
+            }
+        };
+    }
+    let field_inits = fields.iter().map(|(field_ident, _)| {
+        let value_ident: Ident = format_ident!("value_{}", field_ident);
+        quote!(#field_ident: #value_ident)
+    });
+    quote! {
+        #path_parser
+        if !rest.is_empty() {
+            return ::core::option::Option::None;
+        }
+        ::core::option::Option::Some(Self { #(#field_inits,)* })
+    }
+}
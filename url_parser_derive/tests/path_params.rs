@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use url_parser::PathParams;
+
+
+#[derive(Debug, PartialEq, PathParams)]
+#[path_params("/users/{id}/posts/{post_id}")]
+struct Post {
+    id: u32,
+    post_id: String,
+}
+
+
+#[derive(Debug, PartialEq, PathParams)]
+#[path_params("/files/{name}.{ext}/raw")]
+struct File {
+    name: String,
+    ext: String,
+}
+
+
+#[derive(Debug, PartialEq, PathParams)]
+#[path_params("/v/{a}-{b}-{c}")]
+struct Version {
+    a: String,
+    b: u8,
+    c: String,
+}
+
+
+#[derive(Debug, PartialEq, PathParams)]
+#[path_params("/items/{id}")]
+struct Generic<T> {
+    id: T,
+}
+
+
+mod sdk {
+    pub use url_parser::{percent_encoding, PathParams};
+}
+
+
+#[derive(Debug, PartialEq, PathParams)]
+#[path_params("/{id}", crate = "crate::sdk")]
+struct Reexported {
+    id: u8,
+}
+
+
+#[test]
+fn to_path() {
+    let post: Post = Post {
+        id: 1,
+        post_id: "hello world".to_string(),
+    };
+    assert_eq!(post.to_path(), "/users/1/posts/hello%20world".to_string());
+    let post: Post = Post {
+        id: 2,
+        post_id: "a/b?c%d".to_string(),
+    };
+    assert_eq!(post.to_path(), "/users/2/posts/a%2Fb%3Fc%25d".to_string());
+    let file: File = File {
+        name: "a.b".to_string(),
+        ext: "json".to_string(),
+    };
+    assert_eq!(file.to_path(), "/files/a%2Eb.json/raw".to_string());
+}
+
+
+#[test]
+fn from_path() {
+    assert_eq!(
+        Post::from_path("/users/2/posts/a%2Fb%3Fc%25d"),
+        Some(Post {
+            id: 2,
+            post_id: "a/b?c%d".to_string()
+        }),
+    );
+    assert_eq!(
+        File::from_path("/files/a.tar.gz/raw"),
+        Some(File {
+            name: "a".to_string(),
+            ext: "tar.gz".to_string()
+        }),
+    );
+    assert_eq!(Post::from_path("/users/x/posts/1"), None);
+    assert_eq!(Post::from_path("/users/1/posts/a/b"), None);
+    assert_eq!(Post::from_path("/users/1/comments/1"), None);
+    assert_eq!(File::from_path("/files/a/raw"), None);
+}
+
+
+#[test]
+fn round_trip() {
+    let files: [File; 3] = [
+        File {
+            name: "a.b".to_string(),
+            ext: "json".to_string(),
+        },
+        File {
+            name: "a".to_string(),
+            ext: "tar.gz".to_string(),
+        },
+        File {
+            name: ".x.".to_string(),
+            ext: ".".to_string(),
+        },
+    ];
+    for file in files {
+        assert_eq!(File::from_path(&file.to_path()), Some(file));
+    }
+    let versions: [Version; 2] = [
+        Version {
+            a: "1".to_string(),
+            b: 2,
+            c: "3".to_string(),
+        },
+        Version {
+            a: "x-y".to_string(),
+            b: 0,
+            c: "-rc-1-".to_string(),
+        },
+    ];
+    for version in versions {
+        assert_eq!(Version::from_path(&version.to_path()), Some(version));
+    }
+    assert_eq!(
+        Version::from_path("/v/1-2-3"),
+        Some(Version {
+            a: "1".to_string(),
+            b: 2,
+            c: "3".to_string()
+        }),
+    );
+}
+
+
+#[test]
+fn generic_types() {
+    assert_eq!(Generic { id: 'c' }.to_path(), "/items/c".to_string());
+    assert_eq!(
+        Generic::<u64>::from_path("/items/42"),
+        Some(Generic { id: 42 })
+    );
+}
+
+
+#[test]
+fn crate_override() {
+    assert_eq!(
+        sdk::PathParams::to_path(&Reexported { id: 1 }),
+        "/1".to_string()
+    );
+    assert_eq!(
+        <Reexported as sdk::PathParams>::from_path("/1"),
+        Some(Reexported { id: 1 })
+    );
+}
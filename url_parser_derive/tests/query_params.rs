@@ -4,8 +4,7 @@
 use std::fmt::{Display, Formatter, Result as FormatterResult};
 use std::ptr;
 
-use url_parser_derive::QueryParams;
-use url_parser_trait::QueryParams;
+use url_parser::QueryParams;
 
 
 #[derive(QueryParams)]
@@ -94,6 +93,101 @@ struct CustomTypes {
 }
 
 
+#[derive(QueryParams)]
+struct GenericTypes<'a, T, U> {
+    value: T,
+    opt_value: Option<U>,
+    vec_value: Vec<T>,
+    str: &'a str,
+}
+
+
+#[derive(QueryParams)]
+struct FlattenTypes<T> {
+    key: u8,
+    #[query(flatten)]
+    inner: T,
+    #[query(flatten)]
+    empty: EmptyStruct,
+}
+
+
+trait Tag {
+    fn tag(&self) -> &'static str;
+}
+
+impl Tag for u8 {
+    fn tag(&self) -> &'static str {
+        "byte"
+    }
+}
+
+
+struct Tagged<T>(T);
+
+impl<T: Tag> Display for Tagged<T> {
+    fn fmt(&self, fmt: &mut Formatter) -> FormatterResult {
+        write!(fmt, "{}", self.0.tag())
+    }
+}
+
+
+#[derive(QueryParams)]
+#[query(bound = "T: Tag")]
+struct BoundOverride<T> {
+    tagged: Tagged<T>,
+}
+
+
+#[derive(QueryParams)]
+struct Secrets<'a> {
+    user: &'a str,
+    #[query(secret)]
+    token: String,
+    #[query(secret)]
+    sig: Option<&'a str>,
+    #[query(secret)]
+    keys: Vec<u8>,
+}
+
+
+#[derive(QueryParams)]
+struct FlattenSecrets<'a> {
+    key: u8,
+    #[query(flatten)]
+    inner: Secrets<'a>,
+}
+
+
+mod shadowed {
+    #![allow(dead_code)]
+
+    struct String;
+    struct Default;
+    struct Display;
+
+    #[derive(url_parser::QueryParams)]
+    pub struct Shadowed<T> {
+        pub key: T,
+        pub tuple: (u8, char),
+        pub values: Vec<u8>,
+        pub opt: Option<u8>,
+    }
+}
+
+
+mod sdk {
+    pub use url_parser_trait::QueryParams;
+}
+
+
+#[derive(QueryParams)]
+#[query(crate = "crate::sdk")]
+struct Reexported {
+    key: u8,
+}
+
+
 #[test]
 fn basic_types() {
     let param: BasicTypes = BasicTypes {
@@ -264,3 +358,164 @@ fn custom_type() {
     };
     assert_eq!(param.to_query_params(), "?key=value".to_string());
 }
+
+
+#[test]
+fn generic_types() {
+    let param: GenericTypes<u8, CustomType> = GenericTypes {
+        value: 1,
+        opt_value: Some(CustomType::new("custom")),
+        vec_value: vec![2, 3],
+        str: "str",
+    };
+    assert_eq!(
+        param.to_query_params(),
+        "?value=1&opt_value=custom&vec_value=2,3&str=str".to_string(),
+    );
+}
+
+
+#[test]
+fn flatten_types() {
+    let param1: FlattenTypes<CustomTypes> = FlattenTypes {
+        key: 1,
+        inner: CustomTypes {
+            key: CustomType::new("value"),
+        },
+        empty: EmptyStruct {},
+    };
+    assert_eq!(param1.to_query_params(), "?key=1&key=value".to_string());
+
+    let param2: FlattenTypes<EmptyStruct> = FlattenTypes {
+        key: 1,
+        inner: EmptyStruct {},
+        empty: EmptyStruct {},
+    };
+    assert_eq!(param2.to_query_params(), "?key=1".to_string());
+}
+
+
+#[test]
+fn query_pairs() {
+    let param: Secrets = Secrets {
+        user: "a&b=c",
+        token: "t0k3n".to_string(),
+        sig: None,
+        keys: vec![1, 2],
+    };
+    let pairs = |pairs: &[(&str, &[&str])]| -> Vec<(String, Vec<String>)> {
+        pairs
+            .iter()
+            .map(|(key, values): &(&str, &[&str])| {
+                (
+                    key.to_string(),
+                    values
+                        .iter()
+                        .map(|value: &&str| value.to_string())
+                        .collect(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(
+        param.to_query_pairs(),
+        pairs(&[
+            ("user", &["a&b=c"]),
+            ("token", &["t0k3n"]),
+            ("keys", &["1", "2"])
+        ])
+    );
+    assert_eq!(
+        param.to_redacted_query_pairs(),
+        pairs(&[
+            ("user", &["a&b=c"]),
+            ("token", &["REDACTED"]),
+            ("keys", &["REDACTED"])
+        ])
+    );
+
+    let param: FlattenSecrets = FlattenSecrets {
+        key: 1,
+        inner: param,
+    };
+    assert_eq!(
+        param.to_query_pairs()[..2],
+        pairs(&[("key", &["1"]), ("user", &["a&b=c"])])
+    );
+}
+
+
+#[test]
+fn secret_fields() {
+    let param: Secrets = Secrets {
+        user: "alice",
+        token: "t0k3n".to_string(),
+        sig: None,
+        keys: vec![1, 2],
+    };
+    assert_eq!(
+        param.to_query_params(),
+        "?user=alice&token=t0k3n&keys=1,2".to_string()
+    );
+    assert_eq!(
+        param.to_redacted_query_params(),
+        "?user=alice&token=REDACTED&keys=REDACTED".to_string()
+    );
+
+    let param: FlattenSecrets = FlattenSecrets {
+        key: 1,
+        inner: Secrets {
+            user: "bob",
+            token: String::new(),
+            sig: Some("abc"),
+            keys: Vec::new(),
+        },
+    };
+    assert_eq!(
+        param.to_query_params(),
+        "?key=1&user=bob&sig=abc".to_string()
+    );
+    assert_eq!(
+        param.to_redacted_query_params(),
+        "?key=1&user=bob&sig=REDACTED".to_string()
+    );
+}
+
+
+#[test]
+fn no_secret_fields() {
+    let param: BoundOverride<u8> = BoundOverride { tagged: Tagged(1) };
+    assert_eq!(param.to_redacted_query_params(), param.to_query_params());
+}
+
+
+#[test]
+fn bound_override() {
+    let param: BoundOverride<u8> = BoundOverride { tagged: Tagged(1) };
+    assert_eq!(param.to_query_params(), "?tagged=byte".to_string());
+}
+
+
+#[test]
+fn shadowed_names() {
+    let param: shadowed::Shadowed<u8> = shadowed::Shadowed {
+        key: 1,
+        tuple: (2, 'c'),
+        values: vec![3, 4],
+        opt: Some(5),
+    };
+    assert_eq!(
+        param.to_query_params(),
+        "?key=1&tuple=2,c&values=3,4&opt=5".to_string()
+    );
+}
+
+
+#[test]
+fn crate_override() {
+    let param: Reexported = Reexported { key: 1 };
+    assert_eq!(
+        sdk::QueryParams::to_query_params(&param),
+        "?key=1".to_string()
+    );
+}
@@ -0,0 +1,8 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+#[test]
+fn ui() {
+    let t: trybuild::TestCases = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
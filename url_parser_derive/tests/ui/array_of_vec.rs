@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct ArrayOfVec {
+    array_vec_u8: [Vec<u8>; 2],
+}
+
+fn main() {}
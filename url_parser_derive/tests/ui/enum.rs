@@ -0,0 +1,9 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+enum Enum {
+    A,
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct FnPointer {
+    callback: fn() -> u8,
+}
+
+fn main() {}
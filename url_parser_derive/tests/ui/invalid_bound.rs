@@ -0,0 +1,9 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+#[query(bound = "T Display")]
+struct InvalidBound<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct NestedOption {
+    opt_opt_u8: Option<Option<u8>>,
+}
+
+fn main() {}
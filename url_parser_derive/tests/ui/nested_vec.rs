@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct NestedVec {
+    vec_vec_u8: Vec<Vec<u8>>,
+}
+
+fn main() {}
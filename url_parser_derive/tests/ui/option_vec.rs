@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct OptionVec {
+    opt_vec_u8: Option<Vec<u8>>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use url_parser::PathParams;
+
+#[derive(PathParams)]
+#[path("/users/{id}")]
+struct BuiltinPath {
+    id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use url_parser::PathParams;
+
+#[derive(PathParams)]
+#[path_params("/users/{id}")]
+struct MissingPlaceholder {
+    id: u32,
+    post_id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use url_parser::PathParams;
+
+#[derive(PathParams)]
+struct MissingTemplate {
+    id: u32,
+}
+
+fn main() {}
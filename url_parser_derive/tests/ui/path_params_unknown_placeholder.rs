@@ -0,0 +1,9 @@
+use url_parser::PathParams;
+
+#[derive(PathParams)]
+#[path_params("/users/{user_id}")]
+struct UnknownPlaceholder {
+    id: u32,
+}
+
+fn main() {}
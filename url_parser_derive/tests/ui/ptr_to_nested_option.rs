@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct PtrToNestedOption {
+    ptr_opt_opt_u8: *const Option<Option<u8>>,
+}
+
+fn main() {}
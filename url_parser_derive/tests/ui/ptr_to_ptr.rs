@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct PtrToPtr {
+    ptr_ptr_u8: *const *const u8,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct Inner {
+    key: u8,
+}
+
+#[derive(QueryParams)]
+struct SecretFlatten {
+    #[query(flatten, secret)]
+    inner: Inner,
+}
+
+fn main() {}
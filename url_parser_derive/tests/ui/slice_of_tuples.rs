@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct SliceOfTuples<'a> {
+    slice_tuple: &'a [(u8, u8)],
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct TraitObject<'a> {
+    display: &'a dyn std::fmt::Display,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct TupleOfVec {
+    tuple_vec_u8: (Vec<u8>, u8),
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct TupleStruct(u8, String);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+union Union {
+    a: u8,
+    b: u16,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use url_parser::QueryParams;
+
+#[derive(QueryParams)]
+struct UnknownAttribute {
+    #[query(rename = "other")]
+    key: u8,
+}
+
+fn main() {}
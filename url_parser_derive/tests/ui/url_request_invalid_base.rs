@@ -0,0 +1,10 @@
+use url_parser::UrlRequest;
+
+#[derive(UrlRequest)]
+#[url(base = "https://api.example.com:99999", path = "/items/{id}")]
+struct InvalidBase {
+    #[url(path)]
+    id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use url_parser::UrlRequest;
+
+#[derive(UrlRequest)]
+#[url(path = "/items/{id}")]
+struct MissingBase {
+    #[url(path)]
+    id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use url_parser::UrlRequest;
+
+#[derive(UrlRequest)]
+#[url(base = "https://api.example.com", path = "/items/{id}")]
+struct MissingRole {
+    #[url(path)]
+    id: u32,
+    page: u32,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use url_parser::UrlRequest;
+
+#[derive(UrlRequest)]
+#[url(base = "https://api.example.com", path = "/items/{id}")]
+struct UnmarkedPlaceholder {
+    #[url(query)]
+    id: u32,
+}
+
+fn main() {}
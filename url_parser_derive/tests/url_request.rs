@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2023 Awayume <dev@awayume.jp>
+// SPDX-License-Identifier: Apache-2.0
+
+use url_parser::url::ParseError;
+use url_parser::{QueryParams, UrlRequest};
+
+
+#[derive(UrlRequest)]
+#[url(base = "https://api.example.com/", path = "/v1/items/{id}")]
+struct GetItem<'a> {
+    #[url(path)]
+    id: &'a str,
+    #[url(query)]
+    fields: Vec<&'a str>,
+    #[url(query)]
+    page: Option<u32>,
+    #[url(fragment)]
+    section: Option<&'a str>,
+}
+
+
+#[derive(QueryParams)]
+struct Paging {
+    page: u32,
+    per_page: u32,
+}
+
+
+#[derive(UrlRequest)]
+#[url(base = "https://api.example.com/v1/search")]
+struct Search<T> {
+    #[url(query)]
+    q: T,
+    #[url(query)]
+    #[query(flatten)]
+    paging: Paging,
+}
+
+
+// Passes the checks made when the derive expands, but not the IPv4 parser.
+#[derive(UrlRequest)]
+#[url(base = "https://1.2.3.999")]
+struct InvalidHost {}
+
+
+mod sdk {
+    pub use url_parser::{percent_encoding, url};
+}
+
+
+#[derive(UrlRequest)]
+#[url(base = "https://example.com", path = "/{id}", crate = "crate::sdk")]
+struct Reexported {
+    #[url(path)]
+    id: u8,
+}
+
+
+#[test]
+fn to_url() {
+    let request: GetItem = GetItem {
+        id: "a/b c",
+        fields: vec!["name", "price"],
+        page: Some(2),
+        section: Some("top"),
+    };
+    assert_eq!(
+        request.to_url().unwrap().href(),
+        "https://api.example.com/v1/items/a%2Fb%20c?fields=name,price&page=2#top"
+    );
+    let request: GetItem = GetItem {
+        id: "1",
+        fields: Vec::new(),
+        page: None,
+        section: None,
+    };
+    assert_eq!(
+        request.to_url().unwrap().href(),
+        "https://api.example.com/v1/items/1"
+    );
+    let request: GetItem = GetItem {
+        id: "1",
+        fields: vec!["a,b", "c"],
+        page: None,
+        section: None,
+    };
+    assert_eq!(
+        request.to_url().unwrap().href(),
+        "https://api.example.com/v1/items/1?fields=a%2Cb,c"
+    );
+}
+
+
+#[test]
+fn invalid_host() {
+    assert!(matches!(
+        InvalidHost {}.to_url(),
+        Err(ParseError::Ipv4OutOfRangePart(_))
+    ));
+}
+
+
+#[test]
+fn query_values_are_encoded() {
+    let request: Search<&str> = Search {
+        q: "rust #1",
+        paging: Paging {
+            page: 1,
+            per_page: 20,
+        },
+    };
+    assert_eq!(
+        request.to_url().unwrap().href(),
+        "https://api.example.com/v1/search?q=rust%20%231&page=1&per_page=20"
+    );
+    let request: Search<&str> = Search {
+        q: "x&admin=1",
+        paging: Paging {
+            page: 1,
+            per_page: 20,
+        },
+    };
+    assert_eq!(
+        request.to_url().unwrap().href(),
+        "https://api.example.com/v1/search?q=x%26admin%3D1&page=1&per_page=20"
+    );
+}
+
+
+#[test]
+fn crate_override() {
+    assert_eq!(
+        Reexported { id: 7 }.to_url().unwrap().href(),
+        "https://example.com/7"
+    );
+}
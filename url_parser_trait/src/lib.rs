@@ -2,5 +2,67 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub trait QueryParams {
-    fn to_query_params(&self) -> String;
+    // `?key=value&...`, or `""` without pairs. Keys and the elements of each value are percent-encoded
+    // with the component percent-encode set, and the elements are joined with `,`.
+    fn to_query_params(&self) -> String {
+        encode_query_pairs(&self.to_query_pairs())
+    }
+
+    // The unencoded `(key, values)` pairs of `to_query_params`, with one value per element of a
+    // sequence or tuple, so that each can be percent-encoded on its own.
+    fn to_query_pairs(&self) -> Vec<(String, Vec<String>)>;
+
+    // Like `to_query_params`, with the value of each secret field replaced by `REDACTED`, e.g. for
+    // logging.
+    fn to_redacted_query_params(&self) -> String {
+        encode_query_pairs(&self.to_redacted_query_pairs())
+    }
+
+    // Like `to_query_pairs`, with the value of each secret field replaced by `REDACTED`.
+    fn to_redacted_query_pairs(&self) -> Vec<(String, Vec<String>)> {
+        self.to_query_pairs()
+    }
+}
+
+
+fn encode_query_pairs(pairs: &[(String, Vec<String>)]) -> String {
+    let mut query: String = String::from("?");
+    for (key, values) in pairs {
+        let values: Vec<String> = values
+            .iter()
+            .map(|value: &String| encode_component(value))
+            .collect();
+        query += &format!("{}={}&", encode_component(key), values.join(","));
+    }
+    query.pop();
+    query
+}
+
+
+// https://url.spec.whatwg.org/#component-percent-encode-set
+// This crate cannot depend on `url_parser`, whose `percent_encoding::COMPONENT` is the same set.
+fn encode_component(input: &str) -> String {
+    let mut output: String = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || matches!(
+                byte,
+                b'!' | b'\'' | b'(' | b')' | b'*' | b'-' | b'.' | b'_' | b'~'
+            )
+        {
+            output.push(byte as char);
+        } else {
+            output += &format!("%{:02X}", byte);
+        }
+    }
+    output
+}
+
+
+// A path built from a template like `/users/{id}`, with each placeholder filled by a field.
+pub trait PathParams: Sized {
+    fn to_path(&self) -> String;
+
+    // `None` if the path does not fit the template or a segment does not parse as its field.
+    fn from_path(path: &str) -> Option<Self>;
 }